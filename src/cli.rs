@@ -1,4 +1,5 @@
-use clap::{Parser, ValueEnum};
+use crate::{quake3_parser::timestamps::TimestampFormat, scoring::ScoringPreset};
+use clap::{Args, Parser, Subcommand, ValueEnum};
 use std::path::PathBuf;
 
 #[derive(Clone, Debug, ValueEnum, PartialEq, Eq)]
@@ -6,6 +7,11 @@ use std::path::PathBuf;
 /// - Report with player ranking and mean of death ranking
 /// - Report with player ranking
 /// - Report with mean of death ranking
+/// - Report with item pickups per player
+/// - Report with time played per player
+/// - Report with kills, team kills and score per team
+/// - Report with the kills of each player on each other player
+/// - Report with the standings of the players across all games
 pub enum ReportType {
     /// Player kill score ranking + mean of death ranking
     All,
@@ -13,49 +19,240 @@ pub enum ReportType {
     PlayerRank,
     /// Only mean of death ranking
     MeanDeath,
+    /// Item pickups per player, grouped by item class
+    ItemPickups,
+    /// Time played and kills per minute per player
+    TimePlayed,
+    /// Kills, team kills and final score per team, in team games
+    Teams,
+    /// Kills of each player on each other player, who killed whom
+    KillMatrix,
+    /// Standings of the players across all games, a single ranking table
+    Leaderboard,
+}
+
+#[derive(Clone, Debug, ValueEnum, PartialEq, Eq)]
+/// Metric to rank the players by in every report:
+/// - Net score, frags minus suicides, world deaths and team kills
+/// - Frags, deaths, suicides, world deaths or team kills
+/// - Frags per death
+pub enum RankBy {
+    /// Net score, frags minus suicides, world deaths and team kills
+    Score,
+    /// Enemies killed
+    Frags,
+    /// Deaths, whatever the cause
+    Deaths,
+    /// Times the player killed itself
+    Suicides,
+    /// Times the world killed the player
+    WorldDeaths,
+    /// Teammates killed, in team games
+    TeamKills,
+    /// Frags per death
+    Kd,
 }
 
 #[derive(Clone, Debug, ValueEnum, PartialEq, Eq)]
 /// Format of report to generate:
 /// - Text table report in console
 /// - Html table report
+/// - Json report, for scripts and other tools
 pub enum ReportFormat {
     /// HTML table report
     Html,
     /// Text console report with tabled crate
     Text,
+    /// JSON report following a versioned schema
+    Json,
 }
 
 #[derive(Parser, Debug)]
-#[command(version, about, long_about = None)]
+#[command(version, about, long_about = None, args_conflicts_with_subcommands = true)]
 /// The CLI struct
 /// Defines the declarative CLI interface using the `clap` crate
+///
+/// Without a command it reports on the games of the log file
 pub struct Cli {
-    /// The path to the log file, required
-    pub log_file: PathBuf,
+    #[command(subcommand)]
+    /// The command to run instead of the report
+    pub command: Option<Command>,
+
+    #[arg(required = true)]
+    /// The log files to report on, required
+    /// - a log file
+    /// - a directory, for its `*.log` files and their rotations (`*.log.1`, `*.log.2`...)
+    /// - a glob pattern, quoted (e.g. `'logs/*.log'`)
+    /// - `-` for the standard input
+    ///
+    ///   The games are merged log by log, ordered by path with the rotations of a log
+    ///   before it (`games.log.2`, `games.log.1`, `games.log`), and the standard input last
+    pub log_files: Vec<PathBuf>,
 
     #[arg(short, long, value_enum, default_value = "all")]
     /// The type of report to generate
     /// - Report with player ranking and mean of death ranking
     /// - Report with player ranking
     /// - Report with mean of death ranking
-    /// Default: all
+    /// - Report with item pickups per player
+    /// - Report with time played per player
+    /// - Report with kills, team kills and score per team
+    /// - Report with the kills of each player on each other player
+    /// - Report with the standings of the players across all games
+    ///   Default: all
     pub report_type: ReportType,
 
     #[arg(short = 'f', long, value_enum, default_value = "text")]
     /// The format of the report to generate
     /// - Text table report in console
     /// - Html table report
-    /// Default: text
+    /// - Json report, for scripts and other tools
+    ///   Default: text
     pub report_format: ReportFormat,
 
+    #[arg(long, value_enum, default_value = "score")]
+    /// The metric to rank the players by
+    /// - Net score, frags minus suicides, world deaths and team kills
+    /// - Frags, deaths, suicides, world deaths or team kills
+    /// - Frags per death
+    ///   Default: score
+    pub rank_by: RankBy,
+
+    #[command(flatten)]
+    /// How to score the players
+    pub scoring: ScoringArgs,
+
+    #[arg(long)]
+    /// Leave out of the report the games that never reached their shutdown,
+    /// e.g. after a server crash or when the log was cut
+    pub hide_incomplete: bool,
+
+    #[command(flatten)]
+    /// How to parse the log file
+    pub parse: ParseArgs,
+
     #[arg(short, long, value_name = "FILE")]
     /// The output file to write the report
     /// If not provided, the report will be printed to the console
     pub output_file: Option<PathBuf>,
 }
 
+#[derive(Args, Debug, Clone, PartialEq, Eq)]
+/// The options to parse the log file with, shared by the report and the commands
+pub struct ParseArgs {
+    #[arg(long, value_enum, default_value = "auto")]
+    /// The timestamp prefix of the log lines
+    /// - Detected from the first line with a timestamp
+    /// - The classic `MM:SS` time since the server started
+    /// - An ISO-8601 date and time
+    /// - Unix epoch seconds
+    ///   Default: auto
+    pub timestamps: TimestampFormat,
+
+    #[arg(long)]
+    /// Stop at the first line that can't be parsed
+    /// instead of skipping it with a warning
+    pub strict: bool,
+}
+
+#[derive(Args, Debug, Clone, PartialEq, Eq)]
+/// The options to score the players with, shared by the report and the `follow` command
+pub struct ScoringArgs {
+    #[arg(
+        long = "scoring",
+        value_name = "SCORING",
+        value_enum,
+        default_value = "quake3"
+    )]
+    /// The scoring rules preset, following the server mod
+    /// - Quake 3 (baseq3)
+    ///   Default: quake3
+    ///
+    /// There are no OSP or CPMA presets, use a scoring config for their rules
+    pub preset: ScoringPreset,
+
+    #[arg(long, value_name = "FILE")]
+    /// A json file with the points of each kind of kill,
+    /// replacing the ones of the scoring preset
+    pub scoring_config: Option<PathBuf>,
+
+    #[arg(long)]
+    /// Rank players by the scores printed by the server at the end of each game
+    /// instead of the scores computed from the kills
+    pub server_scores: bool,
+}
+
+#[derive(Subcommand, Debug)]
+/// The commands besides the report
+pub enum Command {
+    /// Store the games of a log file in a `SQLite` database,
+    /// reading only the lines appended since its last ingestion
+    Ingest(IngestArgs),
+    /// Follow a log file still being written, like `tail -F`,
+    /// printing the report of each game as soon as it ends
+    Follow(FollowArgs),
+}
+
+#[derive(Args, Debug)]
+/// The arguments of the `ingest` command
+pub struct IngestArgs {
+    /// The path to the log file, required
+    pub log_file: PathBuf,
+
+    #[arg(long, value_name = "FILE", default_value = "wolf_quake.db")]
+    /// The `SQLite` database to store the games in, created if it doesn't exist
+    pub database: PathBuf,
+
+    #[arg(long)]
+    /// Read the whole log again, instead of resuming from the checkpoint of the last ingestion
+    pub from_start: bool,
+
+    #[command(flatten)]
+    /// How to parse the log file
+    pub parse: ParseArgs,
+}
+
+#[derive(Args, Debug)]
+/// The arguments of the `follow` command
+pub struct FollowArgs {
+    /// The path to the log file, required
+    pub log_file: PathBuf,
+
+    #[arg(long)]
+    /// Print every kill of the running game as it happens
+    pub kill_feed: bool,
+
+    #[arg(short, long, value_enum, default_value = "all")]
+    /// The type of report printed at the end of each game, see the report options
+    ///   Default: all
+    pub report_type: ReportType,
+
+    #[arg(long, value_enum, default_value = "score")]
+    /// The metric to rank the players by, see the report options
+    ///   Default: score
+    pub rank_by: RankBy,
+
+    #[arg(long, value_name = "MILLIS", default_value = "500")]
+    /// How often the log is checked for new lines, in milliseconds
+    pub interval: u64,
+
+    #[command(flatten)]
+    /// How to score the players
+    pub scoring: ScoringArgs,
+
+    #[command(flatten)]
+    /// How to parse the log file
+    pub parse: ParseArgs,
+}
+
 #[cfg(test)]
+#[allow(
+    clippy::assertions_on_result_states,
+    clippy::panic,
+    clippy::shadow_unrelated,
+    clippy::unwrap_used
+)]
+// the tests assert on the parse results and reuse `cmd` for every command line
 mod tests {
     use super::*;
     use proptest::prelude::*;
@@ -65,11 +262,32 @@ mod tests {
             Just(ReportType::All),
             Just(ReportType::PlayerRank),
             Just(ReportType::MeanDeath),
+            Just(ReportType::ItemPickups),
+            Just(ReportType::TimePlayed),
+            Just(ReportType::Teams),
+            Just(ReportType::KillMatrix),
+            Just(ReportType::Leaderboard),
+        ]
+    }
+
+    fn rank_by() -> impl Strategy<Value = RankBy> {
+        prop_oneof![
+            Just(RankBy::Score),
+            Just(RankBy::Frags),
+            Just(RankBy::Deaths),
+            Just(RankBy::Suicides),
+            Just(RankBy::WorldDeaths),
+            Just(RankBy::TeamKills),
+            Just(RankBy::Kd),
         ]
     }
 
     fn report_format() -> impl Strategy<Value = ReportFormat> {
-        prop_oneof![Just(ReportFormat::Html), Just(ReportFormat::Text)]
+        prop_oneof![
+            Just(ReportFormat::Html),
+            Just(ReportFormat::Text),
+            Just(ReportFormat::Json)
+        ]
     }
 
     proptest! {
@@ -77,11 +295,109 @@ mod tests {
         fn verify_cmd_default(
             log_file in "\\w+"
         ) {
-            let cmd = Cli::parse_from(&["test", &log_file]);
-            assert_eq!(cmd.log_file, PathBuf::from(&log_file));
+            let cmd = Cli::parse_from(["test", &log_file]);
+            assert!(cmd.command.is_none());
+            assert_eq!(cmd.log_files, vec![PathBuf::from(&log_file)]);
             assert_eq!(cmd.report_type, ReportType::All);
             assert_eq!(cmd.report_format, ReportFormat::Text);
             assert_eq!(cmd.output_file, None);
+            assert!(!cmd.scoring.server_scores);
+            assert!(!cmd.parse.strict);
+            assert!(!cmd.hide_incomplete);
+            assert_eq!(cmd.rank_by, RankBy::Score);
+            assert_eq!(cmd.scoring.preset, ScoringPreset::Quake3);
+            assert_eq!(cmd.scoring.scoring_config, None);
+            assert_eq!(cmd.parse.timestamps, TimestampFormat::Auto);
+        }
+    }
+
+    proptest! {
+    #[test]
+        fn verify_cmd_with_scoring(
+            log_file in "\\w+",
+            config_file in "\\w+",
+        ) {
+            let cmd = Cli::parse_from(["test", &log_file, "--scoring", "quake3"]);
+            assert_eq!(cmd.scoring.preset, ScoringPreset::Quake3);
+
+            let cmd = Cli::parse_from(["test", &log_file, "--scoring-config", &config_file]);
+            assert_eq!(cmd.log_files, vec![PathBuf::from(&log_file)]);
+            assert_eq!(cmd.scoring.scoring_config, Some(PathBuf::from(&config_file)));
+
+            assert!(Cli::try_parse_from(["test", &log_file, "--scoring", "cpma"]).is_err());
+        }
+    }
+
+    proptest! {
+    #[test]
+        fn verify_cmd_with_rank_by(
+            log_file in "\\w+",
+            rank_by in rank_by(),
+        ) {
+            let arg_text = match rank_by {
+                RankBy::Score => "score",
+                RankBy::Frags => "frags",
+                RankBy::Deaths => "deaths",
+                RankBy::Suicides => "suicides",
+                RankBy::WorldDeaths => "world-deaths",
+                RankBy::TeamKills => "team-kills",
+                RankBy::Kd => "kd",
+            };
+            let cmd = Cli::parse_from(["test", &log_file, "--rank-by", arg_text]);
+            assert_eq!(cmd.log_files, vec![PathBuf::from(&log_file)]);
+            assert_eq!(cmd.rank_by, rank_by);
+            assert_eq!(cmd.report_type, ReportType::All);
+        }
+    }
+
+    proptest! {
+    #[test]
+        fn verify_cmd_with_server_scores(
+            log_file in "\\w+"
+        ) {
+            let cmd = Cli::parse_from(["test", &log_file, "--server-scores"]);
+            assert_eq!(cmd.log_files, vec![PathBuf::from(&log_file)]);
+            assert!(cmd.scoring.server_scores);
+        }
+    }
+
+    proptest! {
+    #[test]
+        fn verify_cmd_with_strict(
+            log_file in "\\w+"
+        ) {
+            let cmd = Cli::parse_from(["test", &log_file, "--strict"]);
+            assert_eq!(cmd.log_files, vec![PathBuf::from(&log_file)]);
+            assert!(cmd.parse.strict);
+        }
+    }
+
+    proptest! {
+    #[test]
+        fn verify_cmd_with_timestamps(
+            log_file in "\\w+"
+        ) {
+            for (arg_text, format) in [
+                ("auto", TimestampFormat::Auto),
+                ("relative", TimestampFormat::Relative),
+                ("iso8601", TimestampFormat::Iso8601),
+                ("epoch", TimestampFormat::Epoch),
+            ] {
+                let cmd = Cli::parse_from(["test", &log_file, "--timestamps", arg_text]);
+                assert_eq!(cmd.log_files, vec![PathBuf::from(&log_file)]);
+                assert_eq!(cmd.parse.timestamps, format);
+            }
+        }
+    }
+
+    proptest! {
+    #[test]
+        fn verify_cmd_with_hide_incomplete(
+            log_file in "\\w+"
+        ) {
+            let cmd = Cli::parse_from(["test", &log_file, "--hide-incomplete"]);
+            assert_eq!(cmd.log_files, vec![PathBuf::from(&log_file)]);
+            assert!(cmd.hide_incomplete);
         }
     }
 
@@ -90,14 +406,131 @@ mod tests {
         fn verify_cmd_default_flag_like_log_file(
             log_file in "--\\PC*"
         ) {
-            let cmd = Cli::try_parse_from(&["test", &log_file]);
+            let cmd = Cli::try_parse_from(["test", &log_file]);
             assert!(cmd.is_err());
         }
     }
 
+    proptest! {
+    #[test]
+        fn verify_cmd_ingest(
+            log_file in "\\w+",
+            database in "\\w+",
+        ) {
+            let cmd = Cli::parse_from(["test", "ingest", &log_file]);
+            assert!(cmd.log_files.is_empty());
+            let Some(Command::Ingest(args)) = cmd.command else {
+                panic!("ingest should be parsed as a command");
+            };
+            assert_eq!(args.log_file, PathBuf::from(&log_file));
+            assert_eq!(args.database, PathBuf::from("wolf_quake.db"));
+            assert_eq!(args.parse.timestamps, TimestampFormat::Auto);
+            assert!(!args.parse.strict);
+            assert!(!args.from_start);
+
+            let cmd = Cli::parse_from(
+                ["test", "ingest", &log_file, "--database", &database, "--strict", "--timestamps", "epoch", "--from-start"]
+            );
+            let Some(Command::Ingest(args)) = cmd.command else {
+                panic!("ingest should be parsed as a command");
+            };
+            assert_eq!(args.database, PathBuf::from(&database));
+            assert_eq!(args.parse.timestamps, TimestampFormat::Epoch);
+            assert!(args.parse.strict);
+            assert!(args.from_start);
+        }
+    }
+
+    #[test]
+    fn verify_cmd_follow() {
+        let cmd = Cli::parse_from(["test", "follow", "games.log"]);
+        let Some(Command::Follow(args)) = cmd.command else {
+            panic!("follow should be parsed as a command");
+        };
+        assert_eq!(args.log_file, PathBuf::from("games.log"));
+        assert!(!args.kill_feed);
+        assert_eq!(args.report_type, ReportType::All);
+        assert_eq!(args.rank_by, RankBy::Score);
+        assert_eq!(args.interval, 500);
+        assert_eq!(args.scoring.preset, ScoringPreset::Quake3);
+        assert_eq!(args.scoring.scoring_config, None);
+        assert!(!args.scoring.server_scores);
+
+        let cmd = Cli::parse_from([
+            "test",
+            "follow",
+            "games.log",
+            "--kill-feed",
+            "-r",
+            "kill-matrix",
+            "--rank-by",
+            "kd",
+            "--interval",
+            "2000",
+            "--strict",
+            "--scoring-config",
+            "league.json",
+            "--server-scores",
+        ]);
+        let Some(Command::Follow(args)) = cmd.command else {
+            panic!("follow should be parsed as a command");
+        };
+        assert!(args.kill_feed);
+        assert_eq!(args.report_type, ReportType::KillMatrix);
+        assert_eq!(args.rank_by, RankBy::Kd);
+        assert_eq!(args.interval, 2000);
+        assert!(args.parse.strict);
+        assert_eq!(
+            args.scoring.scoring_config,
+            Some(PathBuf::from("league.json"))
+        );
+        assert!(args.scoring.server_scores);
+
+        assert!(Cli::try_parse_from(["test", "follow"]).is_err());
+        assert!(
+            Cli::try_parse_from(["test", "follow", "games.log", "--interval", "soon"]).is_err()
+        );
+    }
+
+    #[test]
+    fn verify_cmd_ingest_errors() {
+        assert!(Cli::try_parse_from(["test", "ingest"]).is_err());
+        // the report options don't go with the commands,
+        // after them a command name is only one of the log files
+        let cmd = Cli::try_parse_from(["test", "-r", "teams", "ingest", "games.log"]).unwrap();
+        assert!(cmd.command.is_none());
+        assert_eq!(cmd.log_files.len(), 2);
+        assert!(Cli::try_parse_from(["test", "ingest", "games.log", "-r", "teams"]).is_err());
+        assert!(Cli::try_parse_from(["test"]).is_err());
+    }
+
+    #[test]
+    fn verify_cmd_many_log_files() {
+        let cmd = Cli::parse_from([
+            "test",
+            "games.log.1",
+            "games.log",
+            "-",
+            "logs/*.log",
+            "-r",
+            "leaderboard",
+        ]);
+        assert!(cmd.command.is_none());
+        assert_eq!(
+            cmd.log_files,
+            vec![
+                PathBuf::from("games.log.1"),
+                PathBuf::from("games.log"),
+                PathBuf::from("-"),
+                PathBuf::from("logs/*.log"),
+            ]
+        );
+        assert_eq!(cmd.report_type, ReportType::Leaderboard);
+    }
+
     #[test]
     fn verify_cmd_default_empty_log_file() {
-        let cmd = Cli::try_parse_from(&["test", ""]);
+        let cmd = Cli::try_parse_from(["test", ""]);
         assert!(cmd.is_err());
     }
 
@@ -117,15 +550,30 @@ mod tests {
                 ReportType::MeanDeath => {
                     "mean-death"
                 }
+                ReportType::ItemPickups => {
+                    "item-pickups"
+                }
+                ReportType::TimePlayed => {
+                    "time-played"
+                }
+                ReportType::Teams => {
+                    "teams"
+                }
+                ReportType::KillMatrix => {
+                    "kill-matrix"
+                }
+                ReportType::Leaderboard => {
+                    "leaderboard"
+                }
             };
-            let cmd = Cli::parse_from(&["test", &log_file, "--report-type", arg_text]);
-            assert_eq!(cmd.log_file, PathBuf::from(&log_file));
+            let cmd = Cli::parse_from(["test", &log_file, "--report-type", arg_text]);
+            assert_eq!(cmd.log_files, vec![PathBuf::from(&log_file)]);
             assert_eq!(cmd.report_type, report_type);
             assert_eq!(cmd.report_format, ReportFormat::Text);
             assert_eq!(cmd.output_file, None);
 
-            let cmd = Cli::parse_from(&["test", &log_file, "-r", arg_text]);
-            assert_eq!(cmd.log_file, PathBuf::from(&log_file));
+            let cmd = Cli::parse_from(["test", &log_file, "-r", arg_text]);
+            assert_eq!(cmd.log_files, vec![PathBuf::from(&log_file)]);
             assert_eq!(cmd.report_type, report_type);
             assert_eq!(cmd.report_format, ReportFormat::Text);
             assert_eq!(cmd.output_file, None);
@@ -145,15 +593,18 @@ mod tests {
                 ReportFormat::Text => {
                     "text"
                 }
+                ReportFormat::Json => {
+                    "json"
+                }
             };
-            let cmd = Cli::parse_from(&["test", &log_file, "--report-format", arg_text]);
-            assert_eq!(cmd.log_file, PathBuf::from(&log_file));
+            let cmd = Cli::parse_from(["test", &log_file, "--report-format", arg_text]);
+            assert_eq!(cmd.log_files, vec![PathBuf::from(&log_file)]);
             assert_eq!(cmd.report_type, ReportType::All);
             assert_eq!(cmd.report_format, report_format);
             assert_eq!(cmd.output_file, None);
 
-            let cmd = Cli::parse_from(&["test", &log_file, "-f", arg_text]);
-            assert_eq!(cmd.log_file, PathBuf::from(&log_file));
+            let cmd = Cli::parse_from(["test", &log_file, "-f", arg_text]);
+            assert_eq!(cmd.log_files, vec![PathBuf::from(&log_file)]);
             assert_eq!(cmd.report_type, ReportType::All);
             assert_eq!(cmd.report_format, report_format);
             assert_eq!(cmd.output_file, None);
@@ -166,14 +617,14 @@ mod tests {
             log_file in "\\w+",
             output_file in "\\w+"
         ) {
-            let cmd = Cli::parse_from(&["test", &log_file, "--output-file", &output_file]);
-            assert_eq!(cmd.log_file, PathBuf::from(&log_file));
+            let cmd = Cli::parse_from(["test", &log_file, "--output-file", &output_file]);
+            assert_eq!(cmd.log_files, vec![PathBuf::from(&log_file)]);
             assert_eq!(cmd.report_type, ReportType::All);
             assert_eq!(cmd.report_format, ReportFormat::Text);
             assert_eq!(cmd.output_file, Some(PathBuf::from(&output_file)));
 
-            let cmd = Cli::parse_from(&["test", &log_file, "-o", &output_file]);
-            assert_eq!(cmd.log_file, PathBuf::from(&log_file));
+            let cmd = Cli::parse_from(["test", &log_file, "-o", &output_file]);
+            assert_eq!(cmd.log_files, vec![PathBuf::from(&log_file)]);
             assert_eq!(cmd.report_type, ReportType::All);
             assert_eq!(cmd.report_format, ReportFormat::Text);
             assert_eq!(cmd.output_file, Some(PathBuf::from(&output_file)));
@@ -186,7 +637,7 @@ mod tests {
             log_file in "\\w+",
             output_file in "--\\PC*"
         ) {
-            let cmd = Cli::try_parse_from(&["test", &log_file, "--output-file", &output_file]);
+            let cmd = Cli::try_parse_from(["test", &log_file, "--output-file", &output_file]);
             assert!(cmd.is_err());
         }
     }
@@ -196,7 +647,7 @@ mod tests {
         fn verify_cmd_with_empty_output_file(
             log_file in "\\w+",
         ) {
-            let cmd = Cli::try_parse_from(&["test", &log_file, "--output-file", ""]);
+            let cmd = Cli::try_parse_from(["test", &log_file, "--output-file", ""]);
             assert!(cmd.is_err());
         }
     }
@@ -219,6 +670,21 @@ mod tests {
                 ReportType::MeanDeath => {
                     "mean-death"
                 }
+                ReportType::ItemPickups => {
+                    "item-pickups"
+                }
+                ReportType::TimePlayed => {
+                    "time-played"
+                }
+                ReportType::Teams => {
+                    "teams"
+                }
+                ReportType::KillMatrix => {
+                    "kill-matrix"
+                }
+                ReportType::Leaderboard => {
+                    "leaderboard"
+                }
             };
 
             let format_text = match report_format {
@@ -228,20 +694,23 @@ mod tests {
                 ReportFormat::Text => {
                     "text"
                 }
+                ReportFormat::Json => {
+                    "json"
+                }
             };
 
             let cmd = Cli::parse_from(
-                &["test", &log_file, "--report-type", type_text, "--report-format", format_text, "--output-file", &output_file]
+                ["test", &log_file, "--report-type", type_text, "--report-format", format_text, "--output-file", &output_file]
             );
-            assert_eq!(cmd.log_file, PathBuf::from(&log_file));
+            assert_eq!(cmd.log_files, vec![PathBuf::from(&log_file)]);
             assert_eq!(cmd.report_type, report_type);
             assert_eq!(cmd.report_format, report_format);
             assert_eq!(cmd.output_file, Some(PathBuf::from(&output_file)));
 
             let cmd = Cli::parse_from(
-                &["test", &log_file, "-r", type_text, "-f", format_text, "-o", &output_file]
+                ["test", &log_file, "-r", type_text, "-f", format_text, "-o", &output_file]
             );
-            assert_eq!(cmd.log_file, PathBuf::from(&log_file));
+            assert_eq!(cmd.log_files, vec![PathBuf::from(&log_file)]);
             assert_eq!(cmd.report_type, report_type);
             assert_eq!(cmd.report_format, report_format);
             assert_eq!(cmd.output_file, Some(PathBuf::from(&output_file)));
@@ -0,0 +1,289 @@
+use crate::{
+    incremental::{FileState, Resumed},
+    quake3_parser::{
+        errors::ParsingError,
+        parser::{
+            is_recoverable, resume_reader, Game, GameStream, KillFeedEntry, ParseCheckpoint,
+            ParseMode,
+        },
+        timestamps::TimestampFormat,
+    },
+};
+use std::{
+    collections::VecDeque,
+    fs::File,
+    io::BufReader,
+    path::{Path, PathBuf},
+};
+
+#[derive(Debug)]
+/// What happened in a followed log, see `LogFollower::next_update`
+pub enum LiveUpdate {
+    /// A player was killed in the running game, only when the kill feed is kept
+    Kill(KillFeedEntry),
+    /// A game ended
+    GameEnded {
+        /// The game, as reported at its `ShutdownGame:` line
+        game: Box<Game>,
+        /// The number of the game in the log, starting at 1
+        game_number: usize,
+    },
+    /// A line was skipped, in lenient mode
+    Skipped(ParsingError),
+    /// The log was rotated or truncated, and is followed again from its start
+    Reopened(Resumed),
+}
+
+#[derive(Debug)]
+/// Follows a log still being written, like `tail -F`
+///
+/// The lines appended to the log go through the same parser as a whole log,
+/// each game comes out as soon as its `ShutdownGame:` line is written.
+/// When the log is rotated or truncated it's followed again from its start
+pub struct LogFollower {
+    /// The path of the followed log, reopened when the log is rotated
+    path: PathBuf,
+    /// How the bad lines are handled
+    mode: ParseMode,
+    /// The timestamp format of the log, to reopen it with
+    format: TimestampFormat,
+    /// Whether the kills of the running game are reported
+    kill_feed: bool,
+    /// The inode of the file being read, to detect the log rotation
+    inode: Option<u64>,
+    /// The games of the file being read
+    stream: GameStream<BufReader<File>>,
+    /// The number of games finished in the file being read
+    games_read: usize,
+    /// The updates read but not reported yet, oldest first
+    pending: VecDeque<LiveUpdate>,
+}
+
+impl LogFollower {
+    /// Opens the log and reads it up to its end, to catch up with the running game
+    /// the games that already ended and their kills aren't reported,
+    /// the lines skipped on the way are the first updates
+    ///
+    /// can error if the log can't be read, or at the first bad line in strict mode
+    pub fn open(
+        path: &Path,
+        mode: ParseMode,
+        format: TimestampFormat,
+        kill_feed: bool,
+    ) -> Result<Self, ParsingError> {
+        let (inode, mut stream) = open_stream(path, mode, format, kill_feed)?;
+        let caught_up = stream.parse_available()?;
+        stream.take_kill_feed();
+        Ok(Self {
+            path: path.to_path_buf(),
+            mode,
+            format,
+            kill_feed,
+            inode,
+            stream,
+            games_read: caught_up.games.len(),
+            pending: caught_up
+                .diagnostics
+                .into_iter()
+                .map(LiveUpdate::Skipped)
+                .collect(),
+        })
+    }
+
+    /// Returns what happened in the log since the previous update,
+    /// `None` once every complete line written so far was read
+    ///
+    /// can error if the log can't be read, or at the first bad line in strict mode
+    pub fn next_update(&mut self) -> Result<Option<LiveUpdate>, ParsingError> {
+        if let Some(update) = self.pending.pop_front() {
+            return Ok(Some(update));
+        }
+
+        let next = self.stream.next();
+        self.pending.extend(
+            self.stream
+                .take_kill_feed()
+                .into_iter()
+                .map(LiveUpdate::Kill),
+        );
+        match next {
+            Some(Ok(game)) => {
+                self.games_read = self.games_read.saturating_add(1);
+                self.pending.push_back(LiveUpdate::GameEnded {
+                    game: Box::new(game),
+                    game_number: self.games_read,
+                });
+            }
+            Some(Err(err)) if self.mode == ParseMode::Lenient && is_recoverable(&err) => {
+                self.pending.push_back(LiveUpdate::Skipped(err));
+            }
+            Some(Err(err)) => return Err(err),
+            None if self.pending.is_empty() => {
+                if let Some(changed) = self.changed()? {
+                    let (inode, stream) =
+                        open_stream(&self.path, self.mode, self.format, self.kill_feed)?;
+                    self.inode = inode;
+                    self.stream = stream;
+                    self.games_read = 0;
+                    self.pending.push_back(LiveUpdate::Reopened(changed));
+                }
+            }
+            None => {}
+        }
+        Ok(self.pending.pop_front())
+    }
+
+    /// Returns whether the log was rotated or truncated since the file being read was opened
+    /// a log that's missing, in the middle of its rotation, didn't change yet
+    fn changed(&self) -> Result<Option<Resumed>, ParsingError> {
+        let metadata = match std::fs::metadata(&self.path) {
+            Ok(metadata) => metadata,
+            Err(err) if err.kind() == std::io::ErrorKind::NotFound => return Ok(None),
+            Err(err) => return Err(err.into()),
+        };
+        let offset = self.stream.log_position().offset;
+        Ok(FileState::of(&metadata)
+            .changed_since(self.inode, u64::try_from(offset).unwrap_or(u64::MAX)))
+    }
+}
+
+/// Opens the log from its start, returning the inode of its file and its games
+/// the game still running at the end of the log is kept, as more lines are expected
+fn open_stream(
+    path: &Path,
+    mode: ParseMode,
+    format: TimestampFormat,
+    kill_feed: bool,
+) -> Result<(Option<u64>, GameStream<BufReader<File>>), ParsingError> {
+    let file = File::open(path)?;
+    let inode = FileState::of(&file.metadata()?).inode;
+    let stream = resume_reader(BufReader::new(file), mode, ParseCheckpoint::new(format));
+    let stream = if kill_feed {
+        stream.with_kill_feed()
+    } else {
+        stream
+    };
+    Ok((inode, stream))
+}
+
+#[cfg(test)]
+#[allow(clippy::unwrap_used)]
+// a failing unwrap fails the test, with the error
+mod tests {
+    use super::*;
+    use crate::test_support::{append, log_path, started_log};
+
+    /// The end of the running game
+    const ENDED: &str = "  0:05 Kill: 1022 3 22: <world> killed Zeh by MOD_TRIGGER_HURT\n\
+        0:06 Item: 3 weapon_rocketlauncher\n\
+        0:07 Kill: 3 3 7: Zeh killed Zeh by MOD_ROCKET_SPLASH\n\
+        0:30 ShutdownGame:\n";
+
+    /// Returns the updates available, as text
+    fn updates(follower: &mut LogFollower) -> Vec<String> {
+        std::iter::from_fn(|| follower.next_update().unwrap())
+            .map(|update| match update {
+                LiveUpdate::Kill(kill) => kill.to_string(),
+                LiveUpdate::GameEnded { game, game_number } => format!(
+                    "Game {game_number} on {} ended",
+                    game.settings.map_name.unwrap_or_default()
+                ),
+                LiveUpdate::Skipped(err) => format!("Skipped {err}"),
+                LiveUpdate::Reopened(changed) => format!("Reopened {changed:?}"),
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_follow_appended_games() {
+        let path = log_path("follow", "appended");
+        append(&path, &started_log());
+        let mut follower =
+            LogFollower::open(&path, ParseMode::Lenient, TimestampFormat::Auto, true).unwrap();
+        // the game that already ended isn't reported
+        assert!(updates(&mut follower).is_empty());
+
+        // a line is reported once complete
+        let (first, rest) = ENDED.split_at(20);
+        append(&path, first);
+        assert!(updates(&mut follower).is_empty());
+        append(&path, rest);
+        assert_eq!(
+            updates(&mut follower),
+            vec![
+                "00:05 <world> killed Zeh by TriggerHurt",
+                "00:07 Zeh killed Zeh by Rocket Splash",
+                "Game 2 on q3dm6 ended",
+            ]
+        );
+
+        append(&path, "  0:31 Kill: 7 8 7: oops\n");
+        let skipped = updates(&mut follower);
+        assert_eq!(skipped.len(), 1);
+        assert!(skipped[0].starts_with("Skipped"));
+
+        std::fs::remove_file(&path).unwrap();
+    }
+
+    #[test]
+    fn test_follow_reports_lines_skipped_while_catching_up() {
+        let path = log_path("follow", "caught_up");
+        append(&path, "  0:00 Kill: 7 8 7: oops\n");
+        append(&path, &started_log());
+        let mut follower =
+            LogFollower::open(&path, ParseMode::Lenient, TimestampFormat::Auto, false).unwrap();
+        let skipped = updates(&mut follower);
+        assert_eq!(skipped.len(), 1);
+        assert!(skipped[0].starts_with("Skipped"));
+
+        append(&path, ENDED);
+        assert_eq!(updates(&mut follower), vec!["Game 2 on q3dm6 ended"]);
+
+        std::fs::remove_file(&path).unwrap();
+    }
+
+    #[test]
+    fn test_follow_truncated_log() {
+        let path = log_path("follow", "truncated");
+        append(&path, &started_log());
+        append(&path, ENDED);
+        let mut follower =
+            LogFollower::open(&path, ParseMode::Strict, TimestampFormat::Auto, false).unwrap();
+        assert!(updates(&mut follower).is_empty());
+
+        std::fs::write(&path, started_log()).unwrap();
+        assert_eq!(
+            updates(&mut follower),
+            vec!["Reopened Truncated", "Game 1 on q3dm17 ended"]
+        );
+        append(&path, ENDED);
+        assert_eq!(updates(&mut follower), vec!["Game 2 on q3dm6 ended"]);
+
+        std::fs::remove_file(&path).unwrap();
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_follow_rotated_log() {
+        let path = log_path("follow", "rotated");
+        let rotated = log_path("follow", "rotated_1");
+        append(&path, &started_log());
+        let mut follower =
+            LogFollower::open(&path, ParseMode::Strict, TimestampFormat::Auto, false).unwrap();
+
+        // the lines written before the rotation are still read
+        append(&path, ENDED);
+        std::fs::rename(&path, &rotated).unwrap();
+        assert_eq!(updates(&mut follower), vec!["Game 2 on q3dm6 ended"]);
+        assert!(updates(&mut follower).is_empty());
+
+        append(&path, &started_log());
+        assert_eq!(
+            updates(&mut follower),
+            vec!["Reopened Rotated", "Game 1 on q3dm17 ended"]
+        );
+
+        std::fs::remove_file(&path).unwrap();
+        std::fs::remove_file(&rotated).unwrap();
+    }
+}
@@ -0,0 +1,712 @@
+use crate::{
+    incremental::{fnv1a, AppendedLog, LogCheckpoint},
+    quake3_data::{IncompleteReason, PlayerData},
+    quake3_parser::parser::Game,
+};
+use rusqlite::{params, Connection, OptionalExtension, Transaction};
+use serde::Serialize;
+use std::{
+    fmt::Display,
+    path::Path,
+    time::{SystemTime, UNIX_EPOCH},
+};
+
+/// The migrations of the database schema, in the order they are applied
+///
+/// The schema version is the number of migrations applied to the database,
+/// kept in its `user_version`. A released migration is never changed,
+/// schema changes are added as a new migration at the end instead
+const MIGRATIONS: [&str; 2] = [
+    // 1: games with their settings, players, kills and means of death
+    "
+    CREATE TABLE games (
+        id INTEGER PRIMARY KEY,
+        fingerprint TEXT NOT NULL UNIQUE,
+        log_file TEXT NOT NULL,
+        game_number INTEGER NOT NULL,
+        map_name TEXT,
+        game_type TEXT NOT NULL,
+        exit_reason TEXT NOT NULL,
+        incomplete TEXT,
+        winner TEXT,
+        total_kills INTEGER NOT NULL,
+        started_at INTEGER NOT NULL,
+        ended_at INTEGER NOT NULL,
+        started_at_utc INTEGER,
+        ended_at_utc INTEGER,
+        ingested_at INTEGER NOT NULL
+    );
+    CREATE TABLE settings (
+        game_id INTEGER NOT NULL REFERENCES games (id) ON DELETE CASCADE,
+        name TEXT NOT NULL,
+        value TEXT NOT NULL,
+        PRIMARY KEY (game_id, name)
+    );
+    CREATE TABLE players (
+        id INTEGER PRIMARY KEY,
+        game_id INTEGER NOT NULL REFERENCES games (id) ON DELETE CASCADE,
+        name TEXT NOT NULL,
+        guid TEXT,
+        team TEXT NOT NULL,
+        frags INTEGER NOT NULL,
+        deaths INTEGER NOT NULL,
+        suicides INTEGER NOT NULL,
+        world_deaths INTEGER NOT NULL,
+        team_kills INTEGER NOT NULL,
+        server_score INTEGER,
+        time_played INTEGER NOT NULL
+    );
+    CREATE INDEX players_by_name ON players (name);
+    CREATE INDEX players_by_guid ON players (guid);
+    CREATE TABLE player_names (
+        player_id INTEGER NOT NULL REFERENCES players (id) ON DELETE CASCADE,
+        name TEXT NOT NULL,
+        PRIMARY KEY (player_id, name)
+    );
+    CREATE TABLE kills (
+        game_id INTEGER NOT NULL REFERENCES games (id) ON DELETE CASCADE,
+        killer_id INTEGER NOT NULL REFERENCES players (id) ON DELETE CASCADE,
+        victim_id INTEGER NOT NULL REFERENCES players (id) ON DELETE CASCADE,
+        kills INTEGER NOT NULL,
+        PRIMARY KEY (game_id, killer_id, victim_id)
+    );
+    CREATE TABLE kills_by_means (
+        game_id INTEGER NOT NULL REFERENCES games (id) ON DELETE CASCADE,
+        mean TEXT NOT NULL,
+        kills INTEGER NOT NULL,
+        PRIMARY KEY (game_id, mean)
+    );
+    CREATE TABLE player_kills_by_means (
+        player_id INTEGER NOT NULL REFERENCES players (id) ON DELETE CASCADE,
+        mean TEXT NOT NULL,
+        kills INTEGER NOT NULL,
+        PRIMARY KEY (player_id, mean)
+    );
+    ",
+    // 2: where each log was read up to, to only read what is appended to it
+    "
+    CREATE TABLE checkpoints (
+        log_file TEXT PRIMARY KEY,
+        checkpoint TEXT NOT NULL,
+        updated_at INTEGER NOT NULL
+    );
+    ",
+];
+
+#[derive(Debug)]
+/// The match history, the games of every ingested log in a `SQLite` database
+///
+/// Every game is stored once, re-ingesting a log skips the games already stored,
+/// see `MatchHistory::ingest`
+///
+/// The checkpoint of each log is kept along its games, so the next ingestion
+/// only reads the lines appended since, see `LogCheckpoint`
+pub struct MatchHistory {
+    /// The connection to the database, migrated to the latest schema
+    connection: Connection,
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+/// What an ingestion did with the games of a log
+pub struct IngestSummary {
+    /// The games stored by the ingestion
+    pub ingested: usize,
+    /// The games left out because they were already stored
+    pub already_stored: usize,
+    /// The games left out because the log ended before their shutdown,
+    /// they are stored once the rest of the game is appended to the log
+    pub still_running: usize,
+}
+
+impl Display for IngestSummary {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "{} new games, {} already stored, {} still running",
+            self.ingested, self.already_stored, self.still_running
+        )
+    }
+}
+
+impl MatchHistory {
+    /// Opens the match history in a `SQLite` database file, creating it if needed,
+    /// and migrates it to the latest schema
+    ///
+    /// can error if the database can't be opened or was created by a newer version
+    pub fn open(path: &Path) -> Result<Self, HistoryError> {
+        Self::from_connection(Connection::open(path)?)
+    }
+
+    /// Builds the match history over a connection
+    /// enabling the foreign keys and migrating the database to the latest schema
+    fn from_connection(connection: Connection) -> Result<Self, HistoryError> {
+        connection.pragma_update(None, "foreign_keys", true)?;
+        let mut history = Self { connection };
+        history.migrate()?;
+        Ok(history)
+    }
+
+    /// Returns the schema version of the database, the number of migrations applied
+    pub fn schema_version(&self) -> Result<u32, HistoryError> {
+        Ok(self
+            .connection
+            .pragma_query_value(None, "user_version", |row| row.get(0))?)
+    }
+
+    /// Applies the migrations the database is missing, each in its own transaction
+    ///
+    /// can error if the database schema is newer than the latest migration
+    fn migrate(&mut self) -> Result<(), HistoryError> {
+        let version = self.schema_version()?;
+        let latest = u32::try_from(MIGRATIONS.len()).unwrap_or(u32::MAX);
+        if version > latest {
+            return Err(HistoryError::NewerSchema(version));
+        }
+
+        for (migration, to_version) in MIGRATIONS
+            .iter()
+            .zip(1_u32..)
+            .skip_while(|(_, to)| *to <= version)
+        {
+            let transaction = self.connection.transaction()?;
+            transaction.execute_batch(migration)?;
+            transaction.pragma_update(None, "user_version", to_version)?;
+            transaction.commit()?;
+        }
+        Ok(())
+    }
+
+    /// Returns the checkpoint of the last ingestion of a log, if any
+    ///
+    /// can error if the database can't be read or the checkpoint is corrupted
+    pub fn checkpoint(&self, log_file: &str) -> Result<Option<LogCheckpoint>, HistoryError> {
+        let checkpoint: Option<String> = self
+            .connection
+            .query_row(
+                "SELECT checkpoint FROM checkpoints WHERE log_file = ?1",
+                [log_file],
+                |row| row.get(0),
+            )
+            .optional()?;
+        checkpoint
+            .map(|checkpoint| serde_json::from_str(&checkpoint))
+            .transpose()
+            .map_err(|err| HistoryError::Checkpoint(err.to_string()))
+    }
+
+    /// Stores the games appended to a log and its new checkpoint, all of them or none
+    ///
+    /// The games are identified by their number in the log and their content,
+    /// so the games already stored are skipped and ingesting a log twice,
+    /// or a copy or rotation of it, is a no-op,
+    /// while identical games at different positions are all stored.
+    /// The games the log ended in the middle of are left out until they are complete
+    ///
+    /// can error if the database can't be written to
+    pub fn ingest(
+        &mut self,
+        log_file: &str,
+        appended: &AppendedLog,
+    ) -> Result<IngestSummary, HistoryError> {
+        let ingested_at = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map_or(0, |now| i64::try_from(now.as_secs()).unwrap_or(i64::MAX));
+        let mut summary = IngestSummary {
+            still_running: usize::from(appended.checkpoint.parser.has_running_game()),
+            ..IngestSummary::default()
+        };
+        let transaction = self.connection.transaction()?;
+
+        let first_game_number = i64::try_from(appended.first_game_number).unwrap_or(i64::MAX);
+        let games = &appended.parsed.games;
+        for (game, game_number) in games.iter().zip(first_game_number..) {
+            if game.incomplete == Some(IncompleteReason::LogEnded) {
+                summary.still_running = summary.still_running.saturating_add(1);
+                continue;
+            }
+            let inserted = transaction.execute(
+                "INSERT INTO games (
+                    fingerprint, log_file, game_number, map_name, game_type, exit_reason,
+                    incomplete, winner, total_kills, started_at, ended_at,
+                    started_at_utc, ended_at_utc, ingested_at
+                ) VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11, ?12, ?13, ?14)
+                ON CONFLICT (fingerprint) DO NOTHING",
+                params![
+                    fingerprint(game_number, game)?,
+                    log_file,
+                    game_number,
+                    game.settings.map_name,
+                    variant_name(&game.game_type())?,
+                    variant_name(&game.exit_reason)?,
+                    game.incomplete.as_ref().map(variant_name).transpose()?,
+                    game.winner(),
+                    game.total_kills,
+                    game.started_at,
+                    game.ended_at,
+                    game.started_at_utc,
+                    game.ended_at_utc,
+                    ingested_at,
+                ],
+            )?;
+            if inserted == 0 {
+                summary.already_stored = summary.already_stored.saturating_add(1);
+                continue;
+            }
+            insert_game_details(&transaction, transaction.last_insert_rowid(), game)?;
+            summary.ingested = summary.ingested.saturating_add(1);
+        }
+
+        let checkpoint = serde_json::to_string(&appended.checkpoint)
+            .map_err(|err| HistoryError::Serialize(err.to_string()))?;
+        transaction.execute(
+            "INSERT INTO checkpoints (log_file, checkpoint, updated_at) VALUES (?1, ?2, ?3)
+            ON CONFLICT (log_file) DO UPDATE SET checkpoint = ?2, updated_at = ?3",
+            params![log_file, checkpoint, ingested_at],
+        )?;
+        transaction.commit()?;
+        Ok(summary)
+    }
+}
+
+/// Stores the settings, players, kills and means of death of a stored game
+/// the players are the ones of `Game::players_by_identity`
+fn insert_game_details(
+    transaction: &Transaction<'_>,
+    game_id: i64,
+    game: &Game,
+) -> Result<(), HistoryError> {
+    for (name, value) in game.settings.cvars() {
+        transaction.execute(
+            "INSERT INTO settings (game_id, name, value) VALUES (?1, ?2, ?3)",
+            params![game_id, name, value],
+        )?;
+    }
+    for (mean, kills) in &game.kills_by_means_death {
+        transaction.execute(
+            "INSERT INTO kills_by_means (game_id, mean, kills) VALUES (?1, ?2, ?3)",
+            params![game_id, variant_name(mean)?, kills],
+        )?;
+    }
+
+    let people = game.players_by_identity();
+    let mut player_ids = Vec::with_capacity(people.len());
+    for player in &people {
+        player_ids.push(insert_player(transaction, game_id, player)?);
+    }
+    let matrix = game.kill_matrix_by_identity(&people);
+    for (killer_id, victims) in player_ids.iter().zip(&matrix) {
+        for (victim_id, kills) in player_ids.iter().zip(victims) {
+            if *kills > 0 {
+                transaction.execute(
+                    "INSERT INTO kills (game_id, killer_id, victim_id, kills)
+                    VALUES (?1, ?2, ?3, ?4)",
+                    params![game_id, killer_id, victim_id, kills],
+                )?;
+            }
+        }
+    }
+    Ok(())
+}
+
+/// Stores a player of a stored game with its names and kills by mean of death
+/// returning the id of the player row
+fn insert_player(
+    transaction: &Transaction<'_>,
+    game_id: i64,
+    player: &PlayerData,
+) -> Result<i64, HistoryError> {
+    transaction.execute(
+        "INSERT INTO players (
+            game_id, name, guid, team, frags, deaths, suicides, world_deaths, team_kills,
+            server_score, time_played
+        ) VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11)",
+        params![
+            game_id,
+            player.name,
+            player.guid,
+            variant_name(&player.team)?,
+            player.frags,
+            player.deaths,
+            player.suicides,
+            player.world_deaths,
+            player.team_kills,
+            player.server_score,
+            player.time_played(),
+        ],
+    )?;
+    let player_id = transaction.last_insert_rowid();
+
+    for name in std::iter::once(&player.name).chain(&player.name_history) {
+        transaction.execute(
+            "INSERT INTO player_names (player_id, name) VALUES (?1, ?2)
+            ON CONFLICT DO NOTHING",
+            params![player_id, name],
+        )?;
+    }
+    for (mean, kills) in &player.kills_by_means {
+        transaction.execute(
+            "INSERT INTO player_kills_by_means (player_id, mean, kills) VALUES (?1, ?2, ?3)",
+            params![player_id, variant_name(mean)?, kills],
+        )?;
+    }
+    Ok(player_id)
+}
+
+/// Returns the name of an enum variant as in the json report, e.g. `FreeForAll`
+/// the variants holding data are kept as json, e.g. `{"Other":"reason"}`
+fn variant_name<T: Serialize>(value: &T) -> Result<String, HistoryError> {
+    match serde_json::to_value(value) {
+        Ok(serde_json::Value::String(name)) => Ok(name),
+        Ok(other) => Ok(other.to_string()),
+        Err(err) => Err(HistoryError::Serialize(err.to_string())),
+    }
+}
+
+/// Returns the fingerprint of a game, identifying it by its number in the log and its content,
+/// so two identical games at different positions have different fingerprints,
+/// while the same game read from a copy or a rotation of the log has the same one
+///
+/// The 64 bit FNV-1a hash of the game number and the game as json,
+/// with the maps sorted by key so the same game always has the same fingerprint
+fn fingerprint(game_number: i64, game: &Game) -> Result<String, HistoryError> {
+    let json = serde_json::to_value((game_number, game))
+        .map_err(|err| HistoryError::Serialize(err.to_string()))?
+        .to_string();
+    Ok(format!("{:016x}", fnv1a(json.as_bytes())))
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+/// The errors that can occur while storing the match history
+pub enum HistoryError {
+    /// When the database can't be opened, migrated, read or written to
+    Database(String),
+    /// When the database schema is newer than the latest migration,
+    /// i.e. it was created by a newer version
+    NewerSchema(u32),
+    /// When a game or a checkpoint can't be serialized
+    Serialize(String),
+    /// When a stored checkpoint can't be read back
+    Checkpoint(String),
+}
+
+impl From<rusqlite::Error> for HistoryError {
+    fn from(err: rusqlite::Error) -> Self {
+        Self::Database(err.to_string())
+    }
+}
+
+impl Display for HistoryError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Database(err) => write!(f, "Database error: {err}"),
+            Self::NewerSchema(version) => write!(
+                f,
+                "Database schema version {version} is newer than the supported {}",
+                MIGRATIONS.len()
+            ),
+            Self::Serialize(err) => write!(f, "Could not serialize: {err}"),
+            Self::Checkpoint(err) => write!(f, "Could not read the checkpoint: {err}"),
+        }
+    }
+}
+
+#[cfg(test)]
+#[allow(
+    clippy::default_numeric_fallback,
+    clippy::shadow_unrelated,
+    clippy::unwrap_used
+)]
+// the tests unwrap the queries and compare the rows with plain literals
+mod tests {
+    use super::*;
+    use crate::{
+        incremental::Resumed,
+        quake3_parser::parser::{scan_file, ParsedLog},
+    };
+
+    /// A log with a finished game, a game cut by a new one and a game still running
+    const LOG: &str = r"
+        0:00 InitGame: \sv_hostname\Code Miner Server\g_gametype\0\mapname\q3dm17
+        0:00 ClientConnect: 2
+        0:00 ClientUserinfoChanged: 2 n\Izzy\t\0
+        0:00 ClientConnect: 3
+        0:00 ClientUserinfoChanged: 3 n\Zeh\t\0\cl_guid\A1B2
+        0:10 Kill: 2 3 10: Izzy killed Zeh by MOD_RAILGUN
+        0:20 ClientUserinfoChanged: 2 n\Isgalamido\t\0
+        0:30 Kill: 2 3 10: Isgalamido killed Zeh by MOD_RAILGUN
+        0:40 Kill: 1022 2 22: <world> killed Isgalamido by MOD_TRIGGER_HURT
+        0:50 Exit: Fraglimit hit.
+        1:00 ShutdownGame:
+        1:10 InitGame: \mapname\q3dm6
+        1:10 ClientConnect: 2
+        1:10 ClientUserinfoChanged: 2 n\Mal\t\0
+        1:20 InitGame: \mapname\q3dm4
+        1:30 ClientConnect: 2
+        ";
+
+    /// Opens a match history in memory
+    fn history() -> MatchHistory {
+        MatchHistory::from_connection(Connection::open_in_memory().unwrap()).unwrap()
+    }
+
+    /// Returns the games of the whole log, as read without a checkpoint
+    fn whole_log(games: Vec<Game>) -> AppendedLog {
+        AppendedLog {
+            parsed: ParsedLog {
+                games,
+                diagnostics: Vec::new(),
+            },
+            first_game_number: 1,
+            checkpoint: LogCheckpoint::default(),
+            resumed: Resumed::FromStart,
+        }
+    }
+
+    /// Returns the rows of a query with a single text column
+    fn strings(history: &MatchHistory, query: &str) -> Vec<String> {
+        let mut statement = history.connection.prepare(query).unwrap();
+        let rows = statement.query_map([], |row| row.get(0)).unwrap();
+        rows.map(Result::unwrap).collect()
+    }
+
+    #[test]
+    fn test_migrations() {
+        let history = history();
+        assert_eq!(history.schema_version().unwrap(), 2);
+
+        // migrating again keeps the schema
+        let mut history = history;
+        history.migrate().unwrap();
+        assert_eq!(history.schema_version().unwrap(), 2);
+
+        let connection = Connection::open_in_memory().unwrap();
+        connection.pragma_update(None, "user_version", 7).unwrap();
+        assert_eq!(
+            MatchHistory::from_connection(connection).unwrap_err(),
+            HistoryError::NewerSchema(7)
+        );
+    }
+
+    #[test]
+    fn test_ingest_is_idempotent() {
+        let games = whole_log(scan_file(LOG).unwrap());
+        let mut history = history();
+
+        assert_eq!(
+            history.ingest("games.log", &games).unwrap(),
+            IngestSummary {
+                ingested: 2,
+                already_stored: 0,
+                still_running: 1,
+            }
+        );
+        assert_eq!(
+            history.ingest("games.log", &games).unwrap(),
+            IngestSummary {
+                ingested: 0,
+                already_stored: 2,
+                still_running: 1,
+            }
+        );
+        assert_eq!(
+            strings(
+                &history,
+                "SELECT map_name || ' ' || exit_reason || ' ' || ifnull(incomplete, '-')
+                FROM games ORDER BY game_number"
+            ),
+            vec!["q3dm17 Fraglimit -", "q3dm6 Aborted NewGameStarted"]
+        );
+    }
+
+    #[test]
+    fn test_ingest_stores_game_details() {
+        let games = whole_log(scan_file(LOG).unwrap());
+        let mut history = history();
+        history.ingest("games.log", &games).unwrap();
+
+        assert_eq!(
+            strings(
+                &history,
+                "SELECT name || ' ' || frags || ' ' || deaths || ' ' || ifnull(guid, '-')
+                FROM players JOIN games ON games.id = players.game_id
+                WHERE games.game_number = 1 ORDER BY name"
+            ),
+            vec!["Isgalamido 2 1 -", "Zeh 0 2 A1B2"]
+        );
+        assert_eq!(
+            strings(
+                &history,
+                "SELECT player_names.name FROM player_names
+                JOIN players ON players.id = player_names.player_id
+                WHERE players.name = 'Isgalamido' ORDER BY player_names.name"
+            ),
+            vec!["Isgalamido", "Izzy"]
+        );
+        assert_eq!(
+            strings(
+                &history,
+                "SELECT killer.name || ' ' || victim.name || ' ' || kills FROM kills
+                JOIN players killer ON killer.id = kills.killer_id
+                JOIN players victim ON victim.id = kills.victim_id"
+            ),
+            vec!["Isgalamido Zeh 2"]
+        );
+        assert_eq!(
+            strings(
+                &history,
+                "SELECT mean || ' ' || kills FROM kills_by_means
+                WHERE game_id = 1 ORDER BY mean"
+            ),
+            vec!["Railgun 2", "TriggerHurt 1"]
+        );
+        assert_eq!(
+            strings(
+                &history,
+                "SELECT mean || ' ' || kills FROM player_kills_by_means"
+            ),
+            vec!["Railgun 2"]
+        );
+        assert_eq!(
+            strings(
+                &history,
+                "SELECT name || '=' || value FROM settings WHERE game_id = 1 ORDER BY name"
+            ),
+            vec![
+                "g_gametype=0",
+                "mapname=q3dm17",
+                "sv_hostname=Code Miner Server"
+            ]
+        );
+    }
+
+    #[test]
+    fn test_ingest_keeps_checkpoint() {
+        let mut history = history();
+        assert!(history.checkpoint("games.log").unwrap().is_none());
+
+        let mut games = scan_file(LOG).unwrap();
+        games.truncate(2);
+        let mut appended = whole_log(games);
+        appended.first_game_number = 4;
+        appended.checkpoint.games_read = 5;
+        appended.checkpoint.tail_hash = 42;
+        assert_eq!(
+            history
+                .ingest("games.log", &appended)
+                .unwrap()
+                .still_running,
+            0
+        );
+
+        let checkpoint = history.checkpoint("games.log").unwrap().unwrap();
+        assert_eq!(checkpoint.games_read, 5);
+        assert_eq!(checkpoint.tail_hash, 42);
+        assert!(history.checkpoint("other.log").unwrap().is_none());
+        // the games are numbered from their position in the log
+        assert_eq!(
+            strings(
+                &history,
+                "SELECT game_number || ' ' || map_name FROM games ORDER BY game_number"
+            ),
+            vec!["4 q3dm17", "5 q3dm6"]
+        );
+
+        // the checkpoint of a log is replaced by its next ingestion
+        appended.checkpoint.games_read = 6;
+        history.ingest("games.log", &appended).unwrap();
+        let checkpoint = history.checkpoint("games.log").unwrap().unwrap();
+        assert_eq!(checkpoint.games_read, 6);
+
+        history
+            .connection
+            .execute("UPDATE checkpoints SET checkpoint = 'oops'", [])
+            .unwrap();
+        assert!(matches!(
+            history.checkpoint("games.log"),
+            Err(HistoryError::Checkpoint(_))
+        ));
+    }
+
+    #[test]
+    fn test_ingest_stores_identical_games_at_different_positions() {
+        // the same short game played twice in a row, e.g. a map restarted right away
+        let game = r"
+        0:00 InitGame: \mapname\q3dm17
+        0:00 ClientConnect: 2
+        0:00 ClientUserinfoChanged: 2 n\Isgalamido\t\0
+        0:10 Kill: 1022 2 22: <world> killed Isgalamido by MOD_TRIGGER_HURT
+        1:00 ShutdownGame:
+        ";
+        let games = whole_log(scan_file(&game.repeat(2)).unwrap());
+        let mut history = history();
+
+        assert_eq!(history.ingest("games.log", &games).unwrap().ingested, 2);
+        assert_eq!(
+            history.ingest("games.log", &games).unwrap().already_stored,
+            2
+        );
+        assert_eq!(
+            strings(
+                &history,
+                "SELECT log_file || ' ' || game_number FROM games ORDER BY id"
+            ),
+            vec!["games.log 1", "games.log 2"]
+        );
+    }
+
+    #[test]
+    fn test_ingest_skips_copied_and_rotated_logs() {
+        let games = whole_log(scan_file(LOG).unwrap());
+        let mut history = history();
+        assert_eq!(history.ingest("games.log", &games).unwrap().ingested, 2);
+
+        for log_file in ["backup/games.log", "games.log.1"] {
+            assert_eq!(
+                history.ingest(log_file, &games).unwrap(),
+                IngestSummary {
+                    ingested: 0,
+                    already_stored: 2,
+                    still_running: 1,
+                }
+            );
+        }
+        assert_eq!(
+            strings(
+                &history,
+                "SELECT log_file || ' ' || game_number FROM games ORDER BY id"
+            ),
+            vec!["games.log 1", "games.log 2"]
+        );
+    }
+
+    #[test]
+    fn test_fingerprint_identifies_game_position_and_content() {
+        let games = scan_file(LOG).unwrap();
+        let again = scan_file(LOG).unwrap();
+        assert_eq!(
+            fingerprint(1, &games[0]).unwrap(),
+            fingerprint(1, &again[0]).unwrap()
+        );
+        assert_ne!(
+            fingerprint(1, &games[0]).unwrap(),
+            fingerprint(1, &games[1]).unwrap()
+        );
+        assert_ne!(
+            fingerprint(1, &games[0]).unwrap(),
+            fingerprint(2, &games[0]).unwrap()
+        );
+    }
+
+    #[test]
+    fn test_variant_name() {
+        use crate::quake3_data::{ExitReason, GameType};
+        assert_eq!(
+            variant_name(&GameType::CaptureTheFlag).unwrap(),
+            "CaptureTheFlag"
+        );
+        assert_eq!(
+            variant_name(&ExitReason::Other("Server quit".to_owned())).unwrap(),
+            r#"{"Other":"Server quit"}"#
+        );
+    }
+}
@@ -0,0 +1,301 @@
+use crate::quake3_parser::{
+    errors::ParsingError,
+    parser::{resume_reader, ParseCheckpoint, ParseMode, ParsedLog},
+    timestamps::TimestampFormat,
+};
+use serde::{Deserialize, Serialize};
+use std::{
+    fmt::Display,
+    fs::{File, Metadata},
+    io::{BufReader, Read, Seek, SeekFrom},
+    path::Path,
+};
+
+/// How many bytes before the checkpoint offset are hashed to detect a rewritten log
+const TAIL_LENGTH: u64 = 4096;
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+/// Where a log file was read up to, with everything needed to read what is appended to it
+///
+/// The log file is expected to only grow, the checkpoint is dropped when it was
+/// rotated (another file now has its path) or truncated (shorter or rewritten)
+pub struct LogCheckpoint {
+    /// The inode of the log file, to detect its rotation, `None` outside of unix
+    pub inode: Option<u64>,
+    /// The hash of the bytes right before the offset, to detect a rewritten log
+    pub tail_hash: u64,
+    /// The number of games finished before the offset
+    pub games_read: u64,
+    /// The parser state at the offset, with the game still running there
+    pub parser: ParseCheckpoint,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+/// Where the reading of a log file started
+pub enum Resumed {
+    /// There was no checkpoint, the log was read from its start
+    FromStart,
+    /// The log was read from the checkpoint offset, only its appended lines
+    FromCheckpoint,
+    /// Another file has the path of the checkpointed log, e.g. after a log rotation,
+    /// it was read from its start and the game running at the checkpoint was dropped
+    Rotated,
+    /// The log is shorter than at the checkpoint or its content changed before the offset,
+    /// it was read from its start and the game running at the checkpoint was dropped
+    Truncated,
+}
+
+impl Display for Resumed {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::FromStart => write!(f, "read from its start"),
+            Self::FromCheckpoint => write!(f, "read from its checkpoint"),
+            Self::Rotated => write!(f, "rotated"),
+            Self::Truncated => write!(f, "truncated"),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+/// The identity and length of a log file, to detect its rotation and truncation
+pub struct FileState {
+    /// The inode of the file, `None` outside of unix
+    pub inode: Option<u64>,
+    /// The length of the file, in bytes
+    pub len: u64,
+}
+
+impl FileState {
+    /// Returns the state of the file with the metadata
+    pub fn of(metadata: &Metadata) -> Self {
+        #[cfg(unix)]
+        let inode = Some(std::os::unix::fs::MetadataExt::ino(metadata));
+        #[cfg(not(unix))]
+        let inode = None;
+        Self {
+            inode,
+            len: metadata.len(),
+        }
+    }
+
+    /// Returns whether the log was rotated or truncated since it was read up to the `offset`
+    /// of the file with the `inode`, `None` when it may only have grown
+    pub fn changed_since(&self, inode: Option<u64>, offset: u64) -> Option<Resumed> {
+        if inode.is_some() && self.inode.is_some() && inode != self.inode {
+            Some(Resumed::Rotated)
+        } else if self.len < offset {
+            Some(Resumed::Truncated)
+        } else {
+            None
+        }
+    }
+}
+
+#[derive(Debug)]
+/// The games appended to a log file since its checkpoint
+pub struct AppendedLog {
+    /// The games finished since the checkpoint and the diagnostics of the skipped lines
+    pub parsed: ParsedLog,
+    /// The number of the first game in the log, starting at 1
+    pub first_game_number: u64,
+    /// The checkpoint after the last complete line of the log
+    pub checkpoint: LogCheckpoint,
+    /// Where the reading started
+    pub resumed: Resumed,
+}
+
+/// Reads the lines appended to a log file since its `checkpoint`, following the parse `mode`
+/// the whole log is read when there is no checkpoint or when the log was rotated or truncated
+///
+/// The game still running at the end of the log is kept in the new checkpoint,
+/// it comes out once its end is appended. The log timestamps follow the `format`,
+/// unless the checkpoint already detected it
+///
+/// can error if the log can't be read, or at the first bad line in strict mode
+pub fn read_appended(
+    path: &Path,
+    mode: ParseMode,
+    format: TimestampFormat,
+    checkpoint: Option<LogCheckpoint>,
+) -> Result<AppendedLog, ParsingError> {
+    let mut file = File::open(path)?;
+    let file_state = FileState::of(&file.metadata()?);
+
+    let (resumed, checkpoint) = match checkpoint {
+        None => (Resumed::FromStart, LogCheckpoint::default()),
+        Some(checkpoint) => {
+            let offset = u64::try_from(checkpoint.parser.position().offset).unwrap_or(u64::MAX);
+            match file_state.changed_since(checkpoint.inode, offset) {
+                Some(changed) => (changed, LogCheckpoint::default()),
+                None if tail_hash(&mut file, offset)? != checkpoint.tail_hash => {
+                    (Resumed::Truncated, LogCheckpoint::default())
+                }
+                None => (Resumed::FromCheckpoint, checkpoint),
+            }
+        }
+    };
+    let resume_from = if resumed == Resumed::FromCheckpoint {
+        checkpoint.parser
+    } else {
+        ParseCheckpoint::new(format)
+    };
+
+    let start = u64::try_from(resume_from.position().offset).unwrap_or(u64::MAX);
+    file.seek(SeekFrom::Start(start))?;
+    let mut stream = resume_reader(BufReader::new(&file), mode, resume_from);
+    let parsed = stream.parse_available()?;
+    let parser = stream.checkpoint();
+
+    let end = u64::try_from(parser.position().offset).unwrap_or(u64::MAX);
+    let games = u64::try_from(parsed.games.len()).unwrap_or(u64::MAX);
+    Ok(AppendedLog {
+        parsed,
+        first_game_number: checkpoint.games_read.saturating_add(1),
+        checkpoint: LogCheckpoint {
+            inode: file_state.inode,
+            tail_hash: tail_hash(&mut file, end)?,
+            games_read: checkpoint.games_read.saturating_add(games),
+            parser,
+        },
+        resumed,
+    })
+}
+
+/// Returns the hash of the `TAIL_LENGTH` bytes before the `offset` of the file
+///
+/// can error if the file is shorter than the offset
+fn tail_hash(file: &mut File, offset: u64) -> std::io::Result<u64> {
+    let start = offset.saturating_sub(TAIL_LENGTH);
+    let mut tail = Vec::new();
+    file.seek(SeekFrom::Start(start))?;
+    file.by_ref()
+        .take(offset.saturating_sub(start))
+        .read_to_end(&mut tail)?;
+    if u64::try_from(tail.len()).unwrap_or(u64::MAX) < offset.saturating_sub(start) {
+        return Err(std::io::ErrorKind::UnexpectedEof.into());
+    }
+    Ok(fnv1a(&tail))
+}
+
+/// Returns the 64 bit FNV-1a hash of the bytes
+pub fn fnv1a(bytes: &[u8]) -> u64 {
+    /// The FNV-1a 64 bit offset basis
+    const OFFSET_BASIS: u64 = 0xcbf2_9ce4_8422_2325;
+    /// The FNV-1a 64 bit prime
+    const PRIME: u64 = 0x0100_0000_01b3;
+
+    bytes.iter().fold(OFFSET_BASIS, |hash, byte| {
+        (hash ^ u64::from(*byte)).wrapping_mul(PRIME)
+    })
+}
+
+#[cfg(test)]
+#[allow(clippy::unwrap_used)]
+// a failing unwrap fails the test, with the error
+mod tests {
+    use super::*;
+    use crate::test_support::{append, log_path, started_log};
+
+    /// A finished game and the start of a game still running, cut in the middle of its last line
+    fn first_part() -> String {
+        format!(
+            "{}  0:05 Kill: 1022 3 22: <world> killed Zeh",
+            started_log()
+        )
+    }
+
+    /// The end of the running game, completing its last line
+    const SECOND_PART: &str = " by MOD_TRIGGER_HURT\n\
+        0:06 Kill: 1022 3 22: <world> killed Zeh by MOD_TRIGGER_HURT\n\
+        0:30 ShutdownGame:\n";
+
+    /// Reads the log in strict mode
+    fn read(path: &Path, checkpoint: Option<LogCheckpoint>) -> AppendedLog {
+        read_appended(path, ParseMode::Strict, TimestampFormat::Auto, checkpoint).unwrap()
+    }
+
+    #[test]
+    fn test_read_appended_lines_only() {
+        let path = log_path("incremental", "appended");
+        append(&path, &first_part());
+
+        let first = read(&path, None);
+        assert_eq!(first.resumed, Resumed::FromStart);
+        assert_eq!(first.first_game_number, 1);
+        assert_eq!(first.parsed.games.len(), 1);
+        assert!(first.checkpoint.parser.has_running_game());
+        // the last line is still being written
+        let last_line = first_part().rfind('\n').unwrap() + 1;
+        assert_eq!(first.checkpoint.parser.position().offset, last_line);
+
+        // nothing was appended
+        let again = read(&path, Some(first.checkpoint.clone()));
+        assert_eq!(again.resumed, Resumed::FromCheckpoint);
+        assert!(again.parsed.games.is_empty());
+
+        append(&path, SECOND_PART);
+        let second = read(&path, Some(first.checkpoint));
+        assert_eq!(second.resumed, Resumed::FromCheckpoint);
+        assert_eq!(second.first_game_number, 2);
+        assert_eq!(second.checkpoint.games_read, 2);
+        assert!(!second.checkpoint.parser.has_running_game());
+        assert_eq!(second.parsed.games.len(), 1);
+        let game = &second.parsed.games[0];
+        assert_eq!(game.settings.map_name.as_deref(), Some("q3dm6"));
+        assert_eq!(game.total_kills, 2);
+        assert_eq!(game.players_data[&3].world_deaths, 2);
+
+        std::fs::remove_file(&path).unwrap();
+    }
+
+    #[test]
+    fn test_read_appended_detects_truncation() {
+        let path = log_path("incremental", "truncated");
+        append(&path, &first_part());
+        let first = read(&path, None);
+
+        // truncated to a shorter log
+        std::fs::write(&path, "  0:00 InitGame: \\mapname\\q3dm4\n").unwrap();
+        let shorter = read(&path, Some(first.checkpoint.clone()));
+        assert_eq!(shorter.resumed, Resumed::Truncated);
+        assert_eq!(shorter.first_game_number, 1);
+        assert_eq!(shorter.checkpoint.games_read, 0);
+        assert!(shorter.checkpoint.parser.has_running_game());
+
+        // rewritten with a longer log
+        std::fs::write(&path, first_part().replace("q3dm17", "q3dm04")).unwrap();
+        append(&path, SECOND_PART);
+        let rewritten = read(&path, Some(first.checkpoint));
+        assert_eq!(rewritten.resumed, Resumed::Truncated);
+        assert_eq!(rewritten.parsed.games.len(), 2);
+
+        std::fs::remove_file(&path).unwrap();
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_read_appended_detects_rotation() {
+        let path = log_path("incremental", "rotated");
+        let rotated = log_path("incremental", "rotated_1");
+        append(&path, &first_part());
+        let first = read(&path, None);
+
+        // the log moves away and a new log takes its path
+        std::fs::rename(&path, &rotated).unwrap();
+        append(&path, &first_part());
+        append(&path, SECOND_PART);
+        let second = read(&path, Some(first.checkpoint));
+        assert_eq!(second.resumed, Resumed::Rotated);
+        assert_eq!(second.first_game_number, 1);
+        assert_eq!(second.parsed.games.len(), 2);
+
+        std::fs::remove_file(&path).unwrap();
+        std::fs::remove_file(&rotated).unwrap();
+    }
+
+    #[test]
+    fn test_fnv1a() {
+        assert_eq!(fnv1a(b""), 0xcbf2_9ce4_8422_2325);
+        assert_eq!(fnv1a(b"a"), 0xaf63_dc4c_8601_ec8c);
+    }
+}
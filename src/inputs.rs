@@ -0,0 +1,324 @@
+use crate::quake3_parser::{
+    errors::ParsingError,
+    parser::{scan_reader, ParseMode, ParsedLog},
+    timestamps::TimestampFormat,
+};
+use std::{
+    cmp::Reverse,
+    fmt::Display,
+    fs::{self, File},
+    io::{self, BufReader},
+    path::{Path, PathBuf},
+};
+
+/// The input that stands for the standard input
+const STDIN: &str = "-";
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+/// Where the lines of a log come from
+pub enum LogSource {
+    /// The standard input, given as `-`
+    Stdin,
+    /// A log file
+    File(PathBuf),
+}
+
+impl Display for LogSource {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Stdin => write!(f, "<stdin>"),
+            Self::File(path) => write!(f, "{}", path.display()),
+        }
+    }
+}
+
+impl LogSource {
+    /// Parses the log, following the parse `mode` and the timestamp `format`
+    /// each game is tagged with the source, see `Game::source`
+    ///
+    /// can error if the log can't be read, or at the first bad line in strict mode
+    pub fn parse(
+        &self,
+        mode: ParseMode,
+        format: TimestampFormat,
+    ) -> Result<ParsedLog, ParsingError> {
+        let mut parsed = match self {
+            Self::Stdin => scan_reader(io::stdin().lock(), mode, format).parse_log()?,
+            Self::File(path) => {
+                scan_reader(BufReader::new(File::open(path)?), mode, format).parse_log()?
+            }
+        };
+        for game in &mut parsed.games {
+            game.source = Some(self.to_string());
+        }
+        Ok(parsed)
+    }
+}
+
+/// Returns the logs of the inputs, in the order their games are merged
+///
+/// Each input is `-` for the standard input, a log file, a directory or a glob pattern.
+/// The files of a directory are its logs (`*.log`) and their rotations (`*.log.1`, `*.log.2`...),
+/// the files a pattern matches are all read
+///
+/// The files are ordered by path, with the rotations of a log before it, the oldest first
+/// (`games.log.2`, `games.log.1` then `games.log`), and the standard input comes last.
+/// A file given several times, whatever its spelling, is read once
+/// under the name it was first given
+///
+/// can error if an input is neither of them, or if a pattern or directory matches no file
+pub fn log_sources(inputs: &[PathBuf]) -> Result<Vec<LogSource>, InputError> {
+    let mut files = Vec::new();
+    let mut stdin = false;
+    for input in inputs {
+        if input.as_os_str() == STDIN {
+            stdin = true;
+        } else if input.is_dir() {
+            files.extend(directory_logs(input)?);
+        } else if input.exists() {
+            files.push(input.clone());
+        } else {
+            files.extend(pattern_files(input)?);
+        }
+    }
+
+    // the same file can be spelled several ways, e.g. `games.log` and `./games.log`,
+    // so the files are ordered and told apart by their canonical path
+    let mut files: Vec<(PathBuf, PathBuf)> = files
+        .into_iter()
+        .map(|file| {
+            (
+                fs::canonicalize(&file).unwrap_or_else(|_| file.clone()),
+                file,
+            )
+        })
+        .collect();
+    files.sort_by_cached_key(|(canonical, _)| rotation_key(canonical));
+    files.dedup_by(|(canonical, _), (kept, _)| canonical == kept);
+    let mut sources: Vec<LogSource> = files
+        .into_iter()
+        .map(|(_, file)| LogSource::File(file))
+        .collect();
+    if stdin {
+        sources.push(LogSource::Stdin);
+    }
+    Ok(sources)
+}
+
+/// Returns the logs of a directory and their rotations, not looking into its subdirectories
+///
+/// can error if the directory can't be read or has no log
+fn directory_logs(directory: &Path) -> Result<Vec<PathBuf>, InputError> {
+    let entries = fs::read_dir(directory)
+        .map_err(|err| InputError::Io(directory.display().to_string(), err.kind()))?;
+    let logs: Vec<PathBuf> = entries
+        .filter_map(Result::ok)
+        .map(|entry| entry.path())
+        .filter(|path| path.is_file())
+        .filter(|path| {
+            let (log, _) = rotation_key(path);
+            log.extension().is_some_and(|extension| extension == "log")
+        })
+        .collect();
+    if logs.is_empty() {
+        return Err(InputError::NoLog(directory.display().to_string()));
+    }
+    Ok(logs)
+}
+
+/// Returns the files matched by a glob pattern
+///
+/// can error if the pattern is invalid or matches no file
+fn pattern_files(pattern: &Path) -> Result<Vec<PathBuf>, InputError> {
+    let pattern = pattern.to_string_lossy();
+    let matches = glob::glob(&pattern)
+        .map_err(|err| InputError::Pattern(pattern.to_string(), err.msg.to_owned()))?;
+    let files: Vec<PathBuf> = matches
+        .filter_map(Result::ok)
+        .filter(|path| path.is_file())
+        .collect();
+    if files.is_empty() {
+        return Err(InputError::NoLog(pattern.to_string()));
+    }
+    Ok(files)
+}
+
+/// Returns the key ordering the logs, the log a file is a rotation of and how old the rotation is
+///
+/// `games.log.2` is the rotation 2 of `games.log` and sorts before the rotation 1,
+/// `games.log` itself sorts after its rotations
+fn rotation_key(file: &Path) -> (PathBuf, Reverse<Option<u32>>) {
+    let rotation = file
+        .extension()
+        .and_then(|extension| extension.to_str())
+        .and_then(|extension| extension.parse::<u32>().ok());
+    rotation.map_or_else(
+        || (file.to_path_buf(), Reverse(None)),
+        |rotation| (file.with_extension(""), Reverse(Some(rotation))),
+    )
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+/// The errors that can occur while listing the logs of the inputs
+pub enum InputError {
+    /// When a directory or a glob pattern has no log file
+    NoLog(String),
+    /// When a glob pattern is invalid, with why
+    Pattern(String, String),
+    /// When a directory can't be read
+    Io(String, io::ErrorKind),
+}
+
+impl Display for InputError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::NoLog(input) => write!(f, "No log file found for {input}"),
+            Self::Pattern(pattern, err) => write!(f, "Invalid pattern {pattern}: {err}"),
+            Self::Io(directory, kind) => write!(f, "Could not read {directory}: {kind}"),
+        }
+    }
+}
+
+#[cfg(test)]
+#[allow(clippy::shadow_unrelated, clippy::unwrap_used)]
+// the tests unwrap the file operations and reuse `sources` for every input
+mod tests {
+    use super::*;
+    use crate::test_support::game_on;
+
+    /// Returns an empty directory for the logs of the test
+    fn logs_directory(name: &str) -> PathBuf {
+        let directory =
+            std::env::temp_dir().join(format!("wolf_quake_inputs_{}_{name}", std::process::id()));
+        fs::remove_dir_all(&directory).ok();
+        fs::create_dir_all(&directory).unwrap();
+        directory
+    }
+
+    /// Returns the file names of the sources, `-` for the standard input
+    fn names(sources: &[LogSource]) -> Vec<String> {
+        sources
+            .iter()
+            .map(|source| match source {
+                LogSource::Stdin => STDIN.to_owned(),
+                LogSource::File(path) => path.file_name().unwrap().to_string_lossy().into_owned(),
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_log_sources_order_rotated_logs() {
+        let directory = logs_directory("rotated");
+        for name in [
+            "games.log",
+            "games.log.1",
+            "games.log.2",
+            "games.log.10",
+            "admin.log",
+        ] {
+            fs::write(directory.join(name), game_on(name)).unwrap();
+        }
+        fs::write(directory.join("notes.txt"), "not a log").unwrap();
+
+        let sources = log_sources(std::slice::from_ref(&directory)).unwrap();
+        assert_eq!(
+            names(&sources),
+            vec![
+                "admin.log",
+                "games.log.10",
+                "games.log.2",
+                "games.log.1",
+                "games.log"
+            ]
+        );
+
+        // the shell order of `games.log*` doesn't matter, nor repeated files and stdin
+        let sources = log_sources(&[
+            PathBuf::from(STDIN),
+            directory.join("games.log"),
+            directory.join("games.log.*"),
+            directory.join("games.log.1"),
+        ])
+        .unwrap();
+        assert_eq!(
+            names(&sources),
+            vec![
+                "games.log.10",
+                "games.log.2",
+                "games.log.1",
+                "games.log",
+                "-"
+            ]
+        );
+
+        fs::remove_dir_all(&directory).unwrap();
+    }
+
+    #[test]
+    fn test_log_sources_read_a_file_once_whatever_its_spelling() {
+        let directory = logs_directory("spelling");
+        fs::write(directory.join("games.log"), game_on("q3dm17")).unwrap();
+
+        let log = directory.join("games.log");
+        let sources = log_sources(&[
+            log.clone(),
+            directory.join(".").join("games.log"),
+            directory
+                .join("..")
+                .join(directory.file_name().unwrap())
+                .join("games.log"),
+            directory.clone(),
+        ])
+        .unwrap();
+        assert_eq!(sources, vec![LogSource::File(log)]);
+
+        fs::remove_dir_all(&directory).unwrap();
+    }
+
+    #[test]
+    fn test_log_sources_errors() {
+        let directory = logs_directory("errors");
+        assert_eq!(
+            log_sources(std::slice::from_ref(&directory)),
+            Err(InputError::NoLog(directory.display().to_string()))
+        );
+        let missing = directory.join("missing.log");
+        assert_eq!(
+            log_sources(std::slice::from_ref(&missing)),
+            Err(InputError::NoLog(missing.display().to_string()))
+        );
+        assert!(matches!(
+            log_sources(&[directory.join("games[.log")]),
+            Err(InputError::Pattern(..))
+        ));
+
+        fs::remove_dir_all(&directory).unwrap();
+    }
+
+    #[test]
+    fn test_parse_tags_games_with_source() {
+        let directory = logs_directory("parse");
+        let log = directory.join("games.log.1");
+        fs::write(&log, game_on("q3dm17").repeat(2)).unwrap();
+
+        let source = LogSource::File(log.clone());
+        let parsed = source
+            .parse(ParseMode::Strict, TimestampFormat::Auto)
+            .unwrap();
+        assert_eq!(parsed.games.len(), 2);
+        assert!(parsed
+            .games
+            .iter()
+            .all(|game| game.source.as_deref() == Some(log.to_str().unwrap())));
+
+        let missing = LogSource::File(directory.join("missing.log"));
+        assert_eq!(
+            missing
+                .parse(ParseMode::Strict, TimestampFormat::Auto)
+                .unwrap_err(),
+            ParsingError::IoError(io::ErrorKind::NotFound)
+        );
+
+        fs::remove_dir_all(&directory).unwrap();
+    }
+}
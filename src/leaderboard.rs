@@ -0,0 +1,224 @@
+use crate::{quake3_data::PlayerData, quake3_parser::parser::Game, scoring::ScoringRules};
+use std::collections::BTreeSet;
+
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+/// The standing of a player across several games
+/// with its kill counters, pickups and kills by mean of death summed over the games it played
+pub struct Standing {
+    /// The player data summed over its games, see `PlayerData::merge`
+    /// named after the last name the player used
+    pub player: PlayerData,
+    /// The games the player was part of, by their position in the games of the standings
+    /// a game counts once even when the player matched several identities in it
+    pub game_ids: BTreeSet<usize>,
+    /// The player score summed over its games
+    /// each game scored with its own scoring rules, see `Game::apply_scoring`
+    pub score: i32,
+}
+
+impl Standing {
+    /// Returns the number of games the player was part of
+    pub fn games(&self) -> u32 {
+        u32::try_from(self.game_ids.len()).unwrap_or(u32::MAX)
+    }
+
+    /// Merges another standing into this one
+    /// the games are merged by id and the scores, already computed with the rules of their games, are summed
+    fn merge(&mut self, other: &Self) {
+        // both players carry their score, so the rules given to the merge are never used
+        self.player.merge(&other.player, &ScoringRules::default());
+        self.game_ids.extend(&other.game_ids);
+        self.score = self.score.saturating_add(other.score);
+    }
+}
+
+/// Returns the standings of every player across the games, in no particular order
+///
+/// the players are merged by identity, see `Game::players_by_identity`,
+/// in each game first and then across the games,
+/// so a player that reconnects or renames between games keeps a single standing
+pub fn standings(games: &[&Game]) -> Vec<Standing> {
+    let mut standings: Vec<Standing> = Vec::new();
+    for (game_id, game) in games.iter().enumerate() {
+        for mut player in game.players_by_identity() {
+            // the score is kept with the player, so later merges don't score it again
+            let score = player.score(&game.scoring);
+            player.score_override = Some(score);
+            let mut standing = Standing {
+                player,
+                game_ids: BTreeSet::from([game_id]),
+                score,
+            };
+            let (same, others): (Vec<Standing>, Vec<Standing>) = standings
+                .into_iter()
+                .partition(|known| known.player.is_same_person(&standing.player));
+            if let Some((first, rest)) = same.split_first() {
+                let mut merged = first.clone();
+                for known in rest {
+                    merged.merge(known);
+                }
+                merged.merge(&standing);
+                merged.player.name = standing.player.name;
+                standing = merged;
+            }
+            standings = others;
+            standings.push(standing);
+        }
+    }
+    standings
+}
+
+#[cfg(test)]
+#[allow(clippy::default_numeric_fallback, clippy::unwrap_used)]
+// the tests unwrap the parsed logs and compare the standings with plain literals
+mod tests {
+    use super::*;
+    use crate::{quake3_data::MeanDeath, quake3_parser::parser::scan_file, scoring::ScoringRules};
+
+    #[test]
+    fn test_standings_sum_games() {
+        let log_content = r"
+        0:00 InitGame: \mapname\q3dm17
+        0:00 ClientConnect: 2
+        0:00 ClientUserinfoChanged: 2 n\Isgalamido\t\0
+        0:00 ClientConnect: 3
+        0:00 ClientUserinfoChanged: 3 n\Zeh\t\0
+        0:10 Kill: 2 3 10: Isgalamido killed Zeh by MOD_RAILGUN
+        0:20 Kill: 1022 3 22: <world> killed Zeh by MOD_TRIGGER_HURT
+        1:00 ShutdownGame:
+        0:00 InitGame: \mapname\q3dm6
+        0:00 ClientConnect: 4
+        0:00 ClientUserinfoChanged: 4 n\Isgalamido\t\0
+        0:10 Kill: 4 4 7: Isgalamido killed Isgalamido by MOD_ROCKET_SPLASH
+        0:00 ClientConnect: 5
+        0:00 ClientUserinfoChanged: 5 n\Mal\t\0
+        0:30 Kill: 4 5 10: Isgalamido killed Mal by MOD_RAILGUN
+        1:00 ShutdownGame:
+        ";
+
+        let games = scan_file(log_content).unwrap();
+        let mut standings = standings(&games.iter().collect::<Vec<&Game>>());
+        standings.sort_by(|a, b| a.player.name.cmp(&b.player.name));
+        let summary: Vec<(&str, u32, u32, u32, i32)> = standings
+            .iter()
+            .map(|standing| {
+                (
+                    standing.player.name.as_str(),
+                    standing.games(),
+                    standing.player.frags,
+                    standing.player.deaths,
+                    standing.score,
+                )
+            })
+            .collect();
+        assert_eq!(
+            summary,
+            vec![
+                ("Isgalamido", 2, 2, 1, 1),
+                ("Mal", 1, 0, 1, 0),
+                ("Zeh", 1, 0, 2, -1),
+            ]
+        );
+        assert_eq!(
+            standings[0].player.kills_by_means.get(&MeanDeath::Railgun),
+            Some(&2)
+        );
+    }
+
+    #[test]
+    fn test_standings_follow_renames_across_games() {
+        let log_content = r"
+        0:00 InitGame: \mapname\q3dm17
+        0:00 ClientConnect: 2
+        0:00 ClientUserinfoChanged: 2 n\Izzy\t\0\cl_guid\A1B2
+        0:10 Kill: 1022 2 22: <world> killed Izzy by MOD_TRIGGER_HURT
+        1:00 ShutdownGame:
+        0:00 InitGame: \mapname\q3dm6
+        0:00 ClientConnect: 2
+        0:00 ClientUserinfoChanged: 2 n\Isgalamido\t\0\cl_guid\A1B2
+        1:00 ShutdownGame:
+        ";
+
+        let games = scan_file(log_content).unwrap();
+        let standings = standings(&games.iter().collect::<Vec<&Game>>());
+        assert_eq!(standings.len(), 1);
+        assert_eq!(standings[0].player.name, "Isgalamido");
+        assert_eq!(standings[0].player.aliases(), vec!["Izzy"]);
+        assert_eq!(standings[0].games(), 2);
+        assert_eq!(standings[0].player.world_deaths, 1);
+    }
+
+    #[test]
+    fn test_standings_score_each_game_with_its_rules() {
+        let log_content = r"
+        0:00 InitGame: \mapname\q3dm17
+        0:00 ClientConnect: 2
+        0:00 ClientUserinfoChanged: 2 n\Isgalamido\t\0
+        0:00 ClientConnect: 3
+        0:00 ClientUserinfoChanged: 3 n\Zeh\t\0
+        0:10 Kill: 2 3 10: Isgalamido killed Zeh by MOD_RAILGUN
+        1:00 ShutdownGame:
+        0:00 InitGame: \mapname\q3dm6
+        0:00 ClientConnect: 2
+        0:00 ClientUserinfoChanged: 2 n\Isgalamido\t\0
+        0:00 ClientConnect: 3
+        0:00 ClientUserinfoChanged: 3 n\Zeh\t\0
+        0:10 Kill: 2 3 10: Isgalamido killed Zeh by MOD_RAILGUN
+        1:00 ShutdownGame:
+        ";
+
+        let mut games = scan_file(log_content).unwrap();
+        games[1].apply_scoring(ScoringRules {
+            frag: 3,
+            ..Default::default()
+        });
+        let standings = standings(&games.iter().collect::<Vec<&Game>>());
+        let isgalamido = standings
+            .iter()
+            .find(|standing| standing.player.name == "Isgalamido")
+            .unwrap();
+        assert_eq!(isgalamido.player.frags, 2);
+        assert_eq!(isgalamido.score, 4);
+    }
+
+    #[test]
+    fn test_standings_count_each_game_once() {
+        // Izzy and Isgalamido are two clients of the first game,
+        // then one player with the GUID of Izzy and the name of Isgalamido joins the second game
+        let log_content = r"
+        0:00 InitGame: \mapname\q3dm17
+        0:00 ClientConnect: 2
+        0:00 ClientUserinfoChanged: 2 n\Izzy\t\0\cl_guid\A1B2
+        0:00 ClientConnect: 3
+        0:00 ClientUserinfoChanged: 3 n\Isgalamido\t\0
+        0:00 ClientConnect: 4
+        0:00 ClientUserinfoChanged: 4 n\Mal\t\0
+        0:10 Kill: 2 4 10: Izzy killed Mal by MOD_RAILGUN
+        0:20 Kill: 3 4 10: Isgalamido killed Mal by MOD_RAILGUN
+        1:00 ShutdownGame:
+        0:00 InitGame: \mapname\q3dm6
+        0:00 ClientConnect: 2
+        0:00 ClientUserinfoChanged: 2 n\Isgalamido\t\0\cl_guid\A1B2
+        0:00 ClientConnect: 4
+        0:00 ClientUserinfoChanged: 4 n\Mal\t\0
+        0:10 Kill: 2 4 10: Isgalamido killed Mal by MOD_RAILGUN
+        1:00 ShutdownGame:
+        ";
+
+        let mut games = scan_file(log_content).unwrap();
+        games[1].apply_scoring(ScoringRules {
+            frag: 3,
+            ..Default::default()
+        });
+        let standings = standings(&games.iter().collect::<Vec<&Game>>());
+        assert_eq!(standings.len(), 2);
+        let isgalamido = standings
+            .iter()
+            .find(|standing| standing.player.name == "Isgalamido")
+            .unwrap();
+        assert_eq!(isgalamido.games(), 2);
+        assert_eq!(isgalamido.player.frags, 3);
+        assert_eq!(isgalamido.score, 5);
+        assert_eq!(isgalamido.player.score(&ScoringRules::default()), 5);
+    }
+}
@@ -5,6 +5,21 @@
 /// Module responsible for the CLI
 /// Both the CLI configuration and argument parsing
 mod cli;
+/// Module responsible for following a log still being written
+/// reporting each game as soon as it ends
+mod follow;
+/// Module responsible for the match history
+/// storing the games of the ingested logs in a `SQLite` database
+mod history;
+/// Module responsible for the incremental reading of the logs
+/// resuming from the checkpoint of the previous reading
+mod incremental;
+/// Module responsible for the log inputs
+/// expanding the files, directories, glob patterns and stdin into the logs to read
+mod inputs;
+/// Module responsible for the standings of the players across games
+/// the cross-game leaderboard
+mod leaderboard;
 /// Module responsible for the data representation from the log
 /// like the means of death and the players data
 /// the `PlayerData` struct and the `MeanDeath` enum
@@ -14,31 +29,253 @@ mod quake3_parser;
 /// Module responsible for the report generation
 /// both the text and html reports
 mod report;
+/// Module responsible for the scoring rules
+/// the presets and the scoring config file
+mod scoring;
+#[cfg(test)]
+#[allow(clippy::unwrap_used)]
+// the helpers unwrap, a failing file operation fails the test using them
+/// Module with the logs and log file helpers shared by the tests
+mod test_support;
 
-use cli::Cli;
-use quake3_parser::parser::{scan_file, Game};
-use report::get_report;
+use cli::{Cli, Command, FollowArgs, IngestArgs, ParseArgs, ReportFormat, ScoringArgs};
+use follow::{LiveUpdate, LogFollower};
+use history::MatchHistory;
+use incremental::{read_appended, Resumed};
+use inputs::log_sources;
+use quake3_parser::parser::{Game, ParseMode};
+use report::{get_game_report, get_report};
+use scoring::ScoringRules;
 
 use clap::Parser;
-use std::fs;
+use std::{fs, path::PathBuf, thread, time::Duration};
 
 #[cfg_attr(coverage_nightly, coverage(off))]
 /// main function
 fn main() {
     let cli = Cli::parse();
 
-    let filepath = &cli.log_file;
-    let log_str = fs::read_to_string(filepath).expect("Error reading file");
+    match &cli.command {
+        Some(Command::Ingest(args)) => ingest(args),
+        Some(Command::Follow(args)) => follow(args),
+        None => report(&cli),
+    }
+}
+
+#[cfg_attr(coverage_nightly, coverage(off))]
+/// Returns the parse mode asked for
+const fn parse_mode(parse: &ParseArgs) -> ParseMode {
+    if parse.strict {
+        ParseMode::Strict
+    } else {
+        ParseMode::Lenient
+    }
+}
+
+#[cfg_attr(coverage_nightly, coverage(off))]
+/// Returns the scoring rules asked for
+/// `None` if the scoring config can't be read, after printing the error
+fn scoring_rules(scoring: &ScoringArgs) -> Option<ScoringRules> {
+    let Some(config_file) = &scoring.scoring_config else {
+        return Some(ScoringRules::from(scoring.preset));
+    };
+    match ScoringRules::from_config_file(config_file, scoring.preset) {
+        Ok(rules) => Some(rules),
+        Err(err) => {
+            eprintln!(
+                "Error reading scoring config {}: {err}",
+                config_file.display()
+            );
+            None
+        }
+    }
+}
+
+#[cfg_attr(coverage_nightly, coverage(off))]
+/// Scores the players of a game with the rules, printing a warning for every score
+/// that differs from the one printed by the server, whose scores are used when asked
+fn score_game(game: &mut Game, game_number: usize, rules: ScoringRules, scoring: &ScoringArgs) {
+    game.apply_scoring(rules);
+    for mismatch in game.score_mismatches() {
+        eprintln!("Warning: game {game_number}: {mismatch}");
+    }
+    if scoring.server_scores {
+        game.use_server_scores();
+    }
+}
+
+#[cfg_attr(coverage_nightly, coverage(off))]
+/// Parses the logs of the inputs one after the other, printing a warning for every skipped line
+/// `None` if a log can't be parsed, after printing the error
+fn parse_log_files(inputs: &[PathBuf], parse: &ParseArgs) -> Option<Vec<Game>> {
+    let sources = match log_sources(inputs) {
+        Ok(sources) => sources,
+        Err(err) => {
+            eprintln!("Error: {err}");
+            return None;
+        }
+    };
+
+    let mode = parse_mode(parse);
+    let mut games = Vec::new();
+    for source in &sources {
+        let parsed = match source.parse(mode, parse.timestamps) {
+            Ok(parsed) => parsed,
+            Err(err) => {
+                eprintln!("Error parsing file {source}: {err}");
+                return None;
+            }
+        };
+        for diagnostic in &parsed.diagnostics {
+            if sources.len() > 1 {
+                eprintln!("Warning: {source}: skipped line: {diagnostic}");
+            } else {
+                eprintln!("Warning: skipped line: {diagnostic}");
+            }
+        }
+        games.extend(parsed.games);
+    }
+    Some(games)
+}
+
+#[cfg_attr(coverage_nightly, coverage(off))]
+/// The `ingest` command
+/// stores the games appended to the log file since its last ingestion
+/// in the match history database
+fn ingest(args: &IngestArgs) {
+    let log_file = match fs::canonicalize(&args.log_file) {
+        Ok(log_file) => log_file,
+        Err(err) => {
+            eprintln!("Error reading file {}: {err}", args.log_file.display());
+            return;
+        }
+    };
+    let mut history = match MatchHistory::open(&args.database) {
+        Ok(history) => history,
+        Err(err) => {
+            eprintln!("Error opening database {}: {err}", args.database.display());
+            return;
+        }
+    };
 
-    let games: Vec<Game> = match scan_file(&log_str) {
-        Ok(games) => games,
+    let log_name = log_file.display().to_string();
+    let checkpoint = if args.from_start {
+        None
+    } else {
+        match history.checkpoint(&log_name) {
+            Ok(checkpoint) => checkpoint,
+            Err(err) => {
+                eprintln!("Could not ingest {log_name}: {err}");
+                return;
+            }
+        }
+    };
+    let mode = parse_mode(&args.parse);
+    let appended = match read_appended(&log_file, mode, args.parse.timestamps, checkpoint) {
+        Ok(appended) => appended,
         Err(err) => {
-            eprintln!("Error parsing file {filepath:?}: {err}");
+            eprintln!("Error parsing file {log_name}: {err}");
             return;
         }
     };
+    match appended.resumed {
+        Resumed::FromStart | Resumed::FromCheckpoint => {}
+        changed @ (Resumed::Rotated | Resumed::Truncated) => {
+            eprintln!(
+                "Warning: {log_name} was {changed} since its last ingestion, reading it from its start"
+            );
+        }
+    }
+    for diagnostic in &appended.parsed.diagnostics {
+        eprintln!("Warning: skipped line: {diagnostic}");
+    }
+
+    match history.ingest(&log_name, &appended) {
+        Ok(summary) => println!(
+            "Ingested {log_name} into {}: {summary}",
+            args.database.display()
+        ),
+        Err(err) => eprintln!("Could not ingest {log_name}: {err}"),
+    }
+}
+
+#[cfg_attr(coverage_nightly, coverage(off))]
+/// The `follow` command
+/// prints the report of each game of the log as soon as it ends, and its kills when asked,
+/// until the log can't be read anymore
+fn follow(args: &FollowArgs) {
+    let Some(rules) = scoring_rules(&args.scoring) else {
+        return;
+    };
+    let log_name = args.log_file.display();
+    let mode = parse_mode(&args.parse);
+    let mut follower =
+        match LogFollower::open(&args.log_file, mode, args.parse.timestamps, args.kill_feed) {
+            Ok(follower) => follower,
+            Err(err) => {
+                eprintln!("Error parsing file {log_name}: {err}");
+                return;
+            }
+        };
+    eprintln!("Following {log_name}");
+
+    loop {
+        match follower.next_update() {
+            Ok(Some(LiveUpdate::Kill(kill))) => println!("{kill}"),
+            Ok(Some(LiveUpdate::GameEnded {
+                mut game,
+                game_number,
+            })) => {
+                score_game(&mut game, game_number, rules, &args.scoring);
+                let report = get_game_report(
+                    &game,
+                    game_number,
+                    &args.report_type,
+                    &ReportFormat::Text,
+                    &args.rank_by,
+                );
+                match report {
+                    Ok(report) => println!("{report}"),
+                    Err(err) => eprintln!("Could not generate report: {err}"),
+                }
+            }
+            Ok(Some(LiveUpdate::Skipped(diagnostic))) => {
+                eprintln!("Warning: skipped line: {diagnostic}");
+            }
+            Ok(Some(LiveUpdate::Reopened(changed))) => {
+                eprintln!("Warning: {log_name} was {changed}, following it from its start");
+            }
+            Ok(None) => thread::sleep(Duration::from_millis(args.interval)),
+            Err(err) => {
+                eprintln!("Error parsing file {log_name}: {err}");
+                return;
+            }
+        }
+    }
+}
+
+#[cfg_attr(coverage_nightly, coverage(off))]
+/// The report on the games of the log file, without a command
+fn report(cli: &Cli) {
+    let Some(rules) = scoring_rules(&cli.scoring) else {
+        return;
+    };
+
+    let Some(mut games) = parse_log_files(&cli.log_files, &cli.parse) else {
+        return;
+    };
+
+    for (game, game_number) in games.iter_mut().zip(1_usize..) {
+        score_game(game, game_number, rules, &cli.scoring);
+    }
 
-    let result = get_report(&games, &cli.report_type, &cli.report_format);
+    let result = get_report(
+        &games,
+        &cli.report_type,
+        &cli.report_format,
+        &cli.rank_by,
+        !cli.hide_incomplete,
+    );
     match result {
         Ok(term_table) => match &cli.output_file {
             Some(output_file) => {
@@ -52,10 +289,13 @@ fn main() {
 }
 
 #[cfg(test)]
+#[allow(clippy::unwrap_used)]
+// a failing unwrap fails the test, with the error
 mod tests {
-    use self::cli::{ReportFormat, ReportType};
+    use self::cli::{RankBy, ReportFormat, ReportType};
 
     use super::*;
+    use quake3_parser::parser::scan_file;
 
     #[test]
     fn pseudo_integration_test_imitating_main() {
@@ -79,30 +319,39 @@ mod tests {
 " 20:37 Kill: 1022 2 22: <world> killed Player1 by MOD_TRIGGER_HURT\n",
 " 20:37 Kill: 3 2 7: Player2 killed Player1 by MOD_ROCKET_SPLASH\n",
 " 20:37 Kill: 3 2 7: Player2 killed Player1 by MOD_ROCKET_SPLASH\n",
+" 20:37 Exit: Fraglimit hit.\n",
 " 20:37 ShutdownGame:\n",
 " 20:37 ------------------------------------------------------------",
         );
 
-        let games = scan_file(&log_str).unwrap();
-        let result = get_report(&games, &ReportType::All, &ReportFormat::Text).unwrap();
+        let games = scan_file(log_str).unwrap();
+        let result = get_report(
+            &games,
+            &ReportType::All,
+            &ReportFormat::Text,
+            &RankBy::Score,
+            true,
+        )
+        .unwrap();
 
         let expected = concat!(
-            "╭────────┬──────────────────┬─────────────────┬──────────────────╮\n",
-            "│        │                  │                 │                  │\n",
-            "│        │ Total game kills │ Kill Rank       │  Death Causes    │\n",
-            "│        │                  │ (Player: Score) │  (Cause: Count)  │\n",
-            "│        │                  │                 │                  │\n",
-            "├────────┼──────────────────┼─────────────────┼──────────────────┤\n",
-            "│        │                  │                 │                  │\n",
-            "│ Game 1 │        1         │   Player1: -1   │  TriggerHurt: 1  │\n",
-            "│        │                  │                 │                  │\n",
-            "├────────┼──────────────────┼─────────────────┼──────────────────┤\n",
-            "│        │                  │                 │                  │\n",
-            "│        │                  │   Player2: 2    │ Rocket Splash: 2 │\n",
-            "│ Game 2 │        3         │                 │                  │\n",
-            "│        │                  │   Player1: -1   │ TriggerHurt: 1   │\n",
-            "│        │                  │                 │                  │\n",
-            "╰────────┴──────────────────┴─────────────────┴──────────────────╯",
+            "╭──────────────┬──────────────────┬───────────────┬─────────────────┬──────────────────╮\n",
+            "│              │                  │               │                 │                  │\n",
+            "│              │ Total game kills │ Outcome       │ Kill Rank       │  Death Causes    │\n",
+            "│              │                  │ (Winner, End) │ (Player: Score) │  (Cause: Count)  │\n",
+            "│              │                  │               │                 │                  │\n",
+            "├──────────────┼──────────────────┼───────────────┼─────────────────┼──────────────────┤\n",
+            "│ Game 1       │                  │               │                 │                  │\n",
+            "│ q3dm17       │        1         │   No winner   │   Player1: -1   │  TriggerHurt: 1  │\n",
+            "│ Free For All │                  │   Aborted     │                 │                  │\n",
+            "│ 20:37        │                  │               │                 │                  │\n",
+            "├──────────────┼──────────────────┼───────────────┼─────────────────┼──────────────────┤\n",
+            "│ Game 2       │                  │               │                 │                  │\n",
+            "│ q3dm17       │                  │ Player2       │   Player2: 2    │ Rocket Splash: 2 │\n",
+            "│ Free For All │        3         │ Fraglimit hit │                 │                  │\n",
+            "│ 20:37        │                  │               │   Player1: -1   │ TriggerHurt: 1   │\n",
+            "│              │                  │               │                 │                  │\n",
+            "╰──────────────┴──────────────────┴───────────────┴─────────────────┴──────────────────╯",
         );
 
         assert_eq!(result.to_string(), expected);
@@ -1,36 +1,252 @@
-use std::fmt::Display;
+use crate::scoring::ScoringRules;
+use serde::{Deserialize, Serialize};
+use std::{
+    collections::{BTreeMap, HashMap},
+    fmt::Display,
+};
 
 /// The world id is hardcoded as 1022
 pub const WORLD_ID: u32 = 1022;
 
-#[derive(Debug, Clone, PartialEq, Eq)]
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
 /// The player data struct
-/// Contains the player name and the number of kills
-/// The number of kills is decremented when the world kills a player
-///
-/// Implements custom `PartialOrd` and `Ord` to allow sorting by the number of kills
+/// Contains the player name, the kill counters and the item pickups
+/// The score, following the game scoring rules, and the kill/death ratio
+/// are derived from the kill counters
 pub struct PlayerData {
     /// The player name
     pub name: String,
-    /// The player score
-    pub kills: i32,
+    #[serde(default)]
+    /// The enemies killed by the player
+    pub frags: u32,
+    #[serde(default)]
+    /// The times the player died, whatever killed it
+    pub deaths: u32,
+    #[serde(default)]
+    /// The times the player killed itself, also counted in `deaths`
+    pub suicides: u32,
+    #[serde(default)]
+    /// The times the world killed the player (e.g. falling), also counted in `deaths`
+    pub world_deaths: u32,
+    #[serde(default)]
+    /// The teammates killed by the player, in team games
+    pub team_kills: u32,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    /// The score to report instead of the computed one, see `Game::use_server_scores`
+    pub score_override: Option<i32>,
+    #[serde(default)]
+    /// The item pickups
+    /// is represented by a hashmap with the item name (e.g. `item_armor_body`) as key
+    /// and the number of pickups as value
+    pub items: HashMap<String, u32>,
+    #[serde(default)]
+    /// The enemies and teammates killed by the player, by mean of death
+    /// its suicides are left out
+    pub kills_by_means: HashMap<MeanDeath, u32>,
+    #[serde(default)]
+    /// The sessions of the player in the game, in the order they happened
+    pub sessions: Vec<Session>,
+    #[serde(default)]
+    /// The team of the player, the last one set in its user info
+    pub team: Team,
+    #[serde(default)]
+    /// Every team the player joined, in the order it happened
+    pub team_history: Vec<TeamChange>,
+    #[serde(default)]
+    /// The score printed by the server in the `score:` lines at the end of the game
+    /// `None` if the server didn't print one for the player, e.g. it disconnected before
+    pub server_score: Option<i32>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    /// The player GUID, from the `cl_guid` field of its user info, if the server logs it
+    pub guid: Option<String>,
+    #[serde(default)]
+    /// Every name the player used, in the order they were first used
+    pub name_history: Vec<String>,
+}
+
+#[allow(clippy::struct_field_names)]
+// every field is a timestamp of the session
+// and the `_at` postfix makes it clear
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+/// A client slot occupation, from `ClientConnect` to `ClientDisconnect`
+/// The timestamps are the seconds on the log clock
+pub struct Session {
+    /// When the client connected
+    pub connected_at: u32,
+    /// When the client entered the game, if it ever did
+    /// only the first `ClientBegin` of the session counts
+    pub began_at: Option<u32>,
+    /// When the client disconnected
+    /// the game end, if the client was still connected then
+    pub disconnected_at: Option<u32>,
 }
 
-impl PartialOrd for PlayerData {
-    fn partial_cmp(&self, other: &Self) -> Option<std::cmp::Ordering> {
-        Some(self.cmp(other))
+impl Session {
+    /// Returns the seconds actually spent in the game
+    /// from `ClientBegin` until the disconnection
+    ///
+    /// An open session or one that never began counts as zero
+    pub const fn time_played(&self) -> u32 {
+        match (self.began_at, self.disconnected_at) {
+            (Some(began_at), Some(disconnected_at)) => disconnected_at.saturating_sub(began_at),
+            _ => 0,
+        }
+    }
+
+    /// Returns whether both sessions were connected at the same time
+    /// an open session lasts until the game end,
+    /// and a client reconnecting the moment it disconnected does not overlap itself
+    pub fn overlaps(&self, other: &Self) -> bool {
+        let end = |session: &Self| session.disconnected_at.unwrap_or(u32::MAX);
+        self.connected_at < end(other) && other.connected_at < end(self)
     }
 }
 
-impl Ord for PlayerData {
-    /// Sorts by the number of kills in descending order
-    /// The player with the most kills is first
-    fn cmp(&self, other: &Self) -> std::cmp::Ordering {
-        other.kills.cmp(&self.kills)
+impl PlayerData {
+    /// Returns the item pickups grouped by item class
+    /// only the classes with at least one pickup are present
+    pub fn items_by_class(&self) -> BTreeMap<ItemClass, u32> {
+        let mut items_by_class: BTreeMap<ItemClass, u32> = BTreeMap::new();
+        for (item, count) in &self.items {
+            let class_count = items_by_class
+                .entry(ItemClass::from(item.as_str()))
+                .or_default();
+            *class_count = class_count.saturating_add(*count);
+        }
+        items_by_class
+    }
+
+    /// Returns the seconds the player spent in the game, over all sessions
+    pub fn time_played(&self) -> u32 {
+        self.sessions.iter().fold(0, |total, session| {
+            total.saturating_add(session.time_played())
+        })
+    }
+
+    /// Returns the player frags per minute in the game
+    /// `None` if the player never spent a second in the game
+    pub fn kills_per_minute(&self) -> Option<f64> {
+        let time_played = self.time_played();
+        (time_played > 0).then(|| f64::from(self.frags) * 60.0_f64 / f64::from(time_played))
+    }
+
+    /// Returns the player score
+    /// the `score_override` if set, the score computed with the scoring rules otherwise
+    /// by default a frag is worth a point and suicides, world deaths and team kills cost one
+    pub fn score(&self, scoring: &ScoringRules) -> i32 {
+        self.score_override.unwrap_or_else(|| scoring.score(self))
+    }
+
+    /// Returns the frags per death
+    /// a player that never died has its frags as ratio
+    pub fn kd_ratio(&self) -> f64 {
+        f64::from(self.frags) / f64::from(self.deaths.max(1))
+    }
+
+    /// Returns the other names the player used, in the order they were first used
+    pub fn aliases(&self) -> Vec<&str> {
+        self.name_history
+            .iter()
+            .map(String::as_str)
+            .filter(|name| *name != self.name)
+            .collect()
+    }
+
+    /// Returns whether both players, of different games, are the same person
+    ///
+    /// players with a GUID are the same person when their GUIDs match,
+    /// otherwise when they share any name, current or past
+    pub fn is_same_person(&self, other: &Self) -> bool {
+        if let (Some(guid), Some(other_guid)) = (&self.guid, &other.guid) {
+            return guid == other_guid;
+        }
+        self.names()
+            .any(|name| other.names().any(|other_name| other_name == name))
+    }
+
+    /// Returns whether both players, of the same game, are the same person
+    ///
+    /// clients connected at the same time are never the same person,
+    /// otherwise players with a GUID are the same person when their GUIDs match,
+    /// and the others when the later one reconnected under the name the earlier one left with
+    pub fn is_same_person_in_game(&self, other: &Self) -> bool {
+        if self.played_alongside(other) {
+            return false;
+        }
+        if let (Some(guid), Some(other_guid)) = (&self.guid, &other.guid) {
+            return guid == other_guid;
+        }
+        let first_connected_at =
+            |player: &Self| player.sessions.first().map(|session| session.connected_at);
+        let (earlier, later) = if first_connected_at(self) <= first_connected_at(other) {
+            (self, other)
+        } else {
+            (other, self)
+        };
+        later.name_history.first().unwrap_or(&later.name) == &earlier.name
+    }
+
+    /// Returns whether any session of the player overlaps a session of the other player
+    fn played_alongside(&self, other: &Self) -> bool {
+        self.sessions.iter().any(|session| {
+            other
+                .sessions
+                .iter()
+                .any(|other_session| session.overlaps(other_session))
+        })
+    }
+
+    /// Returns the current name of the player followed by its name history
+    fn names(&self) -> impl Iterator<Item = &str> {
+        std::iter::once(self.name.as_str()).chain(self.name_history.iter().map(String::as_str))
+    }
+
+    /// Merges the data of another player into this one
+    /// kill counters, scores, pickups and kills by mean of death are summed,
+    /// the sessions are appended and the names the other player used are added to the name history
+    ///
+    /// the computed scores of both players follow the `scoring` rules
+    pub fn merge(&mut self, other: &Self, scoring: &ScoringRules) {
+        let add_scores = |score: Option<i32>, other_score: Option<i32>| match (score, other_score) {
+            (Some(score), Some(other_score)) => Some(score.saturating_add(other_score)),
+            (score, other_score) => score.or(other_score),
+        };
+        // a player without an override keeps counting its computed score
+        self.score_override = match (self.score_override, other.score_override) {
+            (None, None) => None,
+            (score, other_score) => add_scores(
+                Some(score.unwrap_or_else(|| scoring.score(self))),
+                Some(other_score.unwrap_or_else(|| scoring.score(other))),
+            ),
+        };
+        self.server_score = add_scores(self.server_score, other.server_score);
+        self.frags = self.frags.saturating_add(other.frags);
+        self.deaths = self.deaths.saturating_add(other.deaths);
+        self.suicides = self.suicides.saturating_add(other.suicides);
+        self.world_deaths = self.world_deaths.saturating_add(other.world_deaths);
+        self.team_kills = self.team_kills.saturating_add(other.team_kills);
+        for (item, count) in &other.items {
+            let item_count = self.items.entry(item.clone()).or_default();
+            *item_count = item_count.saturating_add(*count);
+        }
+        for (mean, count) in &other.kills_by_means {
+            let mean_count = self.kills_by_means.entry(mean.clone()).or_default();
+            *mean_count = mean_count.saturating_add(*count);
+        }
+        self.sessions.extend(other.sessions.iter().copied());
+        self.team_history.extend(other.team_history.iter().copied());
+        for name in &other.name_history {
+            if !self.name_history.contains(name) {
+                self.name_history.push(name.clone());
+            }
+        }
+        if self.guid.is_none() {
+            self.guid.clone_from(&other.guid);
+        }
     }
 }
 
-#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+#[derive(Debug, Clone, PartialEq, Eq, Hash, PartialOrd, Ord, Serialize, Deserialize)]
 #[allow(clippy::missing_docs_in_private_items)]
 /// The means of death enum
 /// Contains the possible means of death in Quake 3
@@ -66,6 +282,59 @@ pub enum MeanDeath {
     Grapple,
 }
 
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord, Serialize, Deserialize)]
+/// The item classes enum
+/// Groups the Quake 3 items by their kind, based on the item name
+pub enum ItemClass {
+    /// Weapons (e.g. `weapon_rocketlauncher`)
+    Weapon,
+    /// Ammo boxes (e.g. `ammo_rockets`)
+    Ammo,
+    /// Armor (e.g. `item_armor_body`, the red armor)
+    Armor,
+    /// Health (e.g. `item_health_mega`, the mega health)
+    Health,
+    /// Powerups (e.g. `item_quad`)
+    Powerup,
+    /// Holdable items (e.g. `holdable_medkit`)
+    Holdable,
+    /// CTF flags (e.g. `team_CTF_redflag`)
+    Flag,
+    /// Any item not known by the parser
+    Other,
+}
+
+impl From<&str> for ItemClass {
+    fn from(item: &str) -> Self {
+        match item {
+            "item_quad" | "item_enviro" | "item_haste" | "item_invis" | "item_regen"
+            | "item_flight" => Self::Powerup,
+            _ if item.starts_with("weapon_") => Self::Weapon,
+            _ if item.starts_with("ammo_") => Self::Ammo,
+            _ if item.starts_with("item_armor_") => Self::Armor,
+            _ if item.starts_with("item_health") => Self::Health,
+            _ if item.starts_with("holdable_") => Self::Holdable,
+            _ if item.starts_with("team_CTF_") => Self::Flag,
+            _ => Self::Other,
+        }
+    }
+}
+
+impl Display for ItemClass {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match *self {
+            Self::Weapon => write!(f, "Weapon"),
+            Self::Ammo => write!(f, "Ammo"),
+            Self::Armor => write!(f, "Armor"),
+            Self::Health => write!(f, "Health"),
+            Self::Powerup => write!(f, "Powerup"),
+            Self::Holdable => write!(f, "Holdable"),
+            Self::Flag => write!(f, "Flag"),
+            Self::Other => write!(f, "Other"),
+        }
+    }
+}
+
 impl From<u32> for MeanDeath {
     fn from(id: u32) -> Self {
         match id {
@@ -138,69 +407,685 @@ impl Display for MeanDeath {
     }
 }
 
+#[derive(
+    Debug, Clone, Copy, Default, PartialEq, Eq, Hash, PartialOrd, Ord, Serialize, Deserialize,
+)]
+/// The team enum
+/// From the `t` field of the player user info
+pub enum Team {
+    #[default]
+    /// No team, as in free for all and tournament games (`t\0`)
+    Free,
+    /// The red team (`t\1`)
+    Red,
+    /// The blue team (`t\2`)
+    Blue,
+    /// Watching the game (`t\3`)
+    Spectator,
+}
+
+impl From<&str> for Team {
+    fn from(team: &str) -> Self {
+        match team {
+            "1" => Self::Red,
+            "2" => Self::Blue,
+            "3" => Self::Spectator,
+            _ => Self::Free,
+        }
+    }
+}
+
+impl Display for Team {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match *self {
+            Self::Free => write!(f, "Free"),
+            Self::Red => write!(f, "Red"),
+            Self::Blue => write!(f, "Blue"),
+            Self::Spectator => write!(f, "Spectator"),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+/// A player joining a team
+pub struct TeamChange {
+    /// When the player joined the team, in seconds on the log clock
+    pub time: u32,
+    /// The team joined
+    pub team: Team,
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+/// The kill statistics of a team
+/// Kills are credited to the team the killer was in at the time of the kill
+pub struct TeamStats {
+    /// Enemies killed by the team players
+    pub kills: u32,
+    /// Teammates killed by the team players
+    pub team_kills: u32,
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash, Serialize, Deserialize)]
+/// The game type enum
+/// From the `g_gametype` server setting
+pub enum GameType {
+    #[default]
+    /// Every player for themselves (`g_gametype\0`), single player games (`2`) play the same
+    FreeForAll,
+    /// One on one duel (`g_gametype\1`)
+    Tournament,
+    /// Red team against blue team, scored by frags (`g_gametype\3`)
+    TeamDeathmatch,
+    /// Red team against blue team, scored by flag captures (`g_gametype\4`)
+    CaptureTheFlag,
+}
+
+impl GameType {
+    /// Returns whether players play in the red and blue teams
+    pub const fn is_team_game(self) -> bool {
+        matches!(self, Self::TeamDeathmatch | Self::CaptureTheFlag)
+    }
+
+    /// Returns whether the server also scores the objectives besides the kills,
+    /// e.g. the flag captures, returns and assists in capture the flag
+    pub const fn is_objective_game(self) -> bool {
+        matches!(self, Self::CaptureTheFlag)
+    }
+}
+
+impl From<u32> for GameType {
+    /// Unknown game types, e.g. from mods, are treated as free for all
+    fn from(game_type: u32) -> Self {
+        match game_type {
+            1 => Self::Tournament,
+            3 => Self::TeamDeathmatch,
+            4 => Self::CaptureTheFlag,
+            _ => Self::FreeForAll,
+        }
+    }
+}
+
+impl Display for GameType {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match *self {
+            Self::FreeForAll => write!(f, "Free For All"),
+            Self::Tournament => write!(f, "Tournament"),
+            Self::TeamDeathmatch => write!(f, "Team Deathmatch"),
+            Self::CaptureTheFlag => write!(f, "Capture The Flag"),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+/// The server settings of a game
+/// Parsed from the backslash separated cvar list of the `InitGame:` line
+/// (e.g. `\sv_hostname\Code Miner Server\g_gametype\0\mapname\q3dm17`)
+///
+/// The common cvars have typed fields, every other cvar is kept in `other`
+pub struct GameSettings {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    /// The map name, from `mapname`
+    pub map_name: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    /// The game type number, from `g_gametype`
+    pub game_type: Option<u32>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    /// The frag limit, from `fraglimit`
+    pub frag_limit: Option<u32>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    /// The time limit in minutes, from `timelimit`
+    pub time_limit: Option<u32>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    /// The capture limit, from `capturelimit`
+    pub capture_limit: Option<u32>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    /// The server name, from `sv_hostname`
+    pub hostname: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    /// The server version, from `version`
+    pub version: Option<String>,
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    /// Every other cvar, by cvar name
+    /// a typed cvar whose value is not a number is kept here as well
+    pub other: BTreeMap<String, String>,
+}
+
+impl From<&str> for GameSettings {
+    fn from(cvars: &str) -> Self {
+        let mut settings = Self::default();
+        let mut parts = cvars.trim().trim_start_matches('\\').split('\\');
+        while let (Some(key), Some(value)) = (parts.next(), parts.next()) {
+            let number = value.parse::<u32>().ok();
+            match key {
+                "mapname" => settings.map_name = Some(value.to_owned()),
+                "sv_hostname" => settings.hostname = Some(value.to_owned()),
+                "version" => settings.version = Some(value.to_owned()),
+                "g_gametype" if number.is_some() => settings.game_type = number,
+                "fraglimit" if number.is_some() => settings.frag_limit = number,
+                "timelimit" if number.is_some() => settings.time_limit = number,
+                "capturelimit" if number.is_some() => settings.capture_limit = number,
+                _ => {
+                    settings.other.insert(key.to_owned(), value.to_owned());
+                }
+            }
+        }
+        settings
+    }
+}
+
+impl GameSettings {
+    /// Returns every setting by its cvar name, as in the `InitGame:` line
+    /// the typed settings back under their cvar names, next to the other cvars
+    pub fn cvars(&self) -> BTreeMap<String, String> {
+        let mut cvars = self.other.clone();
+        let typed = [
+            ("mapname", self.map_name.clone()),
+            ("sv_hostname", self.hostname.clone()),
+            ("version", self.version.clone()),
+            ("g_gametype", self.game_type.map(|value| value.to_string())),
+            ("fraglimit", self.frag_limit.map(|value| value.to_string())),
+            ("timelimit", self.time_limit.map(|value| value.to_string())),
+            (
+                "capturelimit",
+                self.capture_limit.map(|value| value.to_string()),
+            ),
+        ];
+        for (key, value) in typed {
+            if let Some(value) = value {
+                cvars.insert(key.to_owned(), value);
+            }
+        }
+        cvars
+    }
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+/// The exit reason enum
+/// Why a game ended, from the `Exit:` line of the log
+pub enum ExitReason {
+    /// A player reached the frag limit
+    Fraglimit,
+    /// The time limit was hit
+    Timelimit,
+    /// A team reached the capture limit
+    Capturelimit,
+    /// Any other reason logged by the server
+    Other(String),
+    #[default]
+    /// The game ended without an `Exit:` line
+    /// e.g. the server changed map or shut down mid game
+    Aborted,
+}
+
+impl From<&str> for ExitReason {
+    fn from(reason: &str) -> Self {
+        match reason.trim().trim_end_matches('.') {
+            "Fraglimit hit" => Self::Fraglimit,
+            "Timelimit hit" => Self::Timelimit,
+            "Capturelimit hit" => Self::Capturelimit,
+            other => Self::Other(other.to_owned()),
+        }
+    }
+}
+
+impl Display for ExitReason {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Fraglimit => write!(f, "Fraglimit hit"),
+            Self::Timelimit => write!(f, "Timelimit hit"),
+            Self::Capturelimit => write!(f, "Capturelimit hit"),
+            Self::Other(reason) => write!(f, "{reason}"),
+            Self::Aborted => write!(f, "Aborted"),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+/// Why a game never reached its `ShutdownGame:` line
+pub enum IncompleteReason {
+    /// A new game started before the shutdown of this one
+    /// e.g. the server crashed and was restarted
+    NewGameStarted,
+    /// The log ended before the shutdown of the game
+    /// e.g. a partial copy of the log or a game still running
+    LogEnded,
+}
+
+impl Display for IncompleteReason {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::NewGameStarted => write!(f, "new game started before shutdown"),
+            Self::LogEnded => write!(f, "log ended before shutdown"),
+        }
+    }
+}
+
 #[cfg(test)]
+#[allow(
+    clippy::as_conversions,
+    clippy::default_numeric_fallback,
+    clippy::shadow_unrelated
+)]
+// the tests build players from plain literals
 mod tests {
     use super::*;
     use proptest::prelude::*;
+    use std::cmp::Ordering;
 
     prop_compose! {
-        fn arb_player_data_pos()(name in "[a-z]*", kills in 0..i32::MAX) -> PlayerData {
-            PlayerData { name, kills }
+        fn arb_player_data_pos()(name in "[a-z]*", frags in 0..i32::MAX.unsigned_abs()) -> PlayerData {
+            PlayerData { name, frags, ..Default::default() }
         }
     }
 
     prop_compose! {
         fn arb_players_pos()
         (player_data in arb_player_data_pos())
-        (name in "[a-z]*", kills in 0..player_data.kills, player_data in Just(player_data))
+        (name in "[a-z]*", frags in 0..player_data.frags, player_data in Just(player_data))
         -> (PlayerData, PlayerData) {
-            (player_data, PlayerData { name, kills })
+            (player_data, PlayerData { name, frags, ..Default::default() })
         }
     }
 
     prop_compose! {
-        fn arb_player_data_neg()(name in "[a-z]*", kills in i32::MIN..0) -> PlayerData {
-            PlayerData { name, kills }
+        fn arb_player_data_neg()(name in "[a-z]*", world_deaths in 1..=i32::MIN.unsigned_abs()) -> PlayerData {
+            PlayerData { name, deaths: world_deaths, world_deaths, ..Default::default() }
         }
     }
 
     prop_compose! {
         fn arb_players_neg()
         (player_data in arb_player_data_neg())
-        (name in "[a-z]*", kills in player_data.kills..0, player_data in Just(player_data))
+        (name in "[a-z]*", world_deaths in 0..player_data.world_deaths, player_data in Just(player_data))
         -> (PlayerData, PlayerData) {
-            (player_data, PlayerData { name, kills })
+            (player_data, PlayerData { name, deaths: world_deaths, world_deaths, ..Default::default() })
         }
     }
 
     proptest! {
         #[test]
         fn test_player_data_ordering_pos((a_player, other_player) in arb_players_pos()) {
-            prop_assert!(a_player < other_player);
+            prop_assert_eq!(ScoringRules::default().compare(&a_player, &other_player), Ordering::Less);
         }
     }
 
     proptest! {
         #[test]
         fn test_player_data_ordering_neg((a_player, other_player) in arb_players_neg()) {
-            prop_assert!(a_player > other_player);
+            prop_assert_eq!(ScoringRules::default().compare(&a_player, &other_player), Ordering::Greater);
         }
     }
 
     proptest! {
         #[test]
-        fn test_player_data_ordering_follows_kills_pos((a_player, other_player) in arb_players_pos()) {
-            prop_assert_eq!(a_player.cmp(&other_player), other_player.kills.cmp(&a_player.kills));
+        fn test_player_data_ordering_follows_score_pos((a_player, other_player) in arb_players_pos()) {
+            let scoring = ScoringRules::default();
+            prop_assert_eq!(scoring.compare(&a_player, &other_player), other_player.score(&scoring).cmp(&a_player.score(&scoring)));
         }
     }
 
     proptest! {
         #[test]
-        fn test_player_data_ordering_follows_kills_neg((a_player, other_player) in arb_players_neg()) {
-            prop_assert_eq!(a_player.cmp(&other_player), other_player.kills.cmp(&a_player.kills));
+        fn test_player_data_ordering_follows_score_neg((a_player, other_player) in arb_players_neg()) {
+            let scoring = ScoringRules::default();
+            prop_assert_eq!(scoring.compare(&a_player, &other_player), other_player.score(&scoring).cmp(&a_player.score(&scoring)));
         }
     }
 
+    #[test]
+    fn test_item_class_from_item() {
+        assert_eq!(ItemClass::from("weapon_rocketlauncher"), ItemClass::Weapon);
+        assert_eq!(ItemClass::from("ammo_slugs"), ItemClass::Ammo);
+        assert_eq!(ItemClass::from("item_armor_body"), ItemClass::Armor);
+        assert_eq!(ItemClass::from("item_armor_shard"), ItemClass::Armor);
+        assert_eq!(ItemClass::from("item_health"), ItemClass::Health);
+        assert_eq!(ItemClass::from("item_health_mega"), ItemClass::Health);
+        assert_eq!(ItemClass::from("item_quad"), ItemClass::Powerup);
+        assert_eq!(ItemClass::from("item_haste"), ItemClass::Powerup);
+        assert_eq!(ItemClass::from("holdable_medkit"), ItemClass::Holdable);
+        assert_eq!(ItemClass::from("team_CTF_redflag"), ItemClass::Flag);
+        assert_eq!(ItemClass::from("item_unknown"), ItemClass::Other);
+        assert_eq!(ItemClass::from(""), ItemClass::Other);
+    }
+
+    #[test]
+    fn test_session_time_played() {
+        let session = Session {
+            connected_at: 10,
+            began_at: Some(15),
+            disconnected_at: Some(75),
+        };
+        assert_eq!(session.time_played(), 60);
+
+        let never_began = Session {
+            began_at: None,
+            ..session
+        };
+        assert_eq!(never_began.time_played(), 0);
+
+        let still_open = Session {
+            disconnected_at: None,
+            ..session
+        };
+        assert_eq!(still_open.time_played(), 0);
+
+        // the log clock went back, e.g. a server restart
+        let clock_reset = Session {
+            disconnected_at: Some(5),
+            ..session
+        };
+        assert_eq!(clock_reset.time_played(), 0);
+    }
+
+    #[test]
+    fn test_player_time_played_and_kills_per_minute() {
+        let mut player = PlayerData {
+            name: "Isgalamido".to_owned(),
+            frags: 3,
+            ..Default::default()
+        };
+        assert_eq!(player.time_played(), 0);
+        assert_eq!(player.kills_per_minute(), None);
+
+        player.sessions = vec![
+            Session {
+                connected_at: 0,
+                began_at: Some(0),
+                disconnected_at: Some(60),
+            },
+            Session {
+                connected_at: 70,
+                began_at: Some(80),
+                disconnected_at: Some(200),
+            },
+        ];
+        assert_eq!(player.time_played(), 180);
+        assert_eq!(player.kills_per_minute(), Some(1.0));
+    }
+
+    #[test]
+    fn test_merge_player_data() {
+        let mut player = PlayerData {
+            name: "Isgalamido".to_owned(),
+            frags: 2,
+            items: HashMap::from([("weapon_railgun".to_owned(), 1)]),
+            kills_by_means: HashMap::from([(MeanDeath::Railgun, 2)]),
+            sessions: vec![Session::default()],
+            ..Default::default()
+        };
+        let reconnected = PlayerData {
+            name: "Isgalamido".to_owned(),
+            world_deaths: 1,
+            items: HashMap::from([
+                ("weapon_railgun".to_owned(), 2),
+                ("item_quad".to_owned(), 1),
+            ]),
+            kills_by_means: HashMap::from([(MeanDeath::Railgun, 1), (MeanDeath::Rocket, 1)]),
+            sessions: vec![Session {
+                connected_at: 60,
+                ..Default::default()
+            }],
+            ..Default::default()
+        };
+
+        player.merge(&reconnected, &ScoringRules::default());
+        assert_eq!(player.score(&ScoringRules::default()), 1);
+        assert_eq!(player.server_score, None);
+        assert_eq!(player.items.get("weapon_railgun"), Some(&3));
+        assert_eq!(player.items.get("item_quad"), Some(&1));
+        assert_eq!(player.kills_by_means.get(&MeanDeath::Railgun), Some(&3));
+        assert_eq!(player.kills_by_means.get(&MeanDeath::Rocket), Some(&1));
+        assert_eq!(player.sessions.len(), 2);
+        assert_eq!(player.sessions[1].connected_at, 60);
+
+        let with_server_score = PlayerData {
+            server_score: Some(4),
+            ..Default::default()
+        };
+        player.merge(&with_server_score, &ScoringRules::default());
+        assert_eq!(player.server_score, Some(4));
+        player.merge(&with_server_score, &ScoringRules::default());
+        assert_eq!(player.server_score, Some(8));
+    }
+
+    #[test]
+    fn test_merge_player_names_and_guid() {
+        let mut player = PlayerData {
+            name: "Isgalamido".to_owned(),
+            name_history: vec!["Izzy".to_owned(), "Isgalamido".to_owned()],
+            ..Default::default()
+        };
+        let renamed = PlayerData {
+            name: "Isga".to_owned(),
+            guid: Some("ABCD".to_owned()),
+            name_history: vec!["Isgalamido".to_owned(), "Isga".to_owned()],
+            ..Default::default()
+        };
+
+        player.merge(&renamed, &ScoringRules::default());
+        assert_eq!(player.name, "Isgalamido");
+        assert_eq!(player.name_history, vec!["Izzy", "Isgalamido", "Isga"]);
+        assert_eq!(player.aliases(), vec!["Izzy", "Isga"]);
+        assert_eq!(player.guid, Some("ABCD".to_owned()));
+    }
+
+    #[test]
+    fn test_is_same_person() {
+        let player = |name: &str, guid: Option<&str>, history: &[&str]| PlayerData {
+            name: name.to_owned(),
+            guid: guid.map(str::to_owned),
+            name_history: history.iter().map(|name| (*name).to_owned()).collect(),
+            ..Default::default()
+        };
+
+        // same name, no guid
+        assert!(player("Zeh", None, &[]).is_same_person(&player("Zeh", None, &[])));
+        // renamed, the old name is in the history
+        assert!(player("Zeh", None, &["Mal", "Zeh"]).is_same_person(&player("Mal", None, &[])));
+        assert!(!player("Zeh", None, &["Zeh"]).is_same_person(&player("Mal", None, &["Mal"])));
+        // the guid wins over the name
+        assert!(player("Zeh", Some("1"), &[]).is_same_person(&player("Mal", Some("1"), &[])));
+        assert!(!player("Zeh", Some("1"), &[]).is_same_person(&player("Zeh", Some("2"), &[])));
+        // only one of them has a guid, fall back to the name
+        assert!(player("Zeh", Some("1"), &[]).is_same_person(&player("Zeh", None, &[])));
+    }
+
+    #[test]
+    fn test_is_same_person_in_game() {
+        let player =
+            |name: &str, guid: Option<&str>, connected_at: u32, disconnected_at| PlayerData {
+                name: name.to_owned(),
+                guid: guid.map(str::to_owned),
+                name_history: vec!["UnnamedPlayer".to_owned(), name.to_owned()],
+                sessions: vec![Session {
+                    connected_at,
+                    began_at: Some(connected_at),
+                    disconnected_at,
+                }],
+                ..Default::default()
+            };
+
+        // connected at the same time, never the same person
+        assert!(
+            !player("Zeh", None, 0, None).is_same_person_in_game(&player("Zeh", None, 10, None))
+        );
+        assert!(
+            !player("Zeh", Some("1"), 0, Some(20)).is_same_person_in_game(&player(
+                "Zeh",
+                Some("1"),
+                10,
+                None
+            ))
+        );
+        // reconnected under the name it left with
+        let mut reconnected = player("Zeh", None, 10, None);
+        reconnected.name_history = vec!["Zeh".to_owned()];
+        assert!(player("Zeh", None, 0, Some(10)).is_same_person_in_game(&reconnected));
+        assert!(reconnected.is_same_person_in_game(&player("Zeh", None, 0, Some(10))));
+        // reconnected under another name
+        assert!(!player("Zeh", None, 0, Some(10))
+            .is_same_person_in_game(&player("Mal", None, 10, None)));
+        // the guid wins over the name
+        assert!(
+            player("Zeh", Some("1"), 0, Some(10)).is_same_person_in_game(&player(
+                "Mal",
+                Some("1"),
+                10,
+                None
+            ))
+        );
+        assert!(
+            !player("Zeh", Some("1"), 0, Some(10)).is_same_person_in_game(&player(
+                "Zeh",
+                Some("2"),
+                10,
+                None
+            ))
+        );
+    }
+
+    #[test]
+    fn test_game_settings_from_cvars() {
+        let settings = GameSettings::from(concat!(
+            r"\sv_floodProtect\1\sv_maxPing\0\sv_hostname\Code Miner Server\g_gametype\0",
+            r"\fraglimit\20\timelimit\15\capturelimit\8",
+            r"\version\ioq3 1.36 linux-x86_64 Apr 12 2009\mapname\q3dm17\g_needpass\0",
+        ));
+
+        assert_eq!(settings.map_name, Some("q3dm17".to_owned()));
+        assert_eq!(settings.game_type, Some(0));
+        assert_eq!(settings.frag_limit, Some(20));
+        assert_eq!(settings.time_limit, Some(15));
+        assert_eq!(settings.capture_limit, Some(8));
+        assert_eq!(settings.hostname, Some("Code Miner Server".to_owned()));
+        assert_eq!(
+            settings.version,
+            Some("ioq3 1.36 linux-x86_64 Apr 12 2009".to_owned())
+        );
+        assert_eq!(
+            settings.other,
+            BTreeMap::from([
+                ("g_needpass".to_owned(), "0".to_owned()),
+                ("sv_floodProtect".to_owned(), "1".to_owned()),
+                ("sv_maxPing".to_owned(), "0".to_owned()),
+            ])
+        );
+    }
+
+    #[test]
+    fn test_game_settings_cvars() {
+        let cvars = concat!(
+            r"\sv_hostname\Code Miner Server\g_gametype\0\fraglimit\20\timelimit\none",
+            r"\mapname\q3dm17\g_needpass\0",
+        );
+        let expected: BTreeMap<String, String> = [
+            ("sv_hostname", "Code Miner Server"),
+            ("g_gametype", "0"),
+            ("fraglimit", "20"),
+            ("timelimit", "none"),
+            ("mapname", "q3dm17"),
+            ("g_needpass", "0"),
+        ]
+        .into_iter()
+        .map(|(key, value)| (key.to_owned(), value.to_owned()))
+        .collect();
+        assert_eq!(GameSettings::from(cvars).cvars(), expected);
+        assert!(GameSettings::default().cvars().is_empty());
+    }
+
+    #[test]
+    fn test_game_settings_from_unexpected_cvars() {
+        assert_eq!(GameSettings::from(""), GameSettings::default());
+
+        // a typed cvar that is not a number is kept as is
+        let settings = GameSettings::from(r"\fraglimit\none\mapname\q3dm17\dangling");
+        assert_eq!(settings.frag_limit, None);
+        assert_eq!(settings.map_name, Some("q3dm17".to_owned()));
+        assert_eq!(
+            settings.other,
+            BTreeMap::from([("fraglimit".to_owned(), "none".to_owned())])
+        );
+    }
+
+    #[test]
+    fn test_team_from_userinfo_value() {
+        assert_eq!(Team::from("0"), Team::Free);
+        assert_eq!(Team::from("1"), Team::Red);
+        assert_eq!(Team::from("2"), Team::Blue);
+        assert_eq!(Team::from("3"), Team::Spectator);
+        assert_eq!(Team::from(""), Team::Free);
+        assert_eq!(Team::Red.to_string(), "Red");
+        assert_eq!(Team::Spectator.to_string(), "Spectator");
+    }
+
+    #[test]
+    fn test_game_type_from_setting() {
+        assert_eq!(GameType::from(0), GameType::FreeForAll);
+        assert_eq!(GameType::from(1), GameType::Tournament);
+        assert_eq!(GameType::from(2), GameType::FreeForAll);
+        assert_eq!(GameType::from(3), GameType::TeamDeathmatch);
+        assert_eq!(GameType::from(4), GameType::CaptureTheFlag);
+        assert_eq!(GameType::from(8), GameType::FreeForAll);
+
+        assert!(!GameType::FreeForAll.is_team_game());
+        assert!(!GameType::Tournament.is_team_game());
+        assert!(GameType::TeamDeathmatch.is_team_game());
+        assert!(GameType::CaptureTheFlag.is_team_game());
+
+        assert_eq!(GameType::CaptureTheFlag.to_string(), "Capture The Flag");
+    }
+
+    #[test]
+    fn test_exit_reason_from_log() {
+        assert_eq!(ExitReason::from("Fraglimit hit."), ExitReason::Fraglimit);
+        assert_eq!(ExitReason::from("Timelimit hit."), ExitReason::Timelimit);
+        assert_eq!(
+            ExitReason::from("Capturelimit hit."),
+            ExitReason::Capturelimit
+        );
+        assert_eq!(ExitReason::from(" Fraglimit hit "), ExitReason::Fraglimit);
+        assert_eq!(
+            ExitReason::from("Map restarted."),
+            ExitReason::Other("Map restarted".to_owned())
+        );
+        assert_eq!(ExitReason::default(), ExitReason::Aborted);
+    }
+
+    #[test]
+    fn test_exit_reason_display() {
+        assert_eq!(ExitReason::Fraglimit.to_string(), "Fraglimit hit");
+        assert_eq!(ExitReason::Timelimit.to_string(), "Timelimit hit");
+        assert_eq!(ExitReason::Capturelimit.to_string(), "Capturelimit hit");
+        assert_eq!(
+            ExitReason::Other("Map restarted".to_owned()).to_string(),
+            "Map restarted"
+        );
+        assert_eq!(ExitReason::Aborted.to_string(), "Aborted");
+    }
+
+    #[test]
+    fn test_items_by_class() {
+        let player = PlayerData {
+            name: "Isgalamido".to_owned(),
+            items: HashMap::from([
+                ("weapon_rocketlauncher".to_owned(), 3),
+                ("weapon_railgun".to_owned(), 1),
+                ("item_armor_body".to_owned(), 2),
+                ("item_health_mega".to_owned(), 1),
+            ]),
+            ..Default::default()
+        };
+
+        let items_by_class = player.items_by_class();
+        assert_eq!(items_by_class.len(), 3);
+        assert_eq!(items_by_class.get(&ItemClass::Weapon), Some(&4));
+        assert_eq!(items_by_class.get(&ItemClass::Armor), Some(&2));
+        assert_eq!(items_by_class.get(&ItemClass::Health), Some(&1));
+        assert_eq!(items_by_class.get(&ItemClass::Ammo), None);
+
+        assert!(PlayerData::default().items_by_class().is_empty());
+    }
+
     fn a_random_mean_death() -> impl Strategy<Value = MeanDeath> {
         prop_oneof![
             Just(MeanDeath::Unknown),
@@ -1,4 +1,4 @@
-use std::fmt::Display;
+use std::{fmt::Display, ops::Range};
 
 #[derive(Debug, Clone, PartialEq, Eq)]
 /// The errors that can occur while parsing the logs
@@ -6,6 +6,12 @@ pub enum ParsingError {
     /// When an unexpected error occurs
     /// (e.g. when a player that hasn't connected performs an action
     UnexpectedError(String),
+    /// When an event refers to a client that never connected
+    /// (e.g. a log that starts in the middle of a game)
+    UnknownClient(u32),
+    /// When a timestamp is malformed
+    /// (e.g. `20:75`, the seconds are past 59)
+    InvalidTimestamp(String),
     /// When an expected value from the log is not found
     /// (e.g. the `mean_id` in the Kill event)
     LogPartNotFound(String),
@@ -16,6 +22,107 @@ pub enum ParsingError {
     /// When an IO error occurs
     /// (e.g. when reading the file, if the filepath is invalid)
     IoError(std::io::ErrorKind),
+    /// When any of the errors above happens on a line of the log
+    /// holds the error along with where it happened
+    AtLine(Box<LineError>),
+}
+
+impl ParsingError {
+    /// Ties the error to a location of the log
+    /// an error that already has one keeps it
+    #[must_use]
+    pub fn at(self, location: Location) -> Self {
+        match self {
+            Self::AtLine(_) => self,
+            error => Self::AtLine(Box::new(LineError { location, error })),
+        }
+    }
+
+    /// Returns the error without its location
+    pub fn inner(&self) -> &Self {
+        match self {
+            Self::AtLine(line_error) => line_error.error.inner(),
+            error => error,
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+/// Where a parsing error happened in the log
+pub struct Location {
+    /// The line number, starting at 1
+    pub line_number: usize,
+    /// The byte offset of the start of the line in the log
+    pub line_offset: usize,
+    /// The byte span of the failing token, relative to the start of the line
+    pub span: Range<usize>,
+    /// The text of the line, without the line break
+    pub line: String,
+}
+
+impl Location {
+    /// Returns the byte span of the failing token in the log
+    pub const fn byte_span(&self) -> Range<usize> {
+        self.line_offset.saturating_add(self.span.start)
+            ..self.line_offset.saturating_add(self.span.end)
+    }
+
+    /// Returns the column of the failing token, in characters, starting at 1
+    pub fn column(&self) -> usize {
+        self.line
+            .get(..self.span.start)
+            .map_or(0, |before| before.chars().count())
+            .saturating_add(1)
+    }
+}
+
+impl Display for Location {
+    /// Renders the line with a caret under the failing token
+    /// ```text
+    ///  --> line 4, column 20 (bytes 120..121)
+    ///   |
+    /// 4 |   0:03 Kill: 1022 2
+    ///   |                    ^
+    /// ```
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let line_number = self.line_number.to_string();
+        let gutter = " ".repeat(line_number.len());
+        let byte_span = self.byte_span();
+        // tabs are kept so the caret lines up with the token
+        let padding = self
+            .line
+            .get(..self.span.start)
+            .unwrap_or_default()
+            .chars()
+            .map(|c| if c == '\t' { '\t' } else { ' ' })
+            .collect::<String>();
+        let carets = "^".repeat(
+            self.line
+                .get(self.span.clone())
+                .map_or(0, |token| token.chars().count())
+                .max(1),
+        );
+
+        writeln!(
+            f,
+            "{gutter}--> line {line_number}, column {} (bytes {}..{})",
+            self.column(),
+            byte_span.start,
+            byte_span.end
+        )?;
+        writeln!(f, "{gutter} |")?;
+        writeln!(f, "{line_number} | {}", self.line)?;
+        write!(f, "{gutter} | {padding}{carets}")
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+/// A parsing error along with where it happened in the log
+pub struct LineError {
+    /// Where the error happened
+    pub location: Location,
+    /// The error itself, never an `AtLine` error
+    pub error: ParsingError,
 }
 
 impl From<std::num::ParseIntError> for ParsingError {
@@ -34,14 +141,35 @@ impl Display for ParsingError {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         match self {
             Self::UnexpectedError(s) => write!(f, "Unexpected error: {s}"),
+            Self::UnknownClient(client_id) => write!(f, "Unknown client: {client_id}"),
+            Self::InvalidTimestamp(s) => write!(f, "Invalid timestamp: {s}"),
             Self::LogPartNotFound(s) => write!(f, "Not found: {s}"),
             Self::ParseIntError(err) => write!(f, "ParseIntError: {err}"),
             Self::IoError(err) => write!(f, "IoError: {err}"),
+            Self::AtLine(line_error) => write!(f, "{}\n{}", line_error.error, line_error.location),
+        }
+    }
+}
+
+impl std::error::Error for ParsingError {
+    /// The located error displays the error it wraps,
+    /// so its source is the one of that error, not the error itself
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            Self::ParseIntError(err) => Some(err),
+            Self::AtLine(line_error) => line_error.error.source(),
+            Self::UnexpectedError(_)
+            | Self::UnknownClient(_)
+            | Self::InvalidTimestamp(_)
+            | Self::LogPartNotFound(_)
+            | Self::IoError(_) => None,
         }
     }
 }
 
 #[cfg(test)]
+#[allow(clippy::panic, clippy::shadow_unrelated, clippy::unwrap_used)]
+// the tests panic on the unexpected errors
 mod tests {
     use super::*;
     use proptest::prelude::*;
@@ -58,6 +186,12 @@ mod tests {
         }
     }
 
+    prop_compose! {
+        fn an_unknown_client_error()(client_id in any::<u32>()) -> ParsingError {
+            ParsingError::UnknownClient(client_id)
+        }
+    }
+
     // fn a_random_parsing_error() -> BoxedStrategy<ParsingError> {
     //     prop_oneof![
     //         a_not_found_error(),
@@ -104,14 +238,31 @@ mod tests {
     proptest! {
         #[test]
         fn test_display_logpart_not_found_error(parsing_error in a_logpart_not_found_error()) {
-            let s = match parsing_error.clone() {
-                ParsingError::LogPartNotFound(s) => s,
-                _ => panic!("Expected NotFound"),
+            let ParsingError::LogPartNotFound(s) = parsing_error.clone() else {
+                panic!("Expected NotFound");
+            };
+            assert_eq!(format!("{parsing_error}"), format!("Not found: {s}"));
+        }
+    }
+
+    proptest! {
+        #[test]
+        fn test_display_unknown_client_error(parsing_error in an_unknown_client_error()) {
+            let ParsingError::UnknownClient(client_id) = parsing_error.clone() else {
+                panic!("Expected UnknownClient");
             };
-            assert_eq!(format!("{}", parsing_error), format!("Not found: {s}"));
+            assert_eq!(format!("{parsing_error}"), format!("Unknown client: {client_id}"));
         }
     }
 
+    #[test]
+    fn test_display_invalid_timestamp_error() {
+        assert_eq!(
+            format!("{}", ParsingError::InvalidTimestamp("20:75".to_owned())),
+            "Invalid timestamp: 20:75"
+        );
+    }
+
     proptest! {
         #[test]
         fn test_display_parseint_error(parsing_error in a_parseint_parsing_error()) {
@@ -119,7 +270,7 @@ mod tests {
                 ParsingError::ParseIntError(err) => err,
                 _ => panic!("Expected ParseIntError"),
             };
-            assert_eq!(format!("{}", parsing_error), format!("ParseIntError: {}", err));
+            assert_eq!(format!("{parsing_error}"), format!("ParseIntError: {}", err));
         }
     }
 
@@ -130,18 +281,105 @@ mod tests {
                 ParsingError::IoError(err) => err,
                 _ => panic!("Expected IoError"),
             };
-            assert_eq!(format!("{}", parsing_error), format!("IoError: {}", err));
+            assert_eq!(format!("{parsing_error}"), format!("IoError: {}", err));
         }
     }
 
     proptest! {
         #[test]
         fn test_display_unexpected_error(parsing_error in a_unexpected_error()) {
-            let s = match parsing_error.clone() {
-                ParsingError::UnexpectedError(s) => s,
-                _ => panic!("Expected UnexpectedError"),
+            let ParsingError::UnexpectedError(s) = parsing_error.clone() else {
+                panic!("Expected UnexpectedError");
             };
-            assert_eq!(format!("{}", parsing_error), format!("Unexpected error: {s}"));
+            assert_eq!(format!("{parsing_error}"), format!("Unexpected error: {s}"));
         }
     }
+
+    fn a_located_error() -> ParsingError {
+        ParsingError::LogPartNotFound("victim_id".to_owned()).at(Location {
+            line_number: 12,
+            line_offset: 300,
+            span: 14..14,
+            line: "\t20:40 Kill: 2".to_owned(),
+        })
+    }
+
+    #[test]
+    fn test_display_located_error() {
+        assert_eq!(
+            format!("{}", a_located_error()),
+            concat!(
+                "Not found: victim_id\n",
+                "  --> line 12, column 15 (bytes 314..314)\n",
+                "   |\n",
+                "12 | \t20:40 Kill: 2\n",
+                "   | \t             ^",
+            )
+        );
+
+        let err = "x1".parse::<u32>().unwrap_err();
+        let located = ParsingError::from(err.clone()).at(Location {
+            line_number: 3,
+            line_offset: 0,
+            span: 13..15,
+            line: " 0:01 Kill: 2 x1 7: ...".to_owned(),
+        });
+        assert_eq!(
+            format!("{located}"),
+            format!(
+                "ParseIntError: {err}\n --> line 3, column 14 (bytes 13..15)\n  |\n3 |  0:01 Kill: 2 x1 7: ...\n  |              ^^"
+            )
+        );
+    }
+
+    #[test]
+    fn test_located_error_keeps_first_location() {
+        let located = a_located_error().at(Location {
+            line_number: 1,
+            line_offset: 0,
+            span: 0..0,
+            line: String::new(),
+        });
+        assert_eq!(located, a_located_error());
+        assert_eq!(
+            located.inner(),
+            &ParsingError::LogPartNotFound("victim_id".to_owned())
+        );
+    }
+
+    #[test]
+    fn test_error_source_chain() {
+        use std::error::Error;
+
+        let err = "x1".parse::<u32>().unwrap_err();
+        let located = ParsingError::from(err.clone()).at(Location {
+            line_number: 3,
+            line_offset: 0,
+            span: 13..15,
+            line: " 0:01 Kill: 2 x1 7: ...".to_owned(),
+        });
+
+        // the located error already displays the error it wraps, it isn't repeated in the chain
+        let root = located.source().unwrap();
+        assert_eq!(root.to_string(), err.to_string());
+        assert!(root.source().is_none());
+        let mut messages = vec![located.to_string()];
+        let mut source = located.source();
+        while let Some(err) = source {
+            messages.push(err.to_string());
+            source = err.source();
+        }
+        assert_eq!(
+            messages
+                .iter()
+                .filter(|message| message.contains("ParseIntError"))
+                .count(),
+            1
+        );
+
+        assert!(a_located_error().source().is_none());
+        assert!(ParsingError::IoError(std::io::ErrorKind::NotFound)
+            .source()
+            .is_none());
+    }
 }
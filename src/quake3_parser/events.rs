@@ -0,0 +1,1272 @@
+use super::{
+    errors::{Location, ParsingError},
+    timestamps::{is_clock, parse_clock, parse_epoch, parse_iso8601, Timestamp, TimestampFormat},
+};
+use crate::quake3_data::MeanDeath;
+use serde::{Deserialize, Serialize};
+use std::{io::BufRead, ops::Range};
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+/// The events that can be found in a Quake 3 log line
+/// each event holds the typed data of the line, without any game context
+///
+/// the events about a client that must already be connected also hold the byte span
+/// of its id in the line, so the errors found while applying them point at it
+pub enum Event {
+    /// A new game started
+    /// holds the raw backslash separated server settings
+    InitGame {
+        /// The raw server settings (e.g. `\sv_hostname\Code Miner Server\g_gametype\0`)
+        settings: String,
+    },
+    /// The current game ended
+    ShutdownGame,
+    /// A client connected to the server
+    ClientConnect {
+        /// The client slot id
+        client_id: u32,
+    },
+    /// A client changed its user info (name, team, model...)
+    ClientUserinfoChanged {
+        /// The client slot id
+        client_id: u32,
+        /// The byte span of the client id in the line
+        client_span: Range<usize>,
+        /// The player name, extracted from the `n\` field
+        name: String,
+        /// The raw backslash separated user info
+        userinfo: String,
+    },
+    /// A client entered the game
+    ClientBegin {
+        /// The client slot id
+        client_id: u32,
+        /// The byte span of the client id in the line
+        client_span: Range<usize>,
+    },
+    /// A client left the server
+    ClientDisconnect {
+        /// The client slot id
+        client_id: u32,
+        /// The byte span of the client id in the line
+        client_span: Range<usize>,
+    },
+    /// A player (or the world) killed a player
+    Kill {
+        /// The killer client id, `WORLD_ID` when killed by the world
+        killer_id: u32,
+        /// The victim client id
+        victim_id: u32,
+        /// The byte span of the killer id in the line
+        killer_span: Range<usize>,
+        /// The byte span of the victim id in the line
+        victim_span: Range<usize>,
+        /// The mean of death
+        mean_death: MeanDeath,
+    },
+    /// A player picked up an item
+    Item {
+        /// The client slot id
+        client_id: u32,
+        /// The byte span of the client id in the line
+        client_span: Range<usize>,
+        /// The item class name (e.g. `weapon_rocketlauncher`)
+        item: String,
+    },
+    /// The game reached an end condition
+    Exit {
+        /// The exit reason as printed by the server (e.g. `Fraglimit hit.`)
+        reason: String,
+    },
+    /// The final score of a player, printed after `Exit`
+    Score {
+        /// The player score as computed by the server
+        score: i32,
+        /// The player ping
+        ping: u32,
+        /// The client slot id
+        client_id: u32,
+        /// The byte span of the client id in the line
+        client_span: Range<usize>,
+        /// The player name
+        name: String,
+    },
+    /// The final score of the teams, printed after `Exit` in team games
+    TeamScore {
+        /// The red team score
+        red: i32,
+        /// The blue team score
+        blue: i32,
+    },
+    /// A chat message
+    Say {
+        /// The name of the player who sent the message
+        name: String,
+        /// The message
+        message: String,
+    },
+}
+
+impl Event {
+    /// Returns the byte span of the id of the client in the line of the event,
+    /// `None` when the event doesn't refer to the client
+    ///
+    /// a kill refers to the victim first, the killer of a suicide is the victim
+    pub fn client_span(&self, client_id: u32) -> Option<Range<usize>> {
+        match self {
+            Self::Kill {
+                victim_id,
+                victim_span,
+                ..
+            } if *victim_id == client_id => Some(victim_span.clone()),
+            Self::Kill {
+                killer_id,
+                killer_span,
+                ..
+            } if *killer_id == client_id => Some(killer_span.clone()),
+            Self::ClientUserinfoChanged {
+                client_id: id,
+                client_span,
+                ..
+            }
+            | Self::ClientBegin {
+                client_id: id,
+                client_span,
+            }
+            | Self::ClientDisconnect {
+                client_id: id,
+                client_span,
+            }
+            | Self::Item {
+                client_id: id,
+                client_span,
+                ..
+            }
+            | Self::Score {
+                client_id: id,
+                client_span,
+                ..
+            } if *id == client_id => Some(client_span.clone()),
+            _ => None,
+        }
+    }
+}
+
+/// Splits a line on whitespace, like `str::split_whitespace`,
+/// keeping the byte span of the last token it returned
+/// so an error can point at the token it failed on
+#[derive(Debug, Clone)]
+struct Tokens<'line> {
+    /// The line being split
+    line: &'line str,
+    /// The byte offset where the search for the next token starts
+    position: usize,
+    /// The byte span of the last token returned,
+    /// an empty span at the end of the line once there are no tokens left
+    span: Range<usize>,
+}
+
+impl<'line> Tokens<'line> {
+    /// Starts splitting the line from its beginning
+    const fn new(line: &'line str) -> Self {
+        Self {
+            line,
+            position: 0,
+            span: 0..0,
+        }
+    }
+}
+
+impl<'line> Iterator for Tokens<'line> {
+    type Item = &'line str;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let rest = self.line.get(self.position..).unwrap_or_default();
+        let Some(token) = rest.split_whitespace().next() else {
+            let end = self.line.trim_end().len();
+            self.position = self.line.len();
+            self.span = end..end;
+            return None;
+        };
+
+        let start = self
+            .position
+            .saturating_add(rest.len().saturating_sub(rest.trim_start().len()));
+        let end = start.saturating_add(token.len());
+        self.position = end;
+        self.span = start..end;
+        Some(token)
+    }
+}
+
+/// parses the next part of the line as a u32 id
+/// `part_name` is used to name the missing part in the error
+fn parse_id<'part, I>(parts: &mut I, part_name: &str) -> Result<u32, ParsingError>
+where
+    I: Iterator<Item = &'part str>,
+{
+    Ok(parts
+        .next()
+        .ok_or_else(|| ParsingError::LogPartNotFound(part_name.to_owned()))?
+        .parse::<u32>()?)
+}
+
+/// parses the next part of the line as a client id, see `parse_id`
+/// returns the id along with the byte span of its token
+fn parse_client_id(
+    parts: &mut Tokens,
+    part_name: &str,
+) -> Result<(u32, Range<usize>), ParsingError> {
+    let client_id = parse_id(parts, part_name)?;
+    Ok((client_id, parts.span.clone()))
+}
+
+/// consumes the next part of the line, which must be the `label` text
+fn expect_label<'part, I>(parts: &mut I, label: &str) -> Result<(), ParsingError>
+where
+    I: Iterator<Item = &'part str>,
+{
+    if parts.next() == Some(label) {
+        Ok(())
+    } else {
+        Err(ParsingError::LogPartNotFound(label.to_owned()))
+    }
+}
+
+/// parses the `ClientUserinfoChanged` event
+/// the player name is the `n\` field, the first field of the user info
+fn parse_user_info(parts: &mut Tokens) -> Result<Event, ParsingError> {
+    let (client_id, client_span) = parse_client_id(parts, "client_id")?;
+    let userinfo = parts.collect::<Vec<&str>>().join(" ");
+    let name = userinfo
+        .chars()
+        .skip(2)
+        .take_while(|&c| c != '\\')
+        .collect::<String>();
+
+    Ok(Event::ClientUserinfoChanged {
+        client_id,
+        client_span,
+        name,
+        userinfo,
+    })
+}
+
+/// parses the Kill event
+///
+/// can error if the parsing of the u32 fails (`std::num::ParseIntError`)
+///
+fn parse_kill(parts: &mut Tokens) -> Result<Event, ParsingError> {
+    let (killer_id, killer_span) = parse_client_id(parts, "killer_id")?;
+    let (victim_id, victim_span) = parse_client_id(parts, "victim_id")?;
+
+    // the mean id is followed by a colon, e.g. `7:`
+    let mean_id = parts
+        .next()
+        .and_then(|mean_id_text| mean_id_text.strip_suffix(':'))
+        .filter(|mean_id_text| !mean_id_text.is_empty())
+        .ok_or_else(|| ParsingError::LogPartNotFound("mean_id".to_owned()))?
+        .parse::<u32>()?;
+
+    Ok(Event::Kill {
+        killer_id,
+        victim_id,
+        killer_span,
+        victim_span,
+        mean_death: MeanDeath::from(mean_id),
+    })
+}
+
+/// parses the Item event
+fn parse_item(parts: &mut Tokens) -> Result<Event, ParsingError> {
+    let (client_id, client_span) = parse_client_id(parts, "client_id")?;
+    let item = parts
+        .next()
+        .ok_or_else(|| ParsingError::LogPartNotFound("item".to_owned()))?
+        .to_owned();
+
+    Ok(Event::Item {
+        client_id,
+        client_span,
+        item,
+    })
+}
+
+/// parses the score event (e.g. `score: 20  ping: 4  client: 4 Zeh`)
+fn parse_score(parts: &mut Tokens) -> Result<Event, ParsingError> {
+    let score = parts
+        .next()
+        .ok_or_else(|| ParsingError::LogPartNotFound("score".to_owned()))?
+        .parse::<i32>()?;
+    expect_label(parts, "ping:")?;
+    let ping = parse_id(parts, "ping")?;
+    expect_label(parts, "client:")?;
+    let (client_id, client_span) = parse_client_id(parts, "client_id")?;
+    let name = parts.collect::<Vec<&str>>().join(" ");
+
+    Ok(Event::Score {
+        score,
+        ping,
+        client_id,
+        client_span,
+        name,
+    })
+}
+
+/// parses the team score event (e.g. `red:8  blue:6`)
+/// the red score is glued to its label, so it comes as the event part
+fn parse_team_score<'part, I>(red: &str, parts: &mut I) -> Result<Event, ParsingError>
+where
+    I: Iterator<Item = &'part str>,
+{
+    let red = red
+        .strip_prefix("red:")
+        .ok_or_else(|| ParsingError::LogPartNotFound("red:".to_owned()))?
+        .parse::<i32>()?;
+    let blue = parts
+        .next()
+        .and_then(|blue| blue.strip_prefix("blue:"))
+        .ok_or_else(|| ParsingError::LogPartNotFound("blue:".to_owned()))?
+        .parse::<i32>()?;
+
+    Ok(Event::TeamScore { red, blue })
+}
+
+/// parses the say event (e.g. `say: Oootsimo: team red`)
+fn parse_say<'part, I>(parts: &mut I) -> Result<Event, ParsingError>
+where
+    I: Iterator<Item = &'part str>,
+{
+    let text = parts.collect::<Vec<&str>>().join(" ");
+    let (name, message) = text
+        .split_once(':')
+        .ok_or_else(|| ParsingError::LogPartNotFound("say message".to_owned()))?;
+
+    Ok(Event::Say {
+        name: name.to_owned(),
+        message: message.trim_start().to_owned(),
+    })
+}
+
+/// parses the timestamp prefix of a log line, following the `format`
+/// returns `None` for lines that don't start with a timestamp of that format
+///
+/// the `Auto` format is replaced by the format of the first line with a timestamp
+/// the date and epoch prefixes can be wrapped in brackets
+/// and be followed by the `MM:SS` time of the classic logs
+fn parse_prefix(
+    parts: &mut Tokens,
+    format: &mut TimestampFormat,
+) -> Result<Option<Timestamp>, ParsingError> {
+    let Some(first) = parts.clone().next() else {
+        return Ok(None);
+    };
+    let detected = match *format {
+        TimestampFormat::Auto => TimestampFormat::detect(first),
+        format => format.matches(first).then_some(format),
+    };
+    let Some(detected) = detected else {
+        // skip lines that don't start with a timestamp
+        return Ok(None);
+    };
+    *format = detected;
+    parts.next();
+
+    let start = parts.span.start;
+    let utc = match detected {
+        TimestampFormat::Relative => {
+            return Ok(Some(Timestamp {
+                clock: Some(parse_clock(first)?),
+                utc: None,
+            }));
+        }
+        TimestampFormat::Epoch => parse_epoch(first.trim_start_matches('[').trim_end_matches(']'))?,
+        TimestampFormat::Iso8601 | TimestampFormat::Auto => {
+            let date = first.trim_start_matches('[');
+            let time = if date.len() > 10 {
+                date.to_owned()
+            } else {
+                let time = parts
+                    .next()
+                    .ok_or_else(|| ParsingError::LogPartNotFound("time".to_owned()))?;
+                parts.span = start..parts.span.end;
+                format!("{date} {time}")
+            };
+            parse_iso8601(time.trim_end_matches(']'))?
+        }
+    };
+
+    let mut lookahead = parts.clone();
+    let clock = match lookahead.next() {
+        Some(clock) if is_clock(clock) && clock.contains(':') => {
+            *parts = lookahead;
+            Some(parse_clock(clock)?)
+        }
+        _ => None,
+    };
+
+    Ok(Some(Timestamp {
+        clock,
+        utc: Some(utc),
+    }))
+}
+
+#[allow(dead_code)]
+// the `EventStream` parses the tokens directly to locate the errors
+// but this entry point is kept to parse a single line
+/// parses a single log line into its timestamp and event
+/// returns `None` for lines that don't hold an event
+/// (empty lines, separators, lines without a timestamp or unknown events)
+pub fn parse_event(line: &str) -> Result<Option<(Timestamp, Event)>, ParsingError> {
+    parse_tokens(&mut Tokens::new(line), &mut TimestampFormat::Auto)
+}
+
+/// parses the tokens of a log line into its timestamp and event, see `parse_event`
+/// the timestamp follows the `format`, see `parse_prefix`
+/// on error, the span of the `parts` is the one of the failing token
+fn parse_tokens(
+    parts: &mut Tokens,
+    format: &mut TimestampFormat,
+) -> Result<Option<(Timestamp, Event)>, ParsingError> {
+    let Some(time) = parse_prefix(parts, format)? else {
+        return Ok(None);
+    };
+    let event = parts
+        .next()
+        .ok_or_else(|| ParsingError::LogPartNotFound("event".to_owned()))?;
+
+    let event = match event {
+        "InitGame:" => Event::InitGame {
+            settings: parts.collect::<Vec<&str>>().join(" "),
+        },
+        "ShutdownGame:" => Event::ShutdownGame,
+        "ClientConnect:" => Event::ClientConnect {
+            client_id: parse_id(parts, "client_id")?,
+        },
+        "ClientUserinfoChanged:" => parse_user_info(parts)?,
+        "ClientBegin:" => {
+            let (client_id, client_span) = parse_client_id(parts, "client_id")?;
+            Event::ClientBegin {
+                client_id,
+                client_span,
+            }
+        }
+        "ClientDisconnect:" => {
+            let (client_id, client_span) = parse_client_id(parts, "client_id")?;
+            Event::ClientDisconnect {
+                client_id,
+                client_span,
+            }
+        }
+        "Kill:" => parse_kill(parts)?,
+        "Item:" => parse_item(parts)?,
+        "Exit:" => Event::Exit {
+            reason: parts.collect::<Vec<&str>>().join(" "),
+        },
+        "score:" => parse_score(parts)?,
+        "say:" => parse_say(parts)?,
+        red if red.starts_with("red:") => parse_team_score(red, parts)?,
+        _ => return Ok(None),
+    };
+
+    Ok(Some((time, event)))
+}
+
+/// Streaming tokenizer over any `BufRead`
+/// yields the timestamp and event of each line that holds one
+/// the timestamps follow the `TimestampFormat` of the log
+///
+/// The errors of a line are located in the log, see `Location`
+/// An error doesn't end the stream, the caller decides whether to stop
+#[derive(Debug)]
+pub struct EventStream<R> {
+    /// The log being read
+    reader: R,
+    /// Reused buffer for the raw bytes of the current line
+    buffer: Vec<u8>,
+    /// Reused buffer for the text of the current line, without the line break
+    line: String,
+    /// The number of the current line, starting at 1
+    line_number: usize,
+    /// The byte offset of the current line in the log
+    line_offset: usize,
+    /// The byte offset of the next line in the log
+    next_offset: usize,
+    /// The timestamp format of the log, `Auto` until a line with a timestamp is read
+    format: TimestampFormat,
+    /// Whether a last line without line break is left unread, as it may still be written
+    complete_lines_only: bool,
+    /// Whether the buffer holds the start of a line still being written
+    pending: bool,
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+/// A position in the log, between two lines
+pub struct LogPosition {
+    /// The number of lines before the position
+    pub line_number: usize,
+    /// The byte offset of the position in the log
+    pub offset: usize,
+}
+
+impl<R> EventStream<R> {
+    /// Returns the position after the last line read
+    pub const fn log_position(&self) -> LogPosition {
+        LogPosition {
+            line_number: self.line_number,
+            offset: self.next_offset,
+        }
+    }
+
+    /// Returns the timestamp format of the log, once detected when read as `Auto`
+    pub const fn format(&self) -> TimestampFormat {
+        self.format
+    }
+
+    /// Returns the location of the `span` of the current line
+    pub fn location(&self, span: Range<usize>) -> Location {
+        Location {
+            line_number: self.line_number,
+            line_offset: self.line_offset,
+            span,
+            line: self.line.clone(),
+        }
+    }
+
+    /// Returns the span of the whole current line, without the surrounding whitespace
+    pub fn line_span(&self) -> Range<usize> {
+        let start = self.line.len().saturating_sub(self.line.trim_start().len());
+        start..self.line.trim_end().len().max(start)
+    }
+}
+
+impl<R: BufRead> Iterator for EventStream<R> {
+    type Item = Result<(Timestamp, Event), ParsingError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            if !self.pending {
+                self.buffer.clear();
+            }
+            match self.reader.read_until(b'\n', &mut self.buffer) {
+                Ok(0) => return None,
+                Ok(_) => {}
+                Err(err) => return Some(Err(err.into())),
+            }
+            // the rest of the line is read along the next lines, once written
+            self.pending = self.complete_lines_only && !self.buffer.ends_with(b"\n");
+            if self.pending {
+                return None;
+            }
+            let read = self.buffer.len();
+            self.line_number = self.line_number.saturating_add(1);
+            self.line_offset = self.next_offset;
+            self.next_offset = self.next_offset.saturating_add(read);
+
+            let text = self.buffer.strip_suffix(b"\n").unwrap_or(&self.buffer);
+            let text = text.strip_suffix(b"\r").unwrap_or(text);
+            self.line.clear();
+            match std::str::from_utf8(text) {
+                Ok(line) => self.line.push_str(line),
+                Err(utf8_error) => {
+                    // the span of the invalid bytes, shown on the lossy text of the line
+                    let start = utf8_error.valid_up_to();
+                    let span = start..start.saturating_add(utf8_error.error_len().unwrap_or(1));
+                    self.line.push_str(&String::from_utf8_lossy(text));
+                    let err = ParsingError::IoError(std::io::ErrorKind::InvalidData);
+                    return Some(Err(err.at(self.location(span))));
+                }
+            }
+
+            let mut tokens = Tokens::new(&self.line);
+            match parse_tokens(&mut tokens, &mut self.format) {
+                Ok(Some(event)) => return Some(Ok(event)),
+                Ok(None) => {}
+                Err(err) => return Some(Err(err.at(self.location(tokens.span)))),
+            }
+        }
+    }
+}
+
+/// reads the log from a reader, one line at a time
+/// returns an iterator over the timestamped events
+/// the timestamps follow the `format`, detected from the log when `Auto`
+pub const fn read_events<R: BufRead>(reader: R, format: TimestampFormat) -> EventStream<R> {
+    EventStream {
+        reader,
+        buffer: Vec::new(),
+        line: String::new(),
+        line_number: 0,
+        line_offset: 0,
+        next_offset: 0,
+        format,
+        complete_lines_only: false,
+        pending: false,
+    }
+}
+
+/// reads the log from a reader already at the `position`, one complete line at a time
+/// returns an iterator over the timestamped events of the lines after the position
+///
+/// unlike `read_events`, a last line without line break is left unread as it may still be written,
+/// it's read once complete when the stream is iterated again
+pub const fn resume_events<R: BufRead>(
+    reader: R,
+    format: TimestampFormat,
+    position: LogPosition,
+) -> EventStream<R> {
+    EventStream {
+        reader,
+        buffer: Vec::new(),
+        line: String::new(),
+        line_number: position.line_number,
+        line_offset: position.offset,
+        next_offset: position.offset,
+        format,
+        complete_lines_only: true,
+        pending: false,
+    }
+}
+
+#[cfg(test)]
+#[allow(
+    clippy::arithmetic_side_effects,
+    clippy::default_numeric_fallback,
+    clippy::panic,
+    clippy::shadow_unrelated,
+    clippy::unseparated_literal_suffix,
+    clippy::unwrap_used
+)]
+// the tests compute the expected token spans and panic on the unexpected events
+mod tests {
+    use super::*;
+    use proptest::prelude::*;
+
+    /// The timestamp of a classic log line, `seconds` after the server started
+    const fn relative(seconds: u32) -> Timestamp {
+        Timestamp {
+            clock: Some(seconds),
+            utc: None,
+        }
+    }
+
+    proptest! {
+        #[test]
+        fn test_parse_user_info(
+            client_id in any::<u32>(),
+            two_chars in "[\\S]{2}",
+            name in "\\w*",
+            rest in "\\PC*",
+        ) {
+            let user_info_line = format!("{client_id} {two_chars}{name}\\{rest}");
+            let mut parts = Tokens::new(&user_info_line);
+
+            let result = parse_user_info(&mut parts);
+            match result {
+                Ok(Event::ClientUserinfoChanged { client_id: parsed_id, name: parsed_name, .. }) => {
+                    prop_assert_eq!(parsed_id, client_id);
+                    prop_assert_eq!(parsed_name, name);
+                }
+                _ => prop_assert!(false),
+            }
+        }
+    }
+
+    proptest! {
+        #[test]
+        fn test_parse_kill(
+            killer_id in any::<u32>(),
+            victim_id in any::<u32>(),
+            mean_id in 0..28_u32,
+            rest in "\\PC*",
+        ) {
+            let kill_line = format!("{killer_id} {victim_id} {mean_id}: {rest}");
+            let mut parts = Tokens::new(&kill_line);
+
+            let result = parse_kill(&mut parts);
+            let killer_end = killer_id.to_string().len();
+            let victim_start = killer_end + 1;
+            let victim_span = victim_start..victim_start + victim_id.to_string().len();
+            prop_assert_eq!(
+                result,
+                Ok(Event::Kill {
+                    killer_id,
+                    victim_id,
+                    killer_span: 0..killer_end,
+                    victim_span,
+                    mean_death: MeanDeath::from(mean_id),
+                })
+            );
+        }
+    }
+
+    proptest! {
+        #[test]
+        fn test_parse_client_id_part_not_found(
+            client_connect_line in "\\s*",
+        ) {
+            let mut parts = client_connect_line.split_whitespace();
+
+            let result = parse_id(&mut parts, "client_id");
+            match result {
+                Err(ParsingError::LogPartNotFound(_)) => {},
+                _ => prop_assert!(false),
+            }
+        }
+    }
+
+    proptest! {
+        #[test]
+        fn test_parse_client_id_parseint_error(
+            client_connect_line in "[^\\d\\s]+", // match everything that is not a digit or a whitespace
+        ) {
+            let mut parts = client_connect_line.split_whitespace();
+
+            let result = parse_id(&mut parts, "client_id");
+            match result {
+                Err(ParsingError::ParseIntError(_)) => {},
+                _ => {
+                    prop_assert!(false);
+                },
+            }
+        }
+    }
+
+    proptest! {
+        #[test]
+        fn test_parse_user_info_part_not_found(
+            user_info_line in "\\s*",
+        ) {
+            let mut parts = Tokens::new(&user_info_line);
+
+            let result = parse_user_info(&mut parts);
+            match result {
+                Err(ParsingError::LogPartNotFound(_)) => {},
+                _ => prop_assert!(false),
+            }
+        }
+    }
+
+    proptest! {
+        #[test]
+        fn test_parse_user_info_parseint_error(
+            client_id in "[^\\d\\s]+", // match everything that is not a digit or a whitespace
+            two_chars in "\\PC*",
+            name in "\\PC*",
+            rest in "\\PC*",
+        ) {
+            let user_info_line = format!("{client_id} {two_chars}{name}\\{rest}");
+            let mut parts = Tokens::new(&user_info_line);
+
+            let result = parse_user_info(&mut parts);
+            match result {
+                Err(ParsingError::ParseIntError(_)) => {},
+                _ => {
+                    prop_assert!(false);
+                },
+            }
+        }
+    }
+
+    proptest! {
+        #[test]
+        fn test_parse_kill_mean_id_not_found(
+            killer_id in any::<u32>(),
+            victim_id in any::<u32>(),
+            mean_id in "\\s*",
+            rest in "\\PC*",
+        ) {
+            let kill_line = format!("{killer_id} {victim_id} {mean_id}: {rest}");
+            let mut parts = Tokens::new(&kill_line);
+
+            let result = parse_kill(&mut parts);
+            match result {
+                Err(ParsingError::LogPartNotFound(_)) => {},
+                _ => prop_assert!(false),
+            }
+        }
+    }
+
+    proptest! {
+        #[test]
+        fn test_parse_kill_victim_id_not_found(
+            killer_id in any::<u32>(),
+            victim_id in "\\s*",
+            mean_id in 0..28u32,
+            rest in "\\PC*",
+        ) {
+            let kill_line = format!("{killer_id} {victim_id} {mean_id}: {rest}");
+            let mut parts = Tokens::new(&kill_line);
+
+            let result = parse_kill(&mut parts);
+            match result {
+                Err(ParsingError::ParseIntError(_)) => {},
+                _ => prop_assert!(false),
+            }
+        }
+    }
+
+    proptest! {
+        #[test]
+        fn test_parse_kill_killer_id_not_found(
+            killer_id in "\\s*",
+            victim_id in any::<u32>(),
+            mean_id in 0..28u32,
+            rest in "\\PC*",
+        ) {
+            let kill_line = format!("{killer_id} {victim_id} {mean_id}: {rest}");
+            let mut parts = Tokens::new(&kill_line);
+
+            let result = parse_kill(&mut parts);
+            match result {
+                Err(ParsingError::ParseIntError(_)) => {},
+                _ => prop_assert!(false),
+            }
+        }
+    }
+
+    proptest! {
+        #[test]
+        fn test_parse_kill_mean_id_parseint_error(
+            killer_id in any::<u32>(),
+            victim_id in any::<u32>(),
+            mean_id in "[^\\d\\s]+", // match everything that is not a digit or a whitespace
+            rest in "\\PC*",
+        ) {
+            let kill_line = format!("{killer_id} {victim_id} {mean_id}: {rest}");
+            let mut parts = Tokens::new(&kill_line);
+
+            let result = parse_kill(&mut parts);
+            match result {
+                Err(ParsingError::ParseIntError(_)) => {},
+                _ => prop_assert!(false),
+            }
+        }
+    }
+
+    proptest! {
+        #[test]
+        fn test_parse_kill_victim_id_parseint_error(
+            killer_id in any::<u32>(),
+            victim_id in "[^\\d\\s]+", // match everything that is not a digit or a whitespace
+            mean_id in 0..28u32,
+            rest in "\\PC*",
+        ) {
+            let kill_line = format!("{killer_id} {victim_id} {mean_id}: {rest}");
+            let mut parts = Tokens::new(&kill_line);
+
+            let result = parse_kill(&mut parts);
+            match result {
+                Err(ParsingError::ParseIntError(_)) => {},
+                _ => prop_assert!(false),
+            }
+        }
+    }
+
+    proptest! {
+        #[test]
+        fn test_parse_kill_killer_id_parseint_error(
+            killer_id in "[^\\d\\s]+", // match everything that is not a digit or a whitespace
+            victim_id in any::<u32>(),
+            mean_id in 0..28u32,
+            rest in "\\PC*",
+        ) {
+            let kill_line = format!("{killer_id} {victim_id} {mean_id}: {rest}");
+            let mut parts = Tokens::new(&kill_line);
+
+            let result = parse_kill(&mut parts);
+            match result {
+                Err(ParsingError::ParseIntError(_)) => {},
+                _ => prop_assert!(false),
+            }
+        }
+    }
+
+    #[test]
+    fn test_parse_event() {
+        let cases = [
+            (
+                r"  0:00 InitGame: \sv_hostname\Code Miner Server\g_gametype\0",
+                Event::InitGame {
+                    settings: r"\sv_hostname\Code Miner Server\g_gametype\0".to_owned(),
+                },
+            ),
+            (" 20:37 ShutdownGame:", Event::ShutdownGame),
+            (
+                " 20:34 ClientConnect: 2",
+                Event::ClientConnect { client_id: 2 },
+            ),
+            (
+                r" 20:34 ClientUserinfoChanged: 2 n\Dono da Bola\t\0\model\sarge",
+                Event::ClientUserinfoChanged {
+                    client_id: 2,
+                    client_span: 30..31,
+                    name: "Dono da Bola".to_owned(),
+                    userinfo: r"n\Dono da Bola\t\0\model\sarge".to_owned(),
+                },
+            ),
+            (
+                " 20:37 ClientBegin: 2",
+                Event::ClientBegin {
+                    client_id: 2,
+                    client_span: 20..21,
+                },
+            ),
+            (
+                " 21:10 ClientDisconnect: 2",
+                Event::ClientDisconnect {
+                    client_id: 2,
+                    client_span: 25..26,
+                },
+            ),
+            (
+                " 20:54 Kill: 1022 2 22: <world> killed Isgalamido by MOD_TRIGGER_HURT",
+                Event::Kill {
+                    killer_id: 1022,
+                    victim_id: 2,
+                    killer_span: 13..17,
+                    victim_span: 18..19,
+                    mean_death: MeanDeath::TriggerHurt,
+                },
+            ),
+            (
+                " 20:40 Item: 2 weapon_rocketlauncher",
+                Event::Item {
+                    client_id: 2,
+                    client_span: 13..14,
+                    item: "weapon_rocketlauncher".to_owned(),
+                },
+            ),
+            (
+                " 11:57 Exit: Fraglimit hit.",
+                Event::Exit {
+                    reason: "Fraglimit hit.".to_owned(),
+                },
+            ),
+            (
+                " 11:57 score: 11  ping: 0  client: 5 Assasinu Credi",
+                Event::Score {
+                    score: 11,
+                    ping: 0,
+                    client_id: 5,
+                    client_span: 35..36,
+                    name: "Assasinu Credi".to_owned(),
+                },
+            ),
+            (" 10:12 red:8  blue:6", Event::TeamScore { red: 8, blue: 6 }),
+            (
+                "981:21 say: Oootsimo: team red",
+                Event::Say {
+                    name: "Oootsimo".to_owned(),
+                    message: "team red".to_owned(),
+                },
+            ),
+        ];
+
+        for (line, event) in cases {
+            let (time, parsed) = parse_event(line).unwrap().unwrap();
+            let clock = parse_clock(line.split_whitespace().next().unwrap()).unwrap();
+            assert_eq!(time, relative(clock));
+            assert_eq!(parsed, event, "line: {line}");
+        }
+    }
+
+    #[test]
+    fn test_parse_event_skips_lines_without_event() {
+        assert_eq!(parse_event(""), Ok(None));
+        assert_eq!(parse_event("   "), Ok(None));
+        assert_eq!(
+            parse_event("  0:00 ------------------------------------------------------------"),
+            Ok(None)
+        );
+        assert_eq!(
+            parse_event(" 26  0:00 ------------------------------------------------------------"),
+            Ok(None)
+        );
+    }
+
+    #[test]
+    fn test_parse_team_score_errors() {
+        match parse_event(" 10:12 red:8") {
+            Err(ParsingError::LogPartNotFound(part)) => assert_eq!(part, "blue:"),
+            _ => panic!("Expected LogPartNotFound"),
+        }
+        match parse_event(" 10:12 red:8  6") {
+            Err(ParsingError::LogPartNotFound(part)) => assert_eq!(part, "blue:"),
+            _ => panic!("Expected LogPartNotFound"),
+        }
+        match parse_event(" 10:12 red:a  blue:6") {
+            Err(ParsingError::ParseIntError(_)) => {}
+            _ => panic!("Expected ParseIntError"),
+        }
+    }
+
+    #[test]
+    fn test_parse_score_errors() {
+        match parse_event(" 11:57 score: 20  ping: 4  4 Zeh") {
+            Err(ParsingError::LogPartNotFound(part)) => assert_eq!(part, "client:"),
+            _ => panic!("Expected LogPartNotFound"),
+        }
+        match parse_event(" 11:57 score: a  ping: 4  client: 4 Zeh") {
+            Err(ParsingError::ParseIntError(_)) => {}
+            _ => panic!("Expected ParseIntError"),
+        }
+    }
+
+    proptest! {
+        #[test]
+        fn test_parse_event_not_found(
+            time in "[0-9]{1,3}:[0-5][0-9]",
+            spaces in "\\s*",
+        ) {
+            let result = parse_event(&format!("{time}{spaces}"));
+            match result {
+                Err(ParsingError::LogPartNotFound(_)) => {},
+                _ => prop_assert!(false),
+            }
+        }
+    }
+
+    #[test]
+    fn test_tokens_span() {
+        let mut tokens = Tokens::new(" 20:40  Kill: 2\tx 7:  ");
+        assert_eq!(tokens.next(), Some("20:40"));
+        assert_eq!(tokens.span, 1..6);
+        assert_eq!(tokens.next(), Some("Kill:"));
+        assert_eq!(tokens.span, 8..13);
+        assert_eq!(tokens.nth(1), Some("x"));
+        assert_eq!(tokens.span, 16..17);
+        assert_eq!(tokens.next(), Some("7:"));
+        assert_eq!(tokens.next(), None);
+        assert_eq!(tokens.span, 20..20);
+        assert_eq!(tokens.next(), None);
+    }
+
+    #[test]
+    fn test_read_events_locates_errors() {
+        let log_content = concat!(
+            "  0:00 InitGame: \\sv_hostname\\Code Miner Server\r\n",
+            " 20:37 Kill: 2 x 7: Isgalamido killed Isgalamido by MOD_ROCKET_SPLASH\n",
+        );
+
+        let events: Vec<Result<(Timestamp, Event), ParsingError>> =
+            read_events(log_content.as_bytes(), TimestampFormat::Auto).collect();
+        match &events[1] {
+            Err(ParsingError::AtLine(line_error)) => {
+                assert!(matches!(line_error.error, ParsingError::ParseIntError(_)));
+                assert_eq!(line_error.location.line_number, 2);
+                assert_eq!(line_error.location.span, 15..16);
+                assert_eq!(line_error.location.byte_span(), 64..65);
+                assert_eq!(
+                    line_error.location.line,
+                    " 20:37 Kill: 2 x 7: Isgalamido killed Isgalamido by MOD_ROCKET_SPLASH"
+                );
+            }
+            other => panic!("Expected a located ParseIntError, got {other:?}"),
+        }
+    }
+
+    #[test]
+    fn test_read_events_locates_multibyte_mean_id_errors() {
+        let log_content = concat!(
+            " 20:37 Kill: 1022 2 7é Isgalamido killed Isgalamido by MOD_ROCKET_SPLASH\n",
+            " 20:38 Kill: 1022 2 é: Isgalamido killed Isgalamido by MOD_ROCKET_SPLASH\n",
+        );
+
+        let events: Vec<Result<(Timestamp, Event), ParsingError>> =
+            read_events(log_content.as_bytes(), TimestampFormat::Auto).collect();
+        match &events[0] {
+            Err(ParsingError::AtLine(line_error)) => {
+                assert_eq!(
+                    line_error.error,
+                    ParsingError::LogPartNotFound("mean_id".to_owned())
+                );
+                assert_eq!(line_error.location.line_number, 1);
+                assert_eq!(line_error.location.span, 20..23);
+            }
+            other => panic!("Expected a located LogPartNotFound, got {other:?}"),
+        }
+        match &events[1] {
+            Err(ParsingError::AtLine(line_error)) => {
+                assert!(matches!(line_error.error, ParsingError::ParseIntError(_)));
+                assert_eq!(line_error.location.line_number, 2);
+                assert_eq!(line_error.location.span, 20..23);
+            }
+            other => panic!("Expected a located ParseIntError, got {other:?}"),
+        }
+    }
+
+    #[test]
+    fn test_read_events() {
+        let log_content = concat!(
+            "  0:00 ------------------------------------------------------------\n",
+            "  0:00 InitGame: \\sv_hostname\\Code Miner Server\n",
+            " 20:34 ClientConnect: 2\n",
+            " 20:37 Kill: 2 2 7: Isgalamido killed Isgalamido by MOD_ROCKET_SPLASH\n",
+            " 20:40 Kill: 2\n",
+            " 20:41 ShutdownGame:\n",
+        );
+
+        let events: Vec<Result<(Timestamp, Event), ParsingError>> =
+            read_events(log_content.as_bytes(), TimestampFormat::Auto).collect();
+        assert_eq!(events.len(), 5);
+        assert_eq!(
+            events[1],
+            Ok((relative(1234), Event::ClientConnect { client_id: 2 }))
+        );
+        assert!(matches!(
+            events[2],
+            Ok((
+                _,
+                Event::Kill {
+                    killer_id: 2,
+                    victim_id: 2,
+                    ..
+                }
+            ))
+        ));
+        // the stream goes on after an error
+        match &events[3] {
+            Err(ParsingError::AtLine(line_error)) => {
+                assert_eq!(
+                    line_error.error,
+                    ParsingError::LogPartNotFound("victim_id".to_owned())
+                );
+                assert_eq!(line_error.location.line_number, 5);
+                assert_eq!(line_error.location.span, 14..14);
+                assert_eq!(line_error.location.line, " 20:40 Kill: 2");
+            }
+            other => panic!("Expected a located LogPartNotFound, got {other:?}"),
+        }
+        assert_eq!(events[4], Ok((relative(1241), Event::ShutdownGame)));
+    }
+
+    #[test]
+    fn test_parse_event_prefixes() {
+        let cases = [
+            ("2024-05-01T20:37:12Z Kill: 1022 2 22:", None),
+            ("[2024-05-01 20:37:12] Kill: 1022 2 22:", None),
+            ("2024-05-01 20:37:12  20:54 Kill: 1022 2 22:", Some(1254)),
+            ("1714595832 Kill: 1022 2 22:", None),
+            ("[1714595832.250]  20:54 Kill: 1022 2 22:", Some(1254)),
+        ];
+        for (line, clock) in cases {
+            let expected = Timestamp {
+                clock,
+                utc: Some(1_714_595_832),
+            };
+            let (time, event) = parse_event(line).unwrap().unwrap();
+            assert_eq!(time, expected, "line: {line}");
+            // the ids are located after the prefix, whatever its length
+            let kill_at = line.find("Kill:").unwrap();
+            assert_eq!(
+                event,
+                Event::Kill {
+                    killer_id: 1022,
+                    victim_id: 2,
+                    killer_span: kill_at + 6..kill_at + 10,
+                    victim_span: kill_at + 11..kill_at + 12,
+                    mean_death: MeanDeath::TriggerHurt,
+                },
+                "line: {line}"
+            );
+        }
+    }
+
+    #[test]
+    fn test_parse_event_prefix_errors() {
+        let mut tokens = Tokens::new("[2024-02-30 20:37:12] Kill: 1022 2 22:");
+        let result = parse_tokens(&mut tokens, &mut TimestampFormat::Auto);
+        assert_eq!(
+            result,
+            Err(ParsingError::InvalidTimestamp(
+                "2024-02-30 20:37:12".to_owned()
+            ))
+        );
+        assert_eq!(tokens.span, 0..21);
+
+        let mut tokens = Tokens::new("2024-05-01");
+        let result = parse_tokens(&mut tokens, &mut TimestampFormat::Iso8601);
+        assert_eq!(
+            result,
+            Err(ParsingError::LogPartNotFound("time".to_owned()))
+        );
+
+        let mut tokens = Tokens::new("1714595832  20:75 Kill: 1022 2 22:");
+        let result = parse_tokens(&mut tokens, &mut TimestampFormat::Epoch);
+        assert_eq!(
+            result,
+            Err(ParsingError::InvalidTimestamp("20:75".to_owned()))
+        );
+        assert_eq!(tokens.span, 12..17);
+    }
+
+    #[test]
+    fn test_read_events_keeps_detected_format() {
+        let log_content = concat!(
+            "Server log started\n",
+            "1714595832 InitGame: \\mapname\\q3dm17\n",
+            " 20:37 Kill: 1022 2 22: <world> killed Isgalamido by MOD_TRIGGER_HURT\n",
+            "1714595900 ShutdownGame:\n",
+        );
+
+        let events: Vec<Result<(Timestamp, Event), ParsingError>> =
+            read_events(log_content.as_bytes(), TimestampFormat::Auto).collect();
+        // the classic line is skipped, the log has epoch timestamps
+        assert_eq!(events.len(), 2);
+        assert!(matches!(events[1], Ok((_, Event::ShutdownGame))));
+
+        // the epoch seconds are broken `MM:SS` times for a classic log
+        let events: Vec<Result<(Timestamp, Event), ParsingError>> =
+            read_events(log_content.as_bytes(), TimestampFormat::Relative).collect();
+        assert_eq!(events.len(), 3);
+        assert!(matches!(events[1], Ok((_, Event::Kill { .. }))));
+        match &events[2] {
+            Err(ParsingError::AtLine(line_error)) => {
+                assert_eq!(
+                    line_error.error,
+                    ParsingError::LogPartNotFound("timestamp".to_owned())
+                );
+                assert_eq!(line_error.location.span, 0..10);
+            }
+            other => panic!("Expected a located LogPartNotFound, got {other:?}"),
+        }
+    }
+
+    #[test]
+    fn test_resume_events_waits_for_line_end() {
+        let log_content = concat!(
+            "  0:00 InitGame: \\mapname\\q3dm17\n",
+            "  0:10 Kill: 1022 2 22: <world> killed Isgalamido by MOD_TRIGGER_HURT\n",
+            "  1:00 ShutdownGame:",
+        );
+        let first_line = log_content.find('\n').unwrap().saturating_add(1);
+        let position = LogPosition {
+            line_number: 1,
+            offset: first_line,
+        };
+
+        let mut events = resume_events(
+            log_content.as_bytes().get(first_line..).unwrap(),
+            TimestampFormat::Auto,
+            position,
+        );
+        assert!(matches!(events.next(), Some(Ok((_, Event::Kill { .. })))));
+        assert_eq!(events.next(), None);
+        // the unfinished line is left for the next run
+        let end_of_kill = log_content.rfind('\n').unwrap().saturating_add(1);
+        assert_eq!(
+            events.log_position(),
+            LogPosition {
+                line_number: 2,
+                offset: end_of_kill,
+            }
+        );
+        assert_eq!(events.format(), TimestampFormat::Relative);
+
+        // while the whole log is read up to its end
+        let events: Vec<Result<(Timestamp, Event), ParsingError>> =
+            read_events(log_content.as_bytes(), TimestampFormat::Auto).collect();
+        assert!(matches!(events.last(), Some(Ok((_, Event::ShutdownGame)))));
+    }
+}
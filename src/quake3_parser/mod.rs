@@ -1,4 +1,11 @@
 /// Module responsible for custom errors
 pub mod errors;
+/// Module responsible for the log tokenizer
+/// turning each line into a typed event
+pub mod events;
 /// Module responsible for the parser implementation
+/// aggregating the events into games
 pub mod parser;
+/// Module responsible for the timestamp prefixes of the log lines
+/// the `MM:SS` time, the ISO-8601 dates and the epoch seconds
+pub mod timestamps;
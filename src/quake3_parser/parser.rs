@@ -1,9 +1,86 @@
-use super::errors::ParsingError;
-use crate::quake3_data::{MeanDeath, PlayerData, WORLD_ID};
-use std::collections::HashMap;
+use super::{
+    errors::ParsingError,
+    events::{read_events, resume_events, Event, EventStream, LogPosition},
+    timestamps::{Timestamp, TimestampFormat},
+};
+use crate::quake3_data::{
+    ExitReason, GameSettings, GameType, IncompleteReason, MeanDeath, PlayerData, Session, Team,
+    TeamChange, TeamStats, WORLD_ID,
+};
+use crate::scoring::ScoringRules;
+use serde::{Deserialize, Serialize};
+use std::{
+    collections::{BTreeMap, HashMap},
+    fmt::Display,
+    io::BufRead,
+};
+
+/// A player whose computed score differs from the score printed by the server
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ScoreMismatch {
+    /// The client id of the player
+    pub client_id: u32,
+    /// The player name
+    pub name: String,
+    /// The score computed from the kill counters in the log
+    pub computed: i32,
+    /// The score printed by the server
+    pub server: i32,
+}
+
+impl Display for ScoreMismatch {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "{} (client {}) has a computed score of {} but the server reported {}",
+            self.name, self.client_id, self.computed, self.server
+        )
+    }
+}
+
+/// How a game ended for its players, see `Game::outcome`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Outcome {
+    /// The player, or team in team games, that won the game
+    Winner(String),
+    /// A limit was hit with the best players, or teams, tied
+    Draw,
+    /// No limit was hit, e.g. the game was aborted
+    NoWinner,
+}
+
+impl Display for Outcome {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Winner(winner) => write!(f, "{winner}"),
+            Self::Draw => write!(f, "Draw"),
+            Self::NoWinner => write!(f, "No winner"),
+        }
+    }
+}
+
+/// How the parser handles the lines it can't make sense of
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum ParseMode {
+    /// The first bad line stops the parsing with its error
+    Strict,
+    /// Bad lines are skipped and kept as diagnostics, the parsing goes on
+    #[default]
+    Lenient,
+}
+
+/// The games parsed from a log
+/// together with the diagnostics of the lines skipped in lenient mode
+#[derive(Debug, Default)]
+pub struct ParsedLog {
+    /// The games, in log order
+    pub games: Vec<Game>,
+    /// The errors of the skipped lines, in log order
+    pub diagnostics: Vec<ParsingError>,
+}
 
 /// Represents a game with the total kills and the players data
-#[derive(Debug)]
+#[derive(Debug, Default, Serialize, Deserialize)]
 pub struct Game {
     /// Even though this info could be derived by summing
     /// all the kills in the `means_death` hashmap
@@ -15,335 +92,1381 @@ pub struct Game {
     pub kills_by_means_death: HashMap<MeanDeath, u32>,
     /// The players data in the game
     /// is represented by a hashmap with the player id as key and the player data as value
-    /// the player data contains the player name and the number of kills
-    /// the number of kills is decremented when the player is killed by the world
+    /// the player data contains the player name and its kill counters
+    ///
+    /// only the players still connected when the game ended are here
     pub players_data: HashMap<u32, PlayerData>,
+    #[serde(default)]
+    /// The players that disconnected before the game ended
+    /// their client id may have been reused by another player afterwards
+    pub departed_players: Vec<PlayerData>,
+    #[serde(default)]
+    /// Why the game ended, `Aborted` if the log has no `Exit:` line for it
+    pub exit_reason: ExitReason,
+    #[serde(default)]
+    /// The server settings the game was played with, from the `InitGame:` line
+    pub settings: GameSettings,
+    #[serde(default)]
+    /// The kill statistics of the red and blue teams, only filled in team games
+    pub team_stats: BTreeMap<Team, TeamStats>,
+    #[serde(default)]
+    /// The team scores printed by the server at the end of the game (`red:8  blue:6`)
+    /// `None` if the server didn't print them
+    pub server_team_scores: Option<BTreeMap<Team, i32>>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    /// Why the game never reached its `ShutdownGame:` line
+    /// `None` for the games that did
+    pub incomplete: Option<IncompleteReason>,
+    #[serde(default)]
+    /// When the game started, in seconds on the log clock
+    /// the `InitGame:` line, or the first event of a log cut before it
+    pub started_at: u32,
+    #[serde(default)]
+    /// When the game ended, in seconds on the log clock
+    /// the `ShutdownGame:` line, or the last event of an incomplete game
+    pub ended_at: u32,
+    #[serde(default)]
+    /// When the first kill of the game happened, in seconds on the log clock
+    pub first_kill_at: Option<u32>,
+    #[serde(default)]
+    /// Whether the server clock was reset since the previous game
+    /// i.e. the game started earlier on the clock than the previous game ended
+    pub clock_reset: bool,
+    #[serde(default)]
+    /// The kills of each player on each other player,
+    /// by killer name then victim name, the names they had at the time of the kill
+    ///
+    /// suicides are kills of the player on itself, the world kills are left out
+    pub kill_matrix: BTreeMap<String, BTreeMap<String, u32>>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    /// When the game started, in unix seconds
+    /// only for the logs with dates or epoch seconds on their lines
+    pub started_at_utc: Option<i64>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    /// When the game ended, in unix seconds
+    /// only for the logs with dates or epoch seconds on their lines
+    pub ended_at_utc: Option<i64>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    /// The log the game was read from, e.g. `games.log.1` or `<stdin>`
+    /// `None` until the game is tagged with it, see `LogSource`
+    pub source: Option<String>,
+    #[serde(default)]
+    /// The scoring rules the scores of the players are computed with, see `Game::apply_scoring`
+    pub scoring: ScoringRules,
 }
 
-/// Adds game data (`total_kills` and `players_data`) to the `games` vector
-/// and clears the `total_kills` and `players_data` vectors
-/// to start a new game
+impl Game {
+    /// Returns all the players of the game, connected or departed,
+    /// with the players that are the same person merged into one,
+    /// e.g. a player that reconnected during the game, under the same name or GUID
+    ///
+    /// the merged player goes by the name it used last,
+    /// the other names are kept in its name history, see `PlayerData::is_same_person_in_game`
+    pub fn players_by_identity(&self) -> Vec<PlayerData> {
+        let mut players: Vec<&PlayerData> = self
+            .players_data
+            .values()
+            .chain(&self.departed_players)
+            .collect();
+        players.sort_by_key(|player| {
+            (
+                player.sessions.first().map(|session| session.connected_at),
+                player.name.as_str(),
+            )
+        });
+
+        let mut people: Vec<PlayerData> = Vec::new();
+        for player in players {
+            // the latest known person the player reconnected as, if any
+            let same = people
+                .iter_mut()
+                .rev()
+                .find(|known| known.is_same_person_in_game(player));
+            if let Some(known) = same {
+                known.merge(player, &self.scoring);
+                known.name.clone_from(&player.name);
+            } else {
+                people.push(player.clone());
+            }
+        }
+        people.sort_by(|a, b| a.name.cmp(&b.name));
+        people
+    }
+
+    /// Returns the kills of each player on each other player, by identity
+    /// `matrix[killer][victim]` is the kills of `people[killer]` on `people[victim]`,
+    /// the `people` being the players of `Game::players_by_identity`, in any order
+    ///
+    /// the kills under a name nobody in `people` used are left out,
+    /// a name several people used goes to the one still using it, if any
+    pub fn kill_matrix_by_identity(&self, people: &[PlayerData]) -> Vec<Vec<u32>> {
+        let index_of = |name: &str| {
+            people
+                .iter()
+                .position(|person| person.name == name)
+                .or_else(|| {
+                    people
+                        .iter()
+                        .position(|person| person.name_history.iter().any(|used| used == name))
+                })
+        };
+        let mut matrix = vec![vec![0_u32; people.len()]; people.len()];
+        for (killer, victims) in &self.kill_matrix {
+            let Some(killer) = index_of(killer) else {
+                continue;
+            };
+            for (victim, kills) in victims {
+                let cell =
+                    index_of(victim).and_then(|victim| matrix.get_mut(killer)?.get_mut(victim));
+                if let Some(cell) = cell {
+                    *cell = cell.saturating_add(*kills);
+                }
+            }
+        }
+        matrix
+    }
+
+    /// Returns the players whose computed score differs
+    /// from the score printed by the server at the end of the game,
+    /// sorted by client id
+    ///
+    /// players without a server score are not compared,
+    /// nor the players of objective games, whose server scores also count the objectives
+    /// see `GameType::is_objective_game`
+    pub fn score_mismatches(&self) -> Vec<ScoreMismatch> {
+        if self.game_type().is_objective_game() {
+            return Vec::new();
+        }
+        let mut mismatches: Vec<ScoreMismatch> = self
+            .players_data
+            .iter()
+            .filter_map(|(client_id, player)| {
+                let server = player.server_score?;
+                let computed = player.score(&self.scoring);
+                (server != computed).then(|| ScoreMismatch {
+                    client_id: *client_id,
+                    name: player.name.clone(),
+                    computed,
+                    server,
+                })
+            })
+            .collect();
+        mismatches.sort_unstable_by_key(|mismatch| mismatch.client_id);
+        mismatches
+    }
+
+    /// Replaces the computed scores with the scores printed by the server
+    /// for the players that have one, so the ranking matches what players saw in game
+    pub fn use_server_scores(&mut self) {
+        for player in self
+            .players_data
+            .values_mut()
+            .chain(self.departed_players.iter_mut())
+        {
+            if player.server_score.is_some() {
+                player.score_override = player.server_score;
+            }
+        }
+    }
+
+    /// Sets the scoring rules the scores of the players are computed with
+    /// the scores printed by the server, once used, are kept
+    pub fn apply_scoring(&mut self, scoring: ScoringRules) {
+        self.scoring = scoring;
+    }
+
+    /// Returns the seconds from the start to the end of the game
+    pub const fn duration(&self) -> u32 {
+        self.ended_at.saturating_sub(self.started_at)
+    }
+
+    /// Returns the seconds from the start of the game to its first kill
+    /// `None` if nobody was killed
+    pub fn time_to_first_kill(&self) -> Option<u32> {
+        self.first_kill_at
+            .map(|first_kill_at| first_kill_at.saturating_sub(self.started_at))
+    }
+
+    /// Returns the game type, from the `g_gametype` setting
+    /// free for all when the log doesn't have it
+    pub fn game_type(&self) -> GameType {
+        self.settings
+            .game_type
+            .map(GameType::from)
+            .unwrap_or_default()
+    }
+
+    /// Returns the score of the red and blue teams,
+    /// the sum of the scores of their players
+    pub fn team_scores(&self) -> BTreeMap<Team, i32> {
+        let mut team_scores = BTreeMap::from([(Team::Red, 0_i32), (Team::Blue, 0_i32)]);
+        for player in self.players_data.values().chain(&self.departed_players) {
+            if let Some(score) = team_scores.get_mut(&player.team) {
+                *score = score.saturating_add(player.score(&self.scoring));
+            }
+        }
+        team_scores
+    }
+
+    /// Returns the final score of the red and blue teams,
+    /// the one printed by the server if any, the `team_scores` otherwise
+    pub fn final_team_scores(&self) -> BTreeMap<Team, i32> {
+        self.server_team_scores
+            .clone()
+            .unwrap_or_else(|| self.team_scores())
+    }
+
+    /// Returns the name of the player, or team in team games, that won the game
+    /// see `Game::outcome`
+    pub fn winner(&self) -> Option<String> {
+        match self.outcome() {
+            Outcome::Winner(winner) => Some(winner),
+            Outcome::Draw | Outcome::NoWinner => None,
+        }
+    }
+
+    /// Returns how the game ended
+    ///
+    /// The winner is the one with the highest score
+    /// when the frag limit or the time limit was hit,
+    /// or the team with the highest final score when the capture limit was hit.
+    /// There is no winner when the game was aborted, and a draw on a tie
+    pub fn outcome(&self) -> Outcome {
+        let team_game = self.game_type().is_team_game();
+        let limit_hit = match self.exit_reason {
+            ExitReason::Fraglimit | ExitReason::Timelimit => true,
+            ExitReason::Capturelimit => team_game,
+            ExitReason::Other(_) | ExitReason::Aborted => false,
+        };
+        if !limit_hit {
+            return Outcome::NoWinner;
+        }
+
+        if team_game {
+            let team_scores = self.final_team_scores();
+            return match (team_scores.get(&Team::Red), team_scores.get(&Team::Blue)) {
+                (Some(red), Some(blue)) if red > blue => {
+                    Outcome::Winner(format!("{} team", Team::Red))
+                }
+                (Some(red), Some(blue)) if blue > red => {
+                    Outcome::Winner(format!("{} team", Team::Blue))
+                }
+                _ => Outcome::Draw,
+            };
+        }
+
+        let mut players = self.players_by_identity();
+        players.sort_unstable_by(|a, b| self.scoring.compare(a, b));
+        match players.as_slice() {
+            [first, second, ..] if first.score(&self.scoring) == second.score(&self.scoring) => {
+                Outcome::Draw
+            }
+            [first, ..] => Outcome::Winner(first.name.clone()),
+            [] => Outcome::Draw,
+        }
+    }
+}
+
+/// The state of the game currently being parsed
+/// it is filled event by event and moved into a `Game` at every game boundary
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+struct GameState {
+    /// The running total of kills in the game
+    total_kills: u32,
+    /// The running kills by means of death in the game
+    kills_by_means_death: HashMap<MeanDeath, u32>,
+    /// The running players data in the game, by client id
+    players_data: HashMap<u32, PlayerData>,
+    /// The players that already disconnected from the game
+    departed_players: Vec<PlayerData>,
+    /// Why the game ended, once the `Exit:` line is read
+    exit_reason: ExitReason,
+    /// The server settings of the game
+    settings: GameSettings,
+    /// The running kill statistics of the teams
+    team_stats: BTreeMap<Team, TeamStats>,
+    /// The team scores printed by the server, once read
+    server_team_scores: Option<BTreeMap<Team, i32>>,
+    /// The running kills by killer and victim name
+    kill_matrix: BTreeMap<String, BTreeMap<String, u32>>,
+    /// Whether an event of the game was read, the game is dropped otherwise
+    started: bool,
+    /// The time of the first event of the game
+    started_at: Option<u32>,
+    /// The time of the last event of the game
+    last_event_at: u32,
+    /// The time of the first kill of the game
+    first_kill_at: Option<u32>,
+    /// The unix time of the first event of the game, for the logs with dates
+    started_at_utc: Option<i64>,
+    /// The unix time of the last event of the game, for the logs with dates
+    last_event_at_utc: Option<i64>,
+}
+
+impl GameState {
+    /// Returns the game type, from the `g_gametype` setting, see `Game::game_type`
+    fn game_type(&self) -> GameType {
+        self.settings
+            .game_type
+            .map(GameType::from)
+            .unwrap_or_default()
+    }
+
+    /// Records that the game had an event at `time`, and at `utc` for the logs with dates
+    /// the first event is the start of the game
+    fn record_event(&mut self, time: u32, utc: Option<i64>) {
+        self.started = true;
+        self.started_at.get_or_insert(time);
+        self.last_event_at = time;
+        self.started_at_utc = self.started_at_utc.or(utc);
+        self.last_event_at_utc = utc.or(self.last_event_at_utc);
+    }
+
+    /// Records a kill, at `time`, in the counters of the players and the game,
+    /// the team stats and the kill matrix
+    ///
+    /// can error if a player is not found or if a counter overflows,
+    /// the players are looked up before any counter changes, see `handle_kill`
+    fn record_kill(
+        &mut self,
+        time: u32,
+        killer_id: u32,
+        victim_id: u32,
+        mean_death: &MeanDeath,
+    ) -> Result<(), ParsingError> {
+        let game_type = self.game_type();
+        handle_kill(
+            killer_id,
+            victim_id,
+            mean_death.clone(),
+            game_type,
+            &mut self.total_kills,
+            &mut self.kills_by_means_death,
+            &mut self.players_data,
+        )?;
+        handle_team_stats(
+            killer_id,
+            victim_id,
+            game_type,
+            &self.players_data,
+            &mut self.team_stats,
+        )?;
+        handle_kill_matrix(
+            killer_id,
+            victim_id,
+            &self.players_data,
+            &mut self.kill_matrix,
+        );
+        self.first_kill_at.get_or_insert(time);
+        Ok(())
+    }
+}
+
+/// Moves the game data (`total_kills`, `kills_by_means_death` and players)
+/// out of the state into a finished `Game`
+/// leaving the state empty to start a new game
+///
+/// the sessions still open are closed at `time`, the end of the game
+/// and the game is marked as `incomplete` when it didn't reach its shutdown
 fn finish_game_and_set_new_game(
-    games: &mut Vec<Game>,
-    total_kills: &mut u32,
-    kills_by_means_death: &mut HashMap<MeanDeath, u32>,
-    players_data: &mut HashMap<u32, PlayerData>,
-) {
-    games.push(Game {
-        total_kills: *total_kills,
-        kills_by_means_death: kills_by_means_death.clone(),
-        players_data: players_data.clone(),
-    });
-    *total_kills = 0;
-    kills_by_means_death.clear();
-    players_data.clear();
+    state: &mut GameState,
+    time: u32,
+    incomplete: Option<IncompleteReason>,
+) -> Game {
+    let GameState {
+        total_kills,
+        kills_by_means_death,
+        mut players_data,
+        departed_players,
+        exit_reason,
+        settings,
+        team_stats,
+        server_team_scores,
+        kill_matrix,
+        started_at,
+        first_kill_at,
+        started_at_utc,
+        last_event_at_utc,
+        ..
+    } = std::mem::take(state);
+
+    for session in players_data
+        .values_mut()
+        .filter_map(|player| player.sessions.last_mut())
+    {
+        session.disconnected_at.get_or_insert(time);
+    }
+
+    Game {
+        total_kills,
+        kills_by_means_death,
+        players_data,
+        departed_players,
+        exit_reason,
+        settings,
+        team_stats,
+        server_team_scores,
+        incomplete,
+        started_at: started_at.unwrap_or(time),
+        ended_at: time,
+        first_kill_at,
+        clock_reset: false,
+        kill_matrix,
+        started_at_utc,
+        ended_at_utc: last_event_at_utc,
+        source: None,
+        scoring: ScoringRules::default(),
+    }
 }
 
-/// parses the `ClientConnect` event and initializes the `players_data`
-fn parse_client_connect<'part, I>(
-    parts: &mut I,
-    players_data: &mut HashMap<u32, PlayerData>,
-) -> Result<(), ParsingError>
-where
-    I: Iterator<Item = &'part str>,
-{
-    let client_id = parts
-        .next()
-        .ok_or_else(|| ParsingError::LogPartNotFound("client_id".to_owned()))?
-        .parse::<u32>()?;
+/// handles the `ClientConnect` event and initializes the `players_data`
+/// opening a new session for the client
+///
+/// a client that is already connected keeps its data and session
+fn handle_client_connect(client_id: u32, time: u32, players_data: &mut HashMap<u32, PlayerData>) {
     players_data.entry(client_id).or_insert_with(|| PlayerData {
         name: "unknown".to_owned(),
-        kills: 0,
+        sessions: vec![Session {
+            connected_at: time,
+            ..Default::default()
+        }],
+        ..Default::default()
     });
+}
+
+/// handles the `ClientBegin` event
+/// marking when the client actually entered the game
+///
+/// can error if the player is not found
+fn handle_client_begin(
+    client_id: u32,
+    time: u32,
+    players_data: &mut HashMap<u32, PlayerData>,
+) -> Result<(), ParsingError> {
+    let data = players_data
+        .get_mut(&client_id)
+        .ok_or(ParsingError::UnknownClient(client_id))?;
+    if let Some(session) = data.sessions.last_mut() {
+        session.began_at.get_or_insert(time);
+    }
+
+    Ok(())
+}
+
+/// handles the `ClientDisconnect` event
+/// closing the client session and freeing its client id,
+/// the player data is moved to the `departed_players`
+///
+/// can error if the player is not found
+fn handle_client_disconnect(
+    client_id: u32,
+    time: u32,
+    players_data: &mut HashMap<u32, PlayerData>,
+    departed_players: &mut Vec<PlayerData>,
+) -> Result<(), ParsingError> {
+    let mut data = players_data
+        .remove(&client_id)
+        .ok_or(ParsingError::UnknownClient(client_id))?;
+    if let Some(session) = data.sessions.last_mut() {
+        session.disconnected_at.get_or_insert(time);
+    }
+    departed_players.push(data);
+
+    Ok(())
+}
+
+/// Returns the value of a key in the backslash separated user info
+/// (e.g. `n\Isgalamido\t\0\model\uriel/zael`)
+fn userinfo_value<'info>(userinfo: &'info str, key: &str) -> Option<&'info str> {
+    let mut parts = userinfo.split('\\');
+    while let (Some(info_key), Some(value)) = (parts.next(), parts.next()) {
+        if info_key == key {
+            return Some(value);
+        }
+    }
+    None
+}
+
+/// handles the `ClientUserinfoChanged` event and updates the `players_data`
+/// with the player name, team and GUID
+/// a new name is kept in the player name history
+/// and a team change, at `time`, in the player team history
+///
+/// a client that never connected (e.g. the log starts in the middle of a game)
+/// is connected at `time` in lenient mode
+///
+/// can error, in strict mode, if the client never connected
+fn handle_user_info(
+    client_id: u32,
+    name: String,
+    team: Team,
+    guid: Option<&str>,
+    time: u32,
+    mode: ParseMode,
+    players_data: &mut HashMap<u32, PlayerData>,
+) -> Result<(), ParsingError> {
+    if mode == ParseMode::Lenient {
+        handle_client_connect(client_id, time, players_data);
+    }
+    let data = players_data
+        .get_mut(&client_id)
+        .ok_or(ParsingError::UnknownClient(client_id))?;
+    if !data.name_history.contains(&name) {
+        data.name_history.push(name.clone());
+    }
+    data.name = name;
+    data.team = team;
+    if let Some(guid) = guid.filter(|guid| !guid.is_empty()) {
+        data.guid = Some(guid.to_owned());
+    }
+    if data.team_history.last().map(|change| change.team) != Some(team) {
+        data.team_history.push(TeamChange { time, team });
+    }
+
+    Ok(())
+}
+
+/// handles the Item event and updates the `players_data`
+/// with the item pickup
+///
+/// can error if the player is not found or if the pickup count overflows
+fn handle_item(
+    client_id: u32,
+    item: String,
+    players_data: &mut HashMap<u32, PlayerData>,
+) -> Result<(), ParsingError> {
+    let data = players_data
+        .get_mut(&client_id)
+        .ok_or(ParsingError::UnknownClient(client_id))?;
+    let count = data.items.entry(item).or_default();
+    *count = count
+        .checked_add(1)
+        .ok_or_else(|| ParsingError::UnexpectedError("Item count overflow".to_owned()))?;
 
     Ok(())
 }
 
-/// parses the `ClientUserinfoChanged` event and updates the `players_data`
-/// with the player name
-fn parse_user_info<'part, I>(
-    parts: &mut I,
+/// handles the score event, printed by the server at the end of the game,
+/// and keeps the score in the `players_data`
+///
+/// can error if the player is not found
+fn handle_score(
+    client_id: u32,
+    score: i32,
     players_data: &mut HashMap<u32, PlayerData>,
-) -> Result<(), ParsingError>
-where
-    I: Iterator<Item = &'part str>,
-{
-    let client_id = parts
-        .next()
-        .ok_or_else(|| ParsingError::LogPartNotFound("client_id".to_owned()))?
-        .parse::<u32>()?;
-    let name = parts.collect::<Vec<&str>>().join(" ");
-    let name = name
-        .chars()
-        .skip(2)
-        .take_while(|&c| c != '\\')
-        .collect::<String>();
+) -> Result<(), ParsingError> {
     players_data
         .get_mut(&client_id)
-        .expect("Player not found")
-        .name = name;
+        .ok_or(ParsingError::UnknownClient(client_id))?
+        .server_score = Some(score);
 
     Ok(())
 }
 
-/// parses the Kill event and updates the `players_data`
-/// with the number of kills
+/// Returns whether the kill is a player of a team game killing a teammate
+fn is_team_kill(
+    killer_id: u32,
+    victim_id: u32,
+    game_type: GameType,
+    players_data: &HashMap<u32, PlayerData>,
+) -> bool {
+    game_type.is_team_game()
+        && killer_id != victim_id
+        && matches!(
+            (players_data.get(&killer_id), players_data.get(&victim_id)),
+            (Some(killer), Some(victim))
+                if killer.team == victim.team && matches!(killer.team, Team::Red | Team::Blue)
+        )
+}
+
+/// updates the `team_stats` with a kill of a team game
+/// credited to the team of the killer at the time of the kill
+///
+/// world kills, suicides and kills by players without a team are not counted
+///
+/// can error if a counter overflows
+fn handle_team_stats(
+    killer_id: u32,
+    victim_id: u32,
+    game_type: GameType,
+    players_data: &HashMap<u32, PlayerData>,
+    team_stats: &mut BTreeMap<Team, TeamStats>,
+) -> Result<(), ParsingError> {
+    if !game_type.is_team_game() || killer_id == victim_id {
+        return Ok(());
+    }
+    let Some(team) = players_data
+        .get(&killer_id)
+        .map(|killer| killer.team)
+        .filter(|team| matches!(team, Team::Red | Team::Blue))
+    else {
+        return Ok(());
+    };
+
+    let stats = team_stats.entry(team).or_default();
+    let counter = if is_team_kill(killer_id, victim_id, game_type, players_data) {
+        &mut stats.team_kills
+    } else {
+        &mut stats.kills
+    };
+    *counter = counter
+        .checked_add(1)
+        .ok_or_else(|| ParsingError::UnexpectedError("Team kill count overflow".to_owned()))?;
+
+    Ok(())
+}
+
+/// handles the Kill event and updates the kill counters of the `players_data`
 /// as well as the `total_kills` vector with the mean of death
 ///
-/// can error if the parsing of the u32 fails (`std::num::ParseIntError`)
+/// the victim always gets a death, and the killer gets
+/// - nothing when it's the world, the victim gets a world death instead
+/// - a suicide when it killed itself
+/// - a team kill when it killed a teammate in a team game
+/// - a frag otherwise
 ///
-fn parse_kill<'part, I>(
-    parts: &mut I,
+/// can error if a player is not found or if a counter overflows,
+/// the players are looked up first so a kill that errors leaves the counters unchanged
+///
+fn handle_kill(
+    killer_id: u32,
+    victim_id: u32,
+    mean_death: MeanDeath,
+    game_type: GameType,
     total_kills: &mut u32,
     kills_by_means_death: &mut HashMap<MeanDeath, u32>,
     players_data: &mut HashMap<u32, PlayerData>,
-) -> Result<(), ParsingError>
-where
-    I: Iterator<Item = &'part str>,
-{
-    let killer_id = parts
-        .next()
-        .ok_or_else(|| ParsingError::LogPartNotFound("killer_id".to_owned()))?
-        .parse::<u32>()?;
-    let victim_id = parts
-        .next()
-        .ok_or_else(|| ParsingError::LogPartNotFound("victim_id".to_owned()))?
-        .parse::<u32>()?;
-
-    let mean_id_text = parts
-        .next()
-        .ok_or_else(|| ParsingError::LogPartNotFound("mean_id".to_owned()))?;
-    // removing the last character (that is a colon) from the mean_id_text
-    if mean_id_text.len() <= 1 {
-        return Err(ParsingError::LogPartNotFound("mean_id".to_owned()));
-    }
-    let mean_id = mean_id_text[..mean_id_text.len().saturating_sub(1)].parse::<u32>()?;
-    let mean_death = MeanDeath::from(mean_id);
-    *total_kills = total_kills
+) -> Result<(), ParsingError> {
+    let killer_scores = killer_id != WORLD_ID && killer_id != victim_id;
+    if killer_scores && !players_data.contains_key(&killer_id) {
+        return Err(ParsingError::UnknownClient(killer_id));
+    }
+    if !players_data.contains_key(&victim_id) {
+        return Err(ParsingError::UnknownClient(victim_id));
+    }
+    let new_total_kills = total_kills
         .checked_add(1)
         .ok_or_else(|| ParsingError::UnexpectedError("Total kills overflow".to_owned()))?;
+    let mean_death_count = kills_by_means_death
+        .get(&mean_death)
+        .copied()
+        .unwrap_or_default()
+        .checked_add(1)
+        .ok_or_else(|| ParsingError::UnexpectedError("Mean of death count overflow".to_owned()))?;
+    *total_kills = new_total_kills;
+    kills_by_means_death.insert(mean_death.clone(), mean_death_count);
 
-    match kills_by_means_death.get_mut(&mean_death) {
-        Some(count) => {
-            *count = count.checked_add(1).ok_or_else(|| {
-                ParsingError::UnexpectedError("Mean of death count overflow".to_owned())
-            })?;
-        }
-        None => {
-            kills_by_means_death.insert(mean_death, 1);
-        }
-    }
+    let team_kill = is_team_kill(killer_id, victim_id, game_type, players_data);
 
-    if killer_id == WORLD_ID {
-        let data = players_data
-            .get_mut(&victim_id)
-            .ok_or_else(|| ParsingError::UnexpectedError("Victim not found".to_owned()))?;
-        data.kills = data.kills.checked_sub(1).ok_or_else(|| {
-            ParsingError::UnexpectedError("Player score has underflowed".to_owned())
+    let increment = |counter: &mut u32| -> Result<(), ParsingError> {
+        *counter = counter.checked_add(1).ok_or_else(|| {
+            ParsingError::UnexpectedError("Player kill counter has overflowed".to_owned())
         })?;
-    } else {
-        let data = players_data
+        Ok(())
+    };
+
+    if killer_scores {
+        let killer = players_data
             .get_mut(&killer_id)
-            .ok_or_else(|| ParsingError::UnexpectedError("Killer not found".to_owned()))?;
-        data.kills = data.kills.checked_add(1).ok_or_else(|| {
-            ParsingError::UnexpectedError("Player score has overflowed".to_owned())
+            .ok_or(ParsingError::UnknownClient(killer_id))?;
+        increment(if team_kill {
+            &mut killer.team_kills
+        } else {
+            &mut killer.frags
         })?;
+        increment(killer.kills_by_means.entry(mean_death).or_default())?;
+    }
+
+    let victim = players_data
+        .get_mut(&victim_id)
+        .ok_or(ParsingError::UnknownClient(victim_id))?;
+    increment(&mut victim.deaths)?;
+    if killer_id == WORLD_ID {
+        increment(&mut victim.world_deaths)?;
+    } else if killer_id == victim_id {
+        increment(&mut victim.suicides)?;
     }
 
     Ok(())
 }
 
+/// handles the Kill event for the kill matrix
+/// counting the kill under the current names of the killer and the victim
+///
+/// the world kills and the kills of players that aren't found are not counted
+fn handle_kill_matrix(
+    killer_id: u32,
+    victim_id: u32,
+    players_data: &HashMap<u32, PlayerData>,
+    kill_matrix: &mut BTreeMap<String, BTreeMap<String, u32>>,
+) {
+    if killer_id == WORLD_ID {
+        return;
+    }
+    let (Some(killer), Some(victim)) = (players_data.get(&killer_id), players_data.get(&victim_id))
+    else {
+        return;
+    };
+    let kills = kill_matrix
+        .entry(killer.name.clone())
+        .or_default()
+        .entry(victim.name.clone())
+        .or_default();
+    *kills = kills.saturating_add(1);
+}
+
+/// applies a single event, that happened at `time`, to the game state
+/// returns the finished game when the event closes one
+///
+/// the `mode` decides how events of clients that never connected are handled
+fn apply_event(
+    time: u32,
+    utc: Option<i64>,
+    event: &Event,
+    mode: ParseMode,
+    state: &mut GameState,
+) -> Result<Option<Game>, ParsingError> {
+    match event {
+        Event::InitGame { settings } => {
+            // the game without a shutdown ends at its last event, the clock may have been reset
+            let finished_game = state.started.then(|| {
+                let ended_at = state.last_event_at;
+                finish_game_and_set_new_game(
+                    state,
+                    ended_at,
+                    Some(IncompleteReason::NewGameStarted),
+                )
+            });
+            state.settings = GameSettings::from(settings.as_str());
+            state.record_event(time, utc);
+            return Ok(finished_game);
+        }
+        Event::ShutdownGame => {
+            state.last_event_at_utc = utc.or(state.last_event_at_utc);
+            return Ok(Some(finish_game_and_set_new_game(state, time, None)));
+        }
+        Event::ClientConnect { client_id } => {
+            handle_client_connect(*client_id, time, &mut state.players_data);
+        }
+        Event::ClientBegin { client_id, .. } => {
+            handle_client_begin(*client_id, time, &mut state.players_data)?;
+        }
+        Event::ClientDisconnect { client_id, .. } => {
+            handle_client_disconnect(
+                *client_id,
+                time,
+                &mut state.players_data,
+                &mut state.departed_players,
+            )?;
+        }
+        Event::ClientUserinfoChanged {
+            client_id,
+            name,
+            userinfo,
+            ..
+        } => {
+            let team = userinfo_value(userinfo, "t")
+                .map(Team::from)
+                .unwrap_or_default();
+            let guid = userinfo_value(userinfo, "cl_guid");
+            handle_user_info(
+                *client_id,
+                name.clone(),
+                team,
+                guid,
+                time,
+                mode,
+                &mut state.players_data,
+            )?;
+        }
+        Event::Kill {
+            killer_id,
+            victim_id,
+            mean_death,
+            ..
+        } => {
+            state.record_kill(time, *killer_id, *victim_id, mean_death)?;
+        }
+        Event::Item {
+            client_id, item, ..
+        } => {
+            handle_item(*client_id, item.clone(), &mut state.players_data)?;
+        }
+        Event::Exit { reason } => {
+            state.exit_reason = ExitReason::from(reason.as_str());
+        }
+        Event::Score {
+            score, client_id, ..
+        } => {
+            handle_score(*client_id, *score, &mut state.players_data)?;
+        }
+        Event::TeamScore { red, blue } => {
+            state.server_team_scores =
+                Some(BTreeMap::from([(Team::Red, *red), (Team::Blue, *blue)]));
+        }
+        Event::Say { .. } => {}
+    }
+    state.record_event(time, utc);
+
+    Ok(None)
+}
+
+#[allow(dead_code)]
+// the binary streams the log with `scan_reader`
+// but this entry point is kept for logs that are already in memory
 /// scans the file and returns a vector of games
 /// each game contains a vector of `total_kills` and a hashmap of `players_data`
 /// the `players_data` hashmap contains the player id as key and the player data as value
 pub fn scan_file(log_content: &str) -> Result<Vec<Game>, ParsingError> {
-    let mut games: Vec<Game> = Vec::new();
-    let mut total_kills: u32 = 0;
-    let mut kills_by_means_death: HashMap<MeanDeath, u32> = HashMap::new();
-    let mut players_data: HashMap<u32, PlayerData> = HashMap::new();
-
-    for line in log_content.lines() {
-        let mut parts = line.split_whitespace();
-        let Some(time) = parts.next() else {
-            // skip empty lines
-            continue;
+    scan_reader(
+        log_content.as_bytes(),
+        ParseMode::Strict,
+        TimestampFormat::Auto,
+    )
+    .collect()
+}
+
+#[allow(dead_code)]
+// same as `scan_file`, for logs that are already in memory
+/// scans the file in lenient mode
+/// returns the games of the log along with the diagnostics of the skipped lines
+pub fn scan_file_lenient(log_content: &str) -> ParsedLog {
+    scan_reader(
+        log_content.as_bytes(),
+        ParseMode::Lenient,
+        TimestampFormat::Auto,
+    )
+    .parse_log()
+    // reading from memory never fails
+    .unwrap_or_default()
+}
+
+/// Streaming parser over any `BufRead`
+/// aggregates the `EventStream` into games
+/// yields each `Game` as soon as it is finished
+///
+/// Only the current line and the game being parsed are kept in memory,
+/// so it can be used on logs that don't fit in memory
+///
+/// An error doesn't end the stream, the caller decides whether to stop
+/// (collecting into a `Result<Vec<Game>, ParsingError>` stops at the first error)
+/// or uses `GameStream::parse_log` to follow the parse mode
+#[derive(Debug)]
+pub struct GameStream<R> {
+    /// The events read from the log
+    events: EventStream<R>,
+    /// The game currently being parsed
+    state: GameState,
+    /// How the bad lines are handled
+    mode: ParseMode,
+    /// When the previous game ended, to detect the clock resets
+    previous_ended_at: Option<u32>,
+    /// The unix time of the first event of a log without `MM:SS` times
+    /// its events are on a log clock starting there
+    utc_origin: Option<i64>,
+    /// Whether the game still running at the end of the log is kept for a later resume
+    /// instead of being finished as `IncompleteReason::LogEnded`
+    keep_running_game: bool,
+    /// The kills read since the kill feed was last taken, `None` when it isn't kept
+    kill_feed: Option<Vec<KillFeedEntry>>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+/// A kill of the running game, for a live kill feed, see `GameStream::with_kill_feed`
+pub struct KillFeedEntry {
+    /// When the kill happened, on the log clock
+    pub time: u32,
+    /// The name of the killer at the time of the kill, `None` when the world killed the victim
+    pub killer: Option<String>,
+    /// The name of the victim at the time of the kill
+    pub victim: String,
+    /// How the victim died
+    pub mean_death: MeanDeath,
+}
+
+impl Display for KillFeedEntry {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "{:02}:{:02} {} killed {} by {}",
+            self.time.div_euclid(60),
+            self.time.rem_euclid(60),
+            self.killer.as_deref().unwrap_or("<world>"),
+            self.victim,
+            self.mean_death
+        )
+    }
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+/// Where the parsing of a log stopped, see `GameStream::checkpoint`
+/// holds everything needed to resume it once more lines are appended to the log
+///
+/// a resumed parsing yields the same games as a parsing of the whole log
+pub struct ParseCheckpoint {
+    /// The position after the last complete line parsed
+    position: LogPosition,
+    /// The timestamp format of the log, once detected
+    format: TimestampFormat,
+    /// The game still running at the position
+    state: GameState,
+    /// When the previous game ended, to detect the clock resets
+    previous_ended_at: Option<u32>,
+    /// The unix time of the first event of a log without `MM:SS` times
+    utc_origin: Option<i64>,
+}
+
+impl ParseCheckpoint {
+    /// Returns the checkpoint of a log not parsed yet, with timestamps following the `format`
+    pub fn new(format: TimestampFormat) -> Self {
+        Self {
+            format,
+            ..Self::default()
+        }
+    }
+
+    /// Returns the position after the last complete line parsed
+    pub const fn position(&self) -> LogPosition {
+        self.position
+    }
+
+    /// Returns whether a game was still running at the position
+    pub const fn has_running_game(&self) -> bool {
+        self.state.started
+    }
+}
+
+impl<R: BufRead> GameStream<R> {
+    /// Marks the game whose clock was reset since the previous game
+    /// and keeps its end for the next one
+    fn clock_game(&mut self, mut game: Game) -> Game {
+        game.clock_reset = self
+            .previous_ended_at
+            .is_some_and(|previous_ended_at| game.started_at < previous_ended_at);
+        self.previous_ended_at = Some(game.ended_at);
+        game
+    }
+
+    /// Returns the time of a line on the log clock
+    /// the `MM:SS` time when the line has one,
+    /// the seconds since the first event of the log otherwise
+    fn clock(&mut self, timestamp: Timestamp) -> u32 {
+        match (timestamp.clock, timestamp.utc) {
+            (Some(clock), _) => clock,
+            (None, Some(utc)) => {
+                let origin = *self.utc_origin.get_or_insert(utc);
+                u32::try_from(utc.saturating_sub(origin).max(0)).unwrap_or(u32::MAX)
+            }
+            (None, None) => self.state.last_event_at,
+        }
+    }
+
+    /// Keeps the kills read from now on, to be taken with `GameStream::take_kill_feed`
+    #[must_use]
+    pub fn with_kill_feed(mut self) -> Self {
+        self.kill_feed = Some(Vec::new());
+        self
+    }
+
+    /// Returns the kills read since the kill feed was last taken, oldest first
+    /// always empty unless the stream was built `with_kill_feed`
+    pub fn take_kill_feed(&mut self) -> Vec<KillFeedEntry> {
+        self.kill_feed
+            .as_mut()
+            .map(std::mem::take)
+            .unwrap_or_default()
+    }
+
+    /// Returns the kill feed entry of the event, when it's a kill and the kill feed is kept
+    /// the names are the ones of the players at the time of the kill
+    fn kill_feed_entry(&self, time: u32, event: &Event) -> Option<KillFeedEntry> {
+        let Event::Kill {
+            killer_id,
+            victim_id,
+            mean_death,
+            ..
+        } = event
+        else {
+            return None;
         };
-        if time.len() < 4 || !(time.chars().all(|c| c.is_numeric() || c == ':')) {
-            // skip lines that don't start with a timestamp
-            continue;
-        }
-        let event = parts
-            .next()
-            .ok_or_else(|| ParsingError::LogPartNotFound("event".to_owned()))?;
-
-        match event {
-            "InitGame:" => {
-                if !kills_by_means_death.is_empty() {
-                    finish_game_and_set_new_game(
-                        &mut games,
-                        &mut total_kills,
-                        &mut kills_by_means_death,
-                        &mut players_data,
-                    );
+        self.kill_feed.as_ref()?;
+        let name = |client_id| {
+            self.state
+                .players_data
+                .get(&client_id)
+                .map(|player| player.name.clone())
+        };
+        let killer = if *killer_id == WORLD_ID {
+            None
+        } else {
+            Some(name(*killer_id)?)
+        };
+        Some(KillFeedEntry {
+            time,
+            killer,
+            victim: name(*victim_id)?,
+            mean_death: mean_death.clone(),
+        })
+    }
+
+    /// Parses the rest of the log following the parse mode
+    /// - strict: stops at the first error and returns it
+    /// - lenient: skips the bad lines and keeps their errors as diagnostics
+    ///
+    /// can error, even in lenient mode, if the log can't be read anymore
+    /// (a line that isn't valid utf-8 is only skipped)
+    pub fn parse_log(mut self) -> Result<ParsedLog, ParsingError> {
+        self.parse_available()
+    }
+
+    /// Parses the lines available in the log, following the parse mode, see `GameStream::parse_log`
+    /// the stream is kept to get its checkpoint or to parse the lines appended later
+    pub fn parse_available(&mut self) -> Result<ParsedLog, ParsingError> {
+        let mode = self.mode;
+        let mut parsed = ParsedLog::default();
+        for game in self.by_ref() {
+            match game {
+                Ok(game) => parsed.games.push(game),
+                Err(err) if mode == ParseMode::Lenient && is_recoverable(&err) => {
+                    parsed.diagnostics.push(err);
                 }
+                Err(err) => return Err(err),
             }
-            "ShutdownGame:" => {
-                finish_game_and_set_new_game(
-                    &mut games,
-                    &mut total_kills,
-                    &mut kills_by_means_death,
-                    &mut players_data,
+        }
+
+        Ok(parsed)
+    }
+
+    /// Returns the position after the last complete line read
+    pub const fn log_position(&self) -> LogPosition {
+        self.events.log_position()
+    }
+
+    /// Returns the checkpoint to resume the parsing after the last complete line read
+    /// see `resume_reader`
+    pub fn checkpoint(&self) -> ParseCheckpoint {
+        ParseCheckpoint {
+            position: self.events.log_position(),
+            format: self.events.format(),
+            state: self.state.clone(),
+            previous_ended_at: self.previous_ended_at,
+            utc_origin: self.utc_origin,
+        }
+    }
+}
+
+/// Returns whether the parsing can go on after the error, skipping its line
+/// every error but the ones reading the log is tied to a single line
+pub fn is_recoverable(err: &ParsingError) -> bool {
+    !matches!(err.inner(), ParsingError::IoError(kind) if !matches!(kind, std::io::ErrorKind::InvalidData))
+}
+
+impl<R: BufRead> Iterator for GameStream<R> {
+    type Item = Result<Game, ParsingError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            let Some(event) = self.events.next() else {
+                // the log ended in the middle of a game
+                let time = self.state.last_event_at;
+                if !self.state.started || self.keep_running_game {
+                    return None;
+                }
+                let game = finish_game_and_set_new_game(
+                    &mut self.state,
+                    time,
+                    Some(IncompleteReason::LogEnded),
                 );
+                return Some(Ok(self.clock_game(game)));
+            };
+            let (timestamp, event) = match event {
+                Ok(event) => event,
+                Err(err) => return Some(Err(err)),
+            };
+            let time = self.clock(timestamp);
+            let kill = self.kill_feed_entry(time, &event);
+            match apply_event(time, timestamp.utc, &event, self.mode, &mut self.state) {
+                Ok(Some(game)) => return Some(Ok(self.clock_game(game))),
+                Ok(None) => {
+                    if let (Some(kill_feed), Some(kill)) = (&mut self.kill_feed, kill) {
+                        kill_feed.push(kill);
+                    }
+                }
+                Err(err) => {
+                    // an unknown client is pointed at, the other errors are about the whole line
+                    let span = match err.inner() {
+                        ParsingError::UnknownClient(client_id) => event.client_span(*client_id),
+                        _ => None,
+                    };
+                    let span = span.unwrap_or_else(|| self.events.line_span());
+                    return Some(Err(err.at(self.events.location(span))));
+                }
             }
-            "ClientConnect:" => {
-                parse_client_connect(&mut parts, &mut players_data)?;
-            }
-            "ClientUserinfoChanged:" => {
-                parse_user_info(&mut parts, &mut players_data)?;
-            }
-            "Kill:" => {
-                parse_kill(
-                    &mut parts,
-                    &mut total_kills,
-                    &mut kills_by_means_death,
-                    &mut players_data,
-                )?;
-            }
-            _ => {}
         }
     }
+}
 
-    Ok(games)
+/// scans the log from a reader, one line at a time
+/// returns an iterator over the finished games
+///
+/// same parsing rules as `scan_file`, but without loading the whole log in memory
+/// the `mode` is followed by `GameStream::parse_log`
+/// the line timestamps follow the `format`, see `TimestampFormat`
+pub fn scan_reader<R: BufRead>(
+    reader: R,
+    mode: ParseMode,
+    format: TimestampFormat,
+) -> GameStream<R> {
+    GameStream {
+        events: read_events(reader, format),
+        state: GameState::default(),
+        mode,
+        previous_ended_at: None,
+        utc_origin: None,
+        keep_running_game: false,
+        kill_feed: None,
+    }
+}
+
+/// resumes the parsing of a log from a checkpoint, see `GameStream::checkpoint`
+/// the `reader` must start at the checkpoint position
+/// returns an iterator over the games finished after the checkpoint
+///
+/// unlike `scan_reader`, the log is expected to grow: a last line without line break
+/// is left unread and the game still running at the end of the log is kept for the next checkpoint
+pub fn resume_reader<R: BufRead>(
+    reader: R,
+    mode: ParseMode,
+    checkpoint: ParseCheckpoint,
+) -> GameStream<R> {
+    GameStream {
+        events: resume_events(reader, checkpoint.format, checkpoint.position),
+        state: checkpoint.state,
+        mode,
+        previous_ended_at: checkpoint.previous_ended_at,
+        utc_origin: checkpoint.utc_origin,
+        keep_running_game: true,
+        kill_feed: None,
+    }
 }
 
 #[cfg(test)]
+#[allow(
+    clippy::arithmetic_side_effects,
+    clippy::assertions_on_result_states,
+    clippy::default_numeric_fallback,
+    clippy::panic,
+    clippy::shadow_unrelated,
+    clippy::similar_names,
+    clippy::unseparated_literal_suffix,
+    clippy::unwrap_used
+)]
+// the tests unwrap and panic on the unexpected results, and rely on literals for readability
 mod tests {
     use super::*;
     use proptest::prelude::*;
 
     prop_compose! {
-        fn arb_player_data()(name in "[a-z]*", kills in any::<i32>()) -> PlayerData {
-            PlayerData { name, kills }
+        fn arb_player_data()(name in "[a-z]*", frags in any::<u16>(), world_deaths in any::<u16>()) -> PlayerData {
+            PlayerData { name, frags: frags.into(), deaths: world_deaths.into(), world_deaths: world_deaths.into(), ..Default::default() }
         }
     }
 
     proptest! {
         #[test]
-        fn test_parse_client_connect(
-            client_connect_line in (any::<u32>().prop_map(|v| v.to_string())),
+        fn test_handle_client_connect(
+            client_id in any::<u32>(),
+            time in any::<u32>(),
             mut players_data in prop::collection::hash_map(any::<u32>(), arb_player_data(), 0..10)
         ) {
-            let mut parts = client_connect_line.split_whitespace();
-            let client_id = parts.clone().next().unwrap().parse::<u32>().unwrap();
-
+            let new_player = PlayerData {
+                name: "unknown".to_owned(),
+                sessions: vec![Session { connected_at: time, ..Default::default() }],
+                ..Default::default()
+            };
             if players_data.contains_key(&client_id) {
-                let result = parse_client_connect(&mut parts, &mut players_data);
-                prop_assert!(result.is_ok());
+                let previous = players_data.get(&client_id).unwrap().clone();
+                handle_client_connect(client_id, time, &mut players_data);
                 prop_assert!(players_data.contains_key(&client_id));
-                prop_assert_ne!(players_data.get(&client_id).unwrap(), &PlayerData { name: "unknown".to_owned(), kills: 0 });
+                prop_assert_eq!(players_data.get(&client_id).unwrap(), &previous);
+                prop_assert_ne!(players_data.get(&client_id).unwrap(), &new_player);
             }
             else {
-                let result = parse_client_connect(&mut parts, &mut players_data);
-                prop_assert!(result.is_ok());
+                handle_client_connect(client_id, time, &mut players_data);
                 prop_assert!(players_data.contains_key(&client_id));
-                prop_assert_eq!(players_data.get(&client_id).unwrap(), &PlayerData { name: "unknown".to_owned(), kills: 0 });
+                prop_assert_eq!(players_data.get(&client_id).unwrap(), &new_player);
             }
         }
     }
 
     proptest! {
         #[test]
-        fn test_parse_client_connect_part_not_found(
-            client_connect_line in "\\s*",
+        fn test_handle_user_info(
+            client_id in any::<u32>(),
+            name in "\\w+",
             mut players_data in prop::collection::hash_map(any::<u32>(), arb_player_data(), 0..10)
         ) {
-            let mut parts = client_connect_line.split_whitespace();
+            prop_assume!(name != "unknown");
+            players_data.insert(client_id, PlayerData { name: "unknown".to_owned(), ..Default::default() });
 
-            let result = parse_client_connect(&mut parts, &mut players_data);
-            match result {
-                Err(ParsingError::LogPartNotFound(_)) => {},
-                _ => prop_assert!(false),
-            }
+            handle_user_info(client_id, name.clone(), Team::Free, None, 0, ParseMode::Strict, &mut players_data).unwrap();
+            prop_assert!(players_data.contains_key(&client_id));
+            prop_assert_ne!(players_data.get(&client_id).unwrap(), &PlayerData { name: "unknown".to_owned(), ..Default::default() });
+            prop_assert_eq!(players_data.get(&client_id).unwrap(), &PlayerData {
+                name: name.clone(),
+                team_history: vec![TeamChange { time: 0, team: Team::Free }],
+                name_history: vec![name],
+                ..Default::default()
+            });
         }
     }
 
-    proptest! {
-        #[test]
-        fn test_parse_client_connect_parseint_error(
-            client_connect_line in "[^\\d\\s]+", // match everything that is not a digit or a whitespace
-            mut players_data in prop::collection::hash_map(any::<u32>(), arb_player_data(), 0..10)
-        ) {
-            let mut parts = client_connect_line.split_whitespace();
-
-            let result = parse_client_connect(&mut parts, &mut players_data);
-            match result {
-                Err(ParsingError::ParseIntError(_)) => {},
-                _ => {
-                    prop_assert!(false)
+    #[test]
+    fn test_handle_user_info_team_history() {
+        let mut players_data = HashMap::from([(2, PlayerData::default())]);
+        handle_user_info(
+            2,
+            "Isgalamido".to_owned(),
+            Team::Red,
+            None,
+            10,
+            ParseMode::Strict,
+            &mut players_data,
+        )
+        .unwrap();
+        handle_user_info(
+            2,
+            "Isgalamido".to_owned(),
+            Team::Red,
+            None,
+            20,
+            ParseMode::Strict,
+            &mut players_data,
+        )
+        .unwrap();
+        handle_user_info(
+            2,
+            "Isgalamido".to_owned(),
+            Team::Blue,
+            None,
+            30,
+            ParseMode::Strict,
+            &mut players_data,
+        )
+        .unwrap();
+
+        let player = &players_data[&2];
+        assert_eq!(player.team, Team::Blue);
+        assert_eq!(
+            player.team_history,
+            vec![
+                TeamChange {
+                    time: 10,
+                    team: Team::Red
                 },
-            }
-        }
+                TeamChange {
+                    time: 30,
+                    team: Team::Blue
+                },
+            ]
+        );
     }
 
-    proptest! {
-        #[test]
-        fn test_parse_user_info(
-            client_id in any::<u32>(),
-            two_chars in "[\\S]{2}",
-            name in "\\w*",
-            rest in "\\PC*",
-            mut players_data in prop::collection::hash_map(any::<u32>(), arb_player_data(), 0..10)
-        ) {
-            let user_info_line = format!("{} {}{}\\{}", client_id, two_chars, name, rest);
-            players_data.insert(client_id, PlayerData { name: "unknown".to_owned(), kills: 0 });
-
-            let mut parts = user_info_line.split_whitespace();
-            let client_id = parts.clone().next().unwrap().parse::<u32>().unwrap();
-
-            let result = parse_user_info(&mut parts, &mut players_data);
-            prop_assert!(result.is_ok());
-            prop_assert!(players_data.contains_key(&client_id));
-            prop_assert_ne!(players_data.get(&client_id).unwrap(), &PlayerData { name: "unknown".to_owned(), kills: 0 });
-            prop_assert_eq!(players_data.get(&client_id).unwrap(), &PlayerData { name: name.to_owned(), kills: 0 });
-        }
+    #[test]
+    fn test_handle_user_info_unknown_client() {
+        let mut players_data = HashMap::new();
+        assert_eq!(
+            handle_user_info(
+                2,
+                "Isgalamido".to_owned(),
+                Team::Red,
+                None,
+                10,
+                ParseMode::Strict,
+                &mut players_data,
+            ),
+            Err(ParsingError::UnknownClient(2))
+        );
+        assert!(players_data.is_empty());
+
+        handle_user_info(
+            2,
+            "Isgalamido".to_owned(),
+            Team::Red,
+            None,
+            10,
+            ParseMode::Lenient,
+            &mut players_data,
+        )
+        .unwrap();
+        let player = &players_data[&2];
+        assert_eq!(player.name, "Isgalamido");
+        assert_eq!(player.team, Team::Red);
+        assert_eq!(
+            player.sessions,
+            vec![Session {
+                connected_at: 10,
+                ..Default::default()
+            }]
+        );
     }
 
-    proptest! {
-        #[test]
-        fn test_parse_user_info_part_not_found(
-            user_info_line in "\\s*",
-            mut players_data in prop::collection::hash_map(any::<u32>(), arb_player_data(), 0..10)
-        ) {
-            let mut parts = user_info_line.split_whitespace();
-
-            let result = parse_user_info(&mut parts, &mut players_data);
-            match result {
-                Err(ParsingError::LogPartNotFound(_)) => {},
-                _ => prop_assert!(false),
-            }
-        }
-    }
+    #[test]
+    fn test_handle_team_stats() {
+        let players_data = HashMap::from([
+            (
+                2,
+                PlayerData {
+                    team: Team::Red,
+                    ..Default::default()
+                },
+            ),
+            (
+                3,
+                PlayerData {
+                    team: Team::Red,
+                    ..Default::default()
+                },
+            ),
+            (
+                4,
+                PlayerData {
+                    team: Team::Blue,
+                    ..Default::default()
+                },
+            ),
+        ]);
+        let mut team_stats = BTreeMap::new();
+        let tdm = GameType::TeamDeathmatch;
+        handle_team_stats(2, 4, tdm, &players_data, &mut team_stats).unwrap();
+        handle_team_stats(2, 3, tdm, &players_data, &mut team_stats).unwrap();
+        handle_team_stats(4, 2, tdm, &players_data, &mut team_stats).unwrap();
+        handle_team_stats(WORLD_ID, 2, tdm, &players_data, &mut team_stats).unwrap();
+        handle_team_stats(3, 3, tdm, &players_data, &mut team_stats).unwrap();
 
-    proptest! {
-        #[test]
-        fn test_parse_user_info_parseint_error(
-            client_id in "[^\\d\\s]+", // match everything that is not a digit or a whitespace
-            two_chars in "\\PC*",
-            name in "\\PC*",
-            rest in "\\PC*",
-            mut players_data in prop::collection::hash_map(any::<u32>(), arb_player_data(), 0..10)
-        ) {
-            let user_info_line = format!("{} {}{}\\{}", client_id, two_chars, name, rest);
-            let mut parts = user_info_line.split_whitespace();
+        assert_eq!(
+            team_stats,
+            BTreeMap::from([
+                (
+                    Team::Red,
+                    TeamStats {
+                        kills: 1,
+                        team_kills: 1
+                    }
+                ),
+                (
+                    Team::Blue,
+                    TeamStats {
+                        kills: 1,
+                        team_kills: 0
+                    }
+                ),
+            ])
+        );
 
-            let result = parse_user_info(&mut parts, &mut players_data);
-            match result {
-                Err(ParsingError::ParseIntError(_)) => {},
-                _ => {
-                    prop_assert!(false)
-                },
-            }
-        }
+        let mut ffa_stats = BTreeMap::new();
+        handle_team_stats(2, 4, GameType::FreeForAll, &players_data, &mut ffa_stats).unwrap();
+        assert!(ffa_stats.is_empty());
     }
 
     fn a_random_mean_death() -> impl Strategy<Value = MeanDeath> {
@@ -382,62 +1505,120 @@ mod tests {
 
     proptest! {
         #[test]
-        fn test_parse_kill(
+        fn test_handle_kill(
             killer_id in any::<u32>(),
             victim_id in any::<u32>(),
             mean_id in 0..28u32,
-            rest in "\\PC*",
             mut players_data in prop::collection::hash_map(any::<u32>(), arb_player_data(), 0..10),
             mut kills_by_means_death in prop::collection::hash_map(a_random_mean_death(), any::<u32>(), 1),
         ) {
             prop_assume!(killer_id != victim_id);
 
-            let initial_total_kills: Vec<u32> = kills_by_means_death.values().cloned().collect();
+            let initial_total_kills: Vec<u32> = kills_by_means_death.values().copied().collect();
             let mut total_kills: u32 = initial_total_kills[0];
-            let kill_line = format!("{} {} {}: {}", killer_id, victim_id, mean_id, rest);
-            players_data.insert(killer_id, PlayerData { name: "unknown".to_owned(), kills: 0 });
-            players_data.insert(victim_id, PlayerData { name: "unknown".to_owned(), kills: 1 });
-
-            let mut parts = kill_line.split_whitespace();
-            let killer_id = parts.clone().next().unwrap().parse::<u32>().unwrap();
-            let victim_id = parts.clone().nth(1).unwrap().parse::<u32>().unwrap();
-            let mean_text = parts.clone().nth(2).unwrap();
-            // remove the last character (that is a colon) from the mean_text
-            let mean_id = mean_text[..mean_text.len().saturating_sub(1)].parse::<u32>().unwrap();
-
-            let result = parse_kill(&mut parts, &mut total_kills, &mut kills_by_means_death, &mut players_data);
+            players_data.insert(killer_id, PlayerData { name: "unknown".to_owned(), ..Default::default() });
+            players_data.insert(victim_id, PlayerData { name: "unknown".to_owned(), frags: 1, ..Default::default() });
+
+            let result = handle_kill(killer_id, victim_id, MeanDeath::from(mean_id), GameType::FreeForAll, &mut total_kills, &mut kills_by_means_death, &mut players_data);
             prop_assert!(result.is_ok());
 
             if killer_id == WORLD_ID {
-                prop_assert_eq!(players_data.get(&victim_id).unwrap().kills, 0);
+                prop_assert_eq!(players_data.get(&victim_id).unwrap().score(&ScoringRules::default()), 0);
             }
             else {
-                prop_assert_eq!(players_data.get(&killer_id).unwrap().kills, 1);
+                prop_assert_eq!(players_data.get(&killer_id).unwrap().score(&ScoringRules::default()), 1);
+                prop_assert_eq!(players_data.get(&killer_id).unwrap().frags, 1);
             }
+            prop_assert_eq!(players_data.get(&victim_id).unwrap().deaths, 1);
 
             prop_assert_eq!(total_kills, initial_total_kills[0] + 1);
             prop_assert!(kills_by_means_death.contains_key(&MeanDeath::from(mean_id)));
-            prop_assert_eq!(total_kills, kills_by_means_death.values().sum());
+            prop_assert_eq!(total_kills, kills_by_means_death.values().sum::<u32>());
         }
     }
 
+    #[test]
+    fn test_handle_kill_counters() {
+        let mut players_data = HashMap::from([
+            (
+                2,
+                PlayerData {
+                    team: Team::Red,
+                    ..Default::default()
+                },
+            ),
+            (
+                3,
+                PlayerData {
+                    team: Team::Red,
+                    ..Default::default()
+                },
+            ),
+            (
+                4,
+                PlayerData {
+                    team: Team::Blue,
+                    ..Default::default()
+                },
+            ),
+        ]);
+        let mut total_kills = 0;
+        let mut kills_by_means_death = HashMap::new();
+        let mut kill = |killer_id, victim_id| {
+            handle_kill(
+                killer_id,
+                victim_id,
+                MeanDeath::Railgun,
+                GameType::TeamDeathmatch,
+                &mut total_kills,
+                &mut kills_by_means_death,
+                &mut players_data,
+            )
+            .unwrap();
+        };
+        kill(2, 4);
+        kill(2, 3);
+        kill(2, 2);
+        kill(WORLD_ID, 2);
+        kill(4, 2);
+
+        let player = &players_data[&2];
+        assert_eq!(
+            (
+                player.frags,
+                player.deaths,
+                player.suicides,
+                player.world_deaths,
+                player.team_kills
+            ),
+            (1, 3, 1, 1, 1)
+        );
+        assert_eq!(player.score(&ScoringRules::default()), -2);
+        assert!((player.kd_ratio() - 1.0 / 3.0).abs() < f64::EPSILON);
+        // the frag and team kill, not the suicide
+        assert_eq!(player.kills_by_means.get(&MeanDeath::Railgun), Some(&2));
+        assert_eq!(players_data[&3].deaths, 1);
+        assert_eq!(players_data[&4].frags, 1);
+        assert_eq!(players_data[&4].deaths, 1);
+        assert!((players_data[&4].kd_ratio() - 1.0).abs() < f64::EPSILON);
+    }
+
     proptest! {
         #[test]
-        fn test_parse_kill_mean_id_not_found(
+        fn test_handle_kill_killer_unknown_client(
             killer_id in any::<u32>(),
             victim_id in any::<u32>(),
-            mean_id in "\\s*",
-            rest in "\\PC*",
-            mut players_data in prop::collection::hash_map(any::<u32>(), arb_player_data(), 0..10),
+            mean_id in 0..28u32,
             mut kills_by_means_death in prop::collection::hash_map(a_random_mean_death(), any::<u32>(), 0..10),
             mut total_kills in any::<u32>(),
         ) {
-            let kill_line = format!("{} {} {}: {}", killer_id, victim_id, mean_id, rest);
-            let mut parts = kill_line.split_whitespace();
+            prop_assume!(killer_id != victim_id);
 
-            let result = parse_kill(&mut parts, &mut total_kills, &mut kills_by_means_death, &mut players_data);
+            let mut players_data: HashMap<u32, PlayerData> = HashMap::new();
+            players_data.insert(victim_id, PlayerData { name: "unknown".to_owned(), frags: 1, ..Default::default() });
+            let result = handle_kill(killer_id, victim_id, MeanDeath::from(mean_id), GameType::FreeForAll, &mut total_kills, &mut kills_by_means_death, &mut players_data);
             match result {
-                Err(ParsingError::LogPartNotFound(_)) => {},
+                Err(ParsingError::UnknownClient(id)) => prop_assert_eq!(id, killer_id),
                 _ => prop_assert!(false),
             }
         }
@@ -445,21 +1626,20 @@ mod tests {
 
     proptest! {
         #[test]
-        fn test_parse_kill_victim_id_not_found(
-            killer_id in any::<u32>(),
-            victim_id in "\\s*",
+        fn test_handle_kill_victim_unknown_client(
+            victim_id in any::<u32>(),
             mean_id in 0..28u32,
-            rest in "\\PC*",
-            mut players_data in prop::collection::hash_map(any::<u32>(), arb_player_data(), 0..10),
             mut kills_by_means_death in prop::collection::hash_map(a_random_mean_death(), any::<u32>(), 0..10),
             mut total_kills in any::<u32>(),
         ) {
-            let kill_line = format!("{} {} {}: {}", killer_id, victim_id, mean_id, rest);
-            let mut parts = kill_line.split_whitespace();
+            let killer_id = WORLD_ID;
+            prop_assume!(killer_id != victim_id);
 
-            let result = parse_kill(&mut parts, &mut total_kills, &mut kills_by_means_death, &mut players_data);
+            let mut players_data: HashMap<u32, PlayerData> = HashMap::new();
+            players_data.insert(killer_id, PlayerData { name: "unknown".to_owned(), frags: 1, ..Default::default() });
+            let result = handle_kill(killer_id, victim_id, MeanDeath::from(mean_id), GameType::FreeForAll, &mut total_kills, &mut kills_by_means_death, &mut players_data);
             match result {
-                Err(ParsingError::ParseIntError(_)) => {},
+                Err(ParsingError::UnknownClient(id)) => prop_assert_eq!(id, victim_id),
                 _ => prop_assert!(false),
             }
         }
@@ -467,152 +1647,700 @@ mod tests {
 
     proptest! {
         #[test]
-        fn test_parse_kill_killer_id_not_found(
-            killer_id in "\\s*",
-            victim_id in any::<u32>(),
-            mean_id in 0..28u32,
-            rest in "\\PC*",
+        fn test_handle_item(
+            client_id in any::<u32>(),
+            item in "[a-z_]+",
+            pickups in 1..10_u32,
+        ) {
+            let mut players_data: HashMap<u32, PlayerData> = HashMap::new();
+            handle_client_connect(client_id, 0, &mut players_data);
+
+            for _ in 0..pickups {
+                let result = handle_item(client_id, item.clone(), &mut players_data);
+                prop_assert!(result.is_ok());
+            }
+            prop_assert_eq!(players_data.get(&client_id).unwrap().items.get(&item), Some(&pickups));
+        }
+    }
+
+    proptest! {
+        #[test]
+        fn test_handle_item_unknown_client(
+            client_id in any::<u32>(),
+            item in "[a-z_]+",
+            mut players_data in prop::collection::hash_map(any::<u32>(), arb_player_data(), 0..10),
+        ) {
+            prop_assume!(!players_data.contains_key(&client_id));
+
+            let result = handle_item(client_id, item, &mut players_data);
+            match result {
+                Err(ParsingError::UnknownClient(id)) => prop_assert_eq!(id, client_id),
+                _ => prop_assert!(false),
+            }
+        }
+    }
+
+    proptest! {
+        #[test]
+        fn test_client_session(
+            client_id in any::<u32>(),
+            connected_at in 0..1000_u32,
+            waiting in 0..100_u32,
+            playing in 0..1000_u32,
+        ) {
+            let began_at = connected_at + waiting;
+            let disconnected_at = began_at + playing;
+            let mut players_data: HashMap<u32, PlayerData> = HashMap::new();
+            let mut departed_players: Vec<PlayerData> = Vec::new();
+
+            handle_client_connect(client_id, connected_at, &mut players_data);
+            prop_assert!(handle_client_begin(client_id, began_at, &mut players_data).is_ok());
+            // a second begin in the same session (e.g. a team change) doesn't restart it
+            prop_assert!(handle_client_begin(client_id, disconnected_at, &mut players_data).is_ok());
+            prop_assert!(handle_client_disconnect(client_id, disconnected_at, &mut players_data, &mut departed_players).is_ok());
+
+            prop_assert!(!players_data.contains_key(&client_id));
+            prop_assert_eq!(departed_players.len(), 1);
+            prop_assert_eq!(&departed_players[0].sessions, &vec![Session {
+                connected_at,
+                began_at: Some(began_at),
+                disconnected_at: Some(disconnected_at),
+            }]);
+            prop_assert_eq!(departed_players[0].time_played(), playing);
+        }
+    }
+
+    proptest! {
+        #[test]
+        fn test_client_session_unknown_client(
+            client_id in any::<u32>(),
+            time in any::<u32>(),
             mut players_data in prop::collection::hash_map(any::<u32>(), arb_player_data(), 0..10),
-            mut kills_by_means_death in prop::collection::hash_map(a_random_mean_death(), any::<u32>(), 0..10),
-            mut total_kills in any::<u32>(),
         ) {
-            let kill_line = format!("{} {} {}: {}", killer_id, victim_id, mean_id, rest);
-            let mut parts = kill_line.split_whitespace();
+            prop_assume!(!players_data.contains_key(&client_id));
+
+            match handle_client_begin(client_id, time, &mut players_data) {
+                Err(ParsingError::UnknownClient(id)) => prop_assert_eq!(id, client_id),
+                _ => prop_assert!(false),
+            }
+            match handle_client_disconnect(client_id, time, &mut players_data, &mut Vec::new()) {
+                Err(ParsingError::UnknownClient(id)) => prop_assert_eq!(id, client_id),
+                _ => prop_assert!(false),
+            }
+        }
+    }
+
+    #[test]
+    fn test_scan_file_client_id_reuse() {
+        let log_content = r"
+        0:00 InitGame: \sv_hostname\Code Miner Server
+        0:00 ClientConnect: 3
+        0:00 ClientUserinfoChanged: 3 n\Isgalamido\t\0
+        0:00 ClientBegin: 3
+        0:00 ClientConnect: 5
+        0:00 ClientUserinfoChanged: 5 n\Dono da Bola\t\0
+        0:00 ClientBegin: 5
+        1:06 Kill: 3 5 7: Isgalamido killed Dono da Bola by MOD_ROCKET_SPLASH
+        1:06 Kill: 3 5 7: Isgalamido killed Dono da Bola by MOD_ROCKET_SPLASH
+        1:30 ClientDisconnect: 3
+        1:40 ClientConnect: 3
+        1:40 ClientUserinfoChanged: 3 n\Mocinha\t\0
+        1:42 ClientBegin: 3
+        2:00 Kill: 3 5 7: Mocinha killed Dono da Bola by MOD_ROCKET_SPLASH
+        3:00 ShutdownGame:
+        ";
+
+        let games = scan_file(log_content).unwrap();
+        assert_eq!(games.len(), 1);
+
+        let game = &games[0];
+        assert_eq!(game.players_data.len(), 2);
+        assert_eq!(game.players_data.get(&3).unwrap().name, "Mocinha");
+        assert_eq!(game.players_data.get(&3).unwrap().score(&game.scoring), 1);
+        assert_eq!(game.players_data.get(&3).unwrap().time_played(), 78);
+        assert_eq!(game.players_data.get(&5).unwrap().time_played(), 180);
+        assert_eq!(game.departed_players.len(), 1);
+        assert_eq!(game.departed_players[0].name, "Isgalamido");
+        assert_eq!(game.departed_players[0].score(&game.scoring), 2);
+        assert_eq!(game.departed_players[0].time_played(), 90);
+    }
+
+    #[test]
+    fn test_players_by_identity_merges_reconnected_players() {
+        let log_content = r"
+        0:00 InitGame: \sv_hostname\Code Miner Server
+        0:00 ClientConnect: 2
+        0:00 ClientUserinfoChanged: 2 n\Isgalamido\t\0
+        0:00 ClientBegin: 2
+        0:30 Kill: 1022 2 22: <world> killed Isgalamido by MOD_TRIGGER_HURT
+        1:00 ClientDisconnect: 2
+        1:10 ClientConnect: 4
+        1:10 ClientUserinfoChanged: 4 n\Isgalamido\t\0
+        1:10 ClientBegin: 4
+        1:20 Item: 4 weapon_railgun
+        1:40 ShutdownGame:
+        ";
+
+        let games = scan_file(log_content).unwrap();
+        let players = games[0].players_by_identity();
+        assert_eq!(players.len(), 1);
+        assert_eq!(players[0].name, "Isgalamido");
+        assert_eq!(players[0].score(&ScoringRules::default()), -1);
+        assert_eq!(players[0].items.get("weapon_railgun"), Some(&1));
+        assert_eq!(players[0].sessions.len(), 2);
+        assert_eq!(players[0].time_played(), 90);
+    }
+
+    #[test]
+    fn test_players_by_identity_keeps_concurrent_clients_apart() {
+        let log_content = r"
+        0:00 InitGame: \sv_hostname\Code Miner Server\fraglimit\2
+        0:00 ClientConnect: 2
+        0:00 ClientUserinfoChanged: 2 n\UnnamedPlayer\t\0
+        0:00 ClientBegin: 2
+        0:01 ClientConnect: 3
+        0:01 ClientUserinfoChanged: 3 n\UnnamedPlayer\t\0
+        0:01 ClientBegin: 3
+        0:10 ClientUserinfoChanged: 2 n\Alice\t\0
+        0:20 ClientUserinfoChanged: 3 n\Bob\t\0
+        0:30 Kill: 2 3 7: Alice killed Bob by MOD_ROCKET_SPLASH
+        0:40 Kill: 2 3 7: Alice killed Bob by MOD_ROCKET_SPLASH
+        0:40 Exit: Fraglimit hit.
+        0:50 ShutdownGame:
+        ";
+
+        let games = scan_file(log_content).unwrap();
+        let game = &games[0];
+        let players = game.players_by_identity();
+        let names: Vec<&str> = players.iter().map(|player| player.name.as_str()).collect();
+        assert_eq!(names, vec!["Alice", "Bob"]);
+        assert_eq!(players[0].score(&game.scoring), 2);
+        assert_eq!(players[1].deaths, 2);
+        assert_eq!(game.winner().as_deref(), Some("Alice"));
+        assert_eq!(
+            game.kill_matrix_by_identity(&players),
+            vec![vec![0, 2], vec![0, 0]]
+        );
+    }
+
+    #[test]
+    fn test_players_by_identity_merges_renames_and_guids() {
+        let log_content = r"
+        0:00 InitGame: \sv_hostname\Code Miner Server
+        0:00 ClientConnect: 2
+        0:00 ClientUserinfoChanged: 2 n\Izzy\t\0
+        0:00 ClientBegin: 2
+        0:10 ClientUserinfoChanged: 2 n\Isgalamido\t\0
+        0:20 Kill: 1022 2 22: <world> killed Isgalamido by MOD_TRIGGER_HURT
+        0:30 ClientDisconnect: 2
+        0:40 ClientConnect: 3
+        0:40 ClientUserinfoChanged: 3 n\Isgalamido\t\0
+        0:40 ClientBegin: 3
+        0:50 ClientConnect: 4
+        0:50 ClientUserinfoChanged: 4 n\Zeh\t\0\cl_guid\A1B2
+        0:50 ClientBegin: 4
+        1:00 ClientDisconnect: 4
+        1:10 ClientConnect: 4
+        1:10 ClientUserinfoChanged: 4 n\Zehzinho\t\0\cl_guid\A1B2
+        1:10 ClientBegin: 4
+        1:20 ClientConnect: 5
+        1:20 ClientUserinfoChanged: 5 n\Zeh\t\0\cl_guid\FFFF
+        1:20 ClientBegin: 5
+        1:40 ShutdownGame:
+        ";
+
+        let games = scan_file(log_content).unwrap();
+        let players = games[0].players_by_identity();
+        let summary: Vec<(&str, Vec<&str>, Option<&str>)> = players
+            .iter()
+            .map(|player| {
+                (
+                    player.name.as_str(),
+                    player.aliases(),
+                    player.guid.as_deref(),
+                )
+            })
+            .collect();
+        assert_eq!(
+            summary,
+            vec![
+                ("Isgalamido", vec!["Izzy"], None),
+                ("Zeh", vec![], Some("FFFF")),
+                ("Zehzinho", vec!["Zeh"], Some("A1B2")),
+            ]
+        );
+        assert_eq!(players[0].score(&ScoringRules::default()), -1);
+        assert_eq!(players[0].sessions.len(), 2);
+        assert_eq!(players[2].sessions.len(), 2);
+    }
+
+    #[test]
+    fn test_scan_file_kill_matrix() {
+        let log_content = r"
+        0:00 InitGame: \sv_hostname\Code Miner Server
+        0:00 ClientConnect: 2
+        0:00 ClientUserinfoChanged: 2 n\Izzy\t\0
+        0:00 ClientConnect: 3
+        0:00 ClientUserinfoChanged: 3 n\Zeh\t\0
+        0:10 Kill: 2 3 7: Izzy killed Zeh by MOD_ROCKET_SPLASH
+        0:20 ClientUserinfoChanged: 2 n\Isgalamido\t\0
+        0:30 Kill: 2 3 7: Isgalamido killed Zeh by MOD_ROCKET_SPLASH
+        0:40 Kill: 2 2 7: Isgalamido killed Isgalamido by MOD_ROCKET_SPLASH
+        0:50 Kill: 3 2 10: Zeh killed Isgalamido by MOD_RAILGUN
+        1:00 Kill: 1022 3 22: <world> killed Zeh by MOD_TRIGGER_HURT
+        1:40 ShutdownGame:
+        ";
+
+        let games = scan_file(log_content).unwrap();
+        let game = &games[0];
+        // keyed by the names at kill time, world kills left out
+        let kills = |killer: &str| {
+            game.kill_matrix[killer]
+                .iter()
+                .map(|(victim, kills)| (victim.as_str(), *kills))
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(game.kill_matrix.len(), 3);
+        assert_eq!(kills("Izzy"), vec![("Zeh", 1)]);
+        assert_eq!(kills("Isgalamido"), vec![("Isgalamido", 1), ("Zeh", 1)]);
+        assert_eq!(kills("Zeh"), vec![("Isgalamido", 1)]);
+
+        // the renamed player is a single row and column by identity
+        let people = game.players_by_identity();
+        let names: Vec<&str> = people.iter().map(|player| player.name.as_str()).collect();
+        assert_eq!(names, vec!["Isgalamido", "Zeh"]);
+        assert_eq!(
+            game.kill_matrix_by_identity(&people),
+            vec![vec![1, 2], vec![1, 0]]
+        );
+    }
+
+    #[test]
+    fn test_game_winner() {
+        let mut game = Game {
+            exit_reason: ExitReason::Fraglimit,
+            ..Default::default()
+        };
+        assert_eq!(game.winner(), None);
+
+        game.players_data.insert(
+            2,
+            PlayerData {
+                name: "Isgalamido".to_owned(),
+                frags: 20,
+                ..Default::default()
+            },
+        );
+        game.players_data.insert(
+            3,
+            PlayerData {
+                name: "Mocinha".to_owned(),
+                frags: 12,
+                ..Default::default()
+            },
+        );
+        assert_eq!(game.winner(), Some("Isgalamido".to_owned()));
+        assert_eq!(game.outcome(), Outcome::Winner("Isgalamido".to_owned()));
+
+        game.exit_reason = ExitReason::Timelimit;
+        assert_eq!(game.winner(), Some("Isgalamido".to_owned()));
+
+        // capture limit only makes sense in team games
+        game.exit_reason = ExitReason::Capturelimit;
+        assert_eq!(game.winner(), None);
+        assert_eq!(game.outcome(), Outcome::NoWinner);
+
+        game.exit_reason = ExitReason::Aborted;
+        assert_eq!(game.winner(), None);
+        assert_eq!(game.outcome(), Outcome::NoWinner);
+
+        // a draw on time limit
+        game.exit_reason = ExitReason::Timelimit;
+        game.departed_players.push(PlayerData {
+            name: "Mocinha".to_owned(),
+            frags: 8,
+            ..Default::default()
+        });
+        assert_eq!(game.winner(), None);
+        assert_eq!(game.outcome(), Outcome::Draw);
+    }
+
+    #[test]
+    fn test_scan_file_exit_reason() {
+        let log_content = r"
+        0:00 InitGame: \sv_hostname\Code Miner Server
+        0:01 ClientConnect: 2
+        0:02 ClientUserinfoChanged: 2 n\Isgalamido\t\0
+        0:03 Kill: 1022 2 22: <world> killed Isgalamido by MOD_TRIGGER_HURT
+        0:04 Exit: Timelimit hit.
+        0:05 ShutdownGame:
+        0:06 InitGame: \sv_hostname\Code Miner Server
+        0:07 ClientConnect: 2
+        0:08 ClientUserinfoChanged: 2 n\Isgalamido\t\0
+        0:09 Kill: 1022 2 22: <world> killed Isgalamido by MOD_TRIGGER_HURT
+        0:10 ShutdownGame:
+        ";
 
-            let result = parse_kill(&mut parts, &mut total_kills, &mut kills_by_means_death, &mut players_data);
-            match result {
-                Err(ParsingError::ParseIntError(_)) => {},
-                _ => prop_assert!(false),
-            }
-        }
+        let games = scan_file(log_content).unwrap();
+        assert_eq!(games.len(), 2);
+        assert_eq!(games[0].exit_reason, ExitReason::Timelimit);
+        assert_eq!(games[0].winner(), Some("Isgalamido".to_owned()));
+        assert_eq!(games[1].exit_reason, ExitReason::Aborted);
+        assert_eq!(games[1].winner(), None);
     }
 
     proptest! {
         #[test]
-        fn test_parse_kill_mean_id_parseint_error(
-            killer_id in any::<u32>(),
-            victim_id in any::<u32>(),
-            mean_id in "[^\\d\\s]+", // match everything that is not a digit or a whitespace
-            rest in "\\PC*",
+        fn test_handle_score(
+            client_id in any::<u32>(),
+            score in any::<i32>(),
             mut players_data in prop::collection::hash_map(any::<u32>(), arb_player_data(), 0..10),
-            mut kills_by_means_death in prop::collection::hash_map(a_random_mean_death(), any::<u32>(), 0..10),
-            mut total_kills in any::<u32>(),
         ) {
-            let kill_line = format!("{} {} {}: {}", killer_id, victim_id, mean_id, rest);
-            let mut parts = kill_line.split_whitespace();
+            prop_assume!(!players_data.contains_key(&client_id));
 
-            let result = parse_kill(&mut parts, &mut total_kills, &mut kills_by_means_death, &mut players_data);
-            match result {
-                Err(ParsingError::ParseIntError(_)) => {},
+            match handle_score(client_id, score, &mut players_data) {
+                Err(ParsingError::UnknownClient(id)) => prop_assert_eq!(id, client_id),
                 _ => prop_assert!(false),
             }
+
+            handle_client_connect(client_id, 0, &mut players_data);
+            prop_assert!(handle_score(client_id, score, &mut players_data).is_ok());
+            prop_assert_eq!(players_data.get(&client_id).unwrap().server_score, Some(score));
         }
     }
 
-    proptest! {
-        #[test]
-        fn test_parse_kill_victim_id_parseint_error(
-            killer_id in any::<u32>(),
-            victim_id in "[^\\d\\s]+", // match everything that is not a digit or a whitespace
-            mean_id in 0..28u32,
-            rest in "\\PC*",
-            mut players_data in prop::collection::hash_map(any::<u32>(), arb_player_data(), 0..10),
-            mut kills_by_means_death in prop::collection::hash_map(a_random_mean_death(), any::<u32>(), 0..10),
-            mut total_kills in any::<u32>(),
-        ) {
-            let kill_line = format!("{} {} {}: {}", killer_id, victim_id, mean_id, rest);
-            let mut parts = kill_line.split_whitespace();
+    #[test]
+    fn test_scan_file_server_scores() {
+        let log_content = r"
+        0:00 InitGame: \sv_hostname\Code Miner Server
+        0:01 ClientConnect: 2
+        0:02 ClientUserinfoChanged: 2 n\Isgalamido\t\0
+        0:03 ClientConnect: 3
+        0:04 ClientUserinfoChanged: 3 n\Mocinha\t\0
+        0:05 Kill: 2 3 7: Isgalamido killed Mocinha by MOD_ROCKET_SPLASH
+        0:06 Kill: 3 2 7: Mocinha killed Isgalamido by MOD_ROCKET_SPLASH
+        0:07 Exit: Fraglimit hit.
+        0:07 score: 1  ping: 4  client: 2 Isgalamido
+        0:07 score: -1  ping: 0  client: 3 Mocinha
+        0:08 ShutdownGame:
+        ";
 
-            let result = parse_kill(&mut parts, &mut total_kills, &mut kills_by_means_death, &mut players_data);
-            match result {
-                Err(ParsingError::ParseIntError(_)) => {},
-                _ => prop_assert!(false),
-            }
-        }
+        let mut games = scan_file(log_content).unwrap();
+        let game = &mut games[0];
+        assert_eq!(game.players_data.get(&2).unwrap().server_score, Some(1));
+        assert_eq!(game.players_data.get(&3).unwrap().server_score, Some(-1));
+
+        assert_eq!(
+            game.score_mismatches(),
+            vec![ScoreMismatch {
+                client_id: 3,
+                name: "Mocinha".to_owned(),
+                computed: 1,
+                server: -1,
+            }]
+        );
+        assert_eq!(
+            game.score_mismatches()[0].to_string(),
+            "Mocinha (client 3) has a computed score of 1 but the server reported -1"
+        );
+        // both players have a score of 1, so it's a draw
+        assert_eq!(game.winner(), None);
+
+        game.use_server_scores();
+        assert!(game.score_mismatches().is_empty());
+        assert_eq!(game.players_data.get(&3).unwrap().score(&game.scoring), -1);
+        assert_eq!(game.winner(), Some("Isgalamido".to_owned()));
     }
 
-    proptest! {
-        #[test]
-        fn test_parse_kill_killer_id_parseint_error(
-            killer_id in "[^\\d\\s]+", // match everything that is not a digit or a whitespace
-            victim_id in any::<u32>(),
-            mean_id in 0..28u32,
-            rest in "\\PC*",
-            mut players_data in prop::collection::hash_map(any::<u32>(), arb_player_data(), 0..10),
-            mut kills_by_means_death in prop::collection::hash_map(a_random_mean_death(), any::<u32>(), 0..10),
-            mut total_kills in any::<u32>(),
-        ) {
-            let kill_line = format!("{} {} {}: {}", killer_id, victim_id, mean_id, rest);
-            let mut parts = kill_line.split_whitespace();
+    #[test]
+    fn test_score_mismatches_skip_objective_games() {
+        let log_content = r"
+        0:00 InitGame: \g_gametype\4
+        0:01 ClientConnect: 2
+        0:02 ClientUserinfoChanged: 2 n\Isgalamido\t\1
+        0:03 ClientConnect: 3
+        0:04 ClientUserinfoChanged: 3 n\Mocinha\t\2
+        0:05 Kill: 2 3 7: Isgalamido killed Mocinha by MOD_ROCKET_SPLASH
+        0:06 Exit: Capturelimit hit.
+        0:06 red:1  blue:0
+        0:07 score: 6  ping: 4  client: 2 Isgalamido
+        0:07 score: 0  ping: 0  client: 3 Mocinha
+        0:08 ShutdownGame:
+        ";
+
+        let mut games = scan_file(log_content).unwrap();
+        let game = &mut games[0];
+        assert_eq!(game.game_type(), GameType::CaptureTheFlag);
+        assert_eq!(game.players_data.get(&2).unwrap().server_score, Some(6));
+        // the flag capture is worth points the kills don't account for
+        assert!(game.score_mismatches().is_empty());
+
+        game.settings.game_type = Some(3);
+        assert_eq!(game.score_mismatches().len(), 1);
+    }
 
-            let result = parse_kill(&mut parts, &mut total_kills, &mut kills_by_means_death, &mut players_data);
-            match result {
-                Err(ParsingError::ParseIntError(_)) => {},
-                _ => prop_assert!(false),
-            }
-        }
+    #[test]
+    fn test_use_server_scores_keeps_players_without_one() {
+        let mut game = Game::default();
+        game.players_data.insert(
+            2,
+            PlayerData {
+                name: "Isgalamido".to_owned(),
+                frags: 3,
+                ..Default::default()
+            },
+        );
+        game.departed_players.push(PlayerData {
+            name: "Mocinha".to_owned(),
+            frags: 2,
+            ..Default::default()
+        });
+
+        game.use_server_scores();
+        assert!(game.score_mismatches().is_empty());
+        assert_eq!(game.players_data.get(&2).unwrap().score(&game.scoring), 3);
+        assert_eq!(game.departed_players[0].score(&game.scoring), 2);
     }
 
-    proptest! {
-        #[test]
-        fn test_parse_kill_killer_not_found_unexpected_error(
-            killer_id in any::<u32>(),
-            victim_id in any::<u32>(),
-            mean_id in 0..28u32,
-            rest in "\\PC*",
-            mut kills_by_means_death in prop::collection::hash_map(a_random_mean_death(), any::<u32>(), 0..10),
-            mut total_kills in any::<u32>(),
-        ) {
-            prop_assume!(killer_id != victim_id);
+    #[test]
+    fn test_apply_scoring() {
+        let mut game = Game::default();
+        game.players_data.insert(
+            2,
+            PlayerData {
+                name: "Isgalamido".to_owned(),
+                frags: 3,
+                deaths: 2,
+                suicides: 1,
+                ..Default::default()
+            },
+        );
+        game.departed_players.push(PlayerData {
+            name: "Mocinha".to_owned(),
+            frags: 2,
+            team_kills: 1,
+            server_score: Some(7),
+            ..Default::default()
+        });
+        assert_eq!(game.players_data.get(&2).unwrap().score(&game.scoring), 2);
+        assert_eq!(game.departed_players[0].score(&game.scoring), 1);
+
+        game.use_server_scores();
+        game.apply_scoring(ScoringRules {
+            frag: 2,
+            suicide: 0,
+            world_death: 0,
+            team_kill: -3,
+            death: -1,
+        });
+        assert_eq!(game.players_data.get(&2).unwrap().score(&game.scoring), 4);
+        assert_eq!(game.scoring.score(&game.departed_players[0]), 1);
+        assert_eq!(game.departed_players[0].score(&game.scoring), 7);
+    }
 
-            let mut players_data: HashMap<u32, PlayerData> = HashMap::new();
-            players_data.insert(victim_id, PlayerData { name: "unknown".to_owned(), kills: 1 });
-            let kill_line = format!("{} {} {}: {}", killer_id, victim_id, mean_id, rest);
-            let mut parts = kill_line.split_whitespace();
+    #[test]
+    fn test_scan_file_game_settings() {
+        let log_content = r"
+        0:00 InitGame: \sv_hostname\Code Miner Server\g_gametype\0\mapname\q3dm17
+        0:01 ClientConnect: 2
+        0:02 ClientUserinfoChanged: 2 n\Isgalamido\t\0
+        0:03 Kill: 1022 2 22: <world> killed Isgalamido by MOD_TRIGGER_HURT
+        0:04 InitGame: \sv_hostname\Code Miner Server\g_gametype\4\mapname\q3dm6\capturelimit\8
+        0:05 ClientConnect: 2
+        0:06 ClientUserinfoChanged: 2 n\Isgalamido\t\1
+        0:07 Kill: 1022 2 22: <world> killed Isgalamido by MOD_TRIGGER_HURT
+        0:08 ShutdownGame:
+        ";
 
-            let result = parse_kill(&mut parts, &mut total_kills, &mut kills_by_means_death, &mut players_data);
-            match result {
-                Err(ParsingError::UnexpectedError(_)) => {},
-                _ => prop_assert!(false),
-            }
-        }
+        let games = scan_file(log_content).unwrap();
+        assert_eq!(games.len(), 2);
+        assert_eq!(games[0].settings.map_name, Some("q3dm17".to_owned()));
+        assert_eq!(games[0].settings.game_type, Some(0));
+        assert_eq!(games[0].settings.capture_limit, None);
+        // the game closed by the next `InitGame:` doesn't take its settings
+        assert_eq!(games[1].settings.map_name, Some("q3dm6".to_owned()));
+        assert_eq!(games[1].settings.game_type, Some(4));
+        assert_eq!(games[1].settings.capture_limit, Some(8));
+        assert_eq!(
+            games[1].settings.hostname,
+            Some("Code Miner Server".to_owned())
+        );
     }
 
-    proptest! {
-        #[test]
-        fn test_parse_kill_victim_not_found_unexpected_error(
-            victim_id in any::<u32>(),
-            mean_id in 0..28u32,
-            rest in "\\PC*",
-            mut kills_by_means_death in prop::collection::hash_map(a_random_mean_death(), any::<u32>(), 0..10),
-            mut total_kills in any::<u32>(),
-        ) {
-            let killer_id = WORLD_ID;
-            prop_assume!(killer_id != victim_id);
+    #[test]
+    fn test_userinfo_value() {
+        let userinfo = r"n\Isgalamido\t\2\model\uriel/zael\g_redteam\\g_blueteam\\c1\5";
+        assert_eq!(userinfo_value(userinfo, "n"), Some("Isgalamido"));
+        assert_eq!(userinfo_value(userinfo, "t"), Some("2"));
+        assert_eq!(userinfo_value(userinfo, "g_redteam"), Some(""));
+        assert_eq!(userinfo_value(userinfo, "c1"), Some("5"));
+        assert_eq!(userinfo_value(userinfo, "hc"), None);
+        assert_eq!(userinfo_value("", "t"), None);
+    }
 
-            let mut players_data: HashMap<u32, PlayerData> = HashMap::new();
-            players_data.insert(killer_id, PlayerData { name: "unknown".to_owned(), kills: 1 });
-            let kill_line = format!("{} {} {}: {}", killer_id, victim_id, mean_id, rest);
-            let mut parts = kill_line.split_whitespace();
+    #[test]
+    fn test_scan_file_team_game() {
+        let log_content = r"
+        0:00 InitGame: \g_gametype\3\mapname\q3dm6
+        0:01 ClientConnect: 2
+        0:01 ClientUserinfoChanged: 2 n\Isgalamido\t\1\model\uriel/zael
+        0:02 ClientConnect: 3
+        0:02 ClientUserinfoChanged: 3 n\Mocinha\t\1\model\sarge
+        0:03 ClientConnect: 4
+        0:03 ClientUserinfoChanged: 4 n\Zeh\t\2\model\sarge
+        0:04 ClientConnect: 5
+        0:04 ClientUserinfoChanged: 5 n\Mal\t\3\model\sarge
+        0:05 Kill: 2 4 7: Isgalamido killed Zeh by MOD_ROCKET_SPLASH
+        0:06 Kill: 2 3 7: Isgalamido killed Mocinha by MOD_ROCKET_SPLASH
+        0:07 Kill: 4 2 10: Zeh killed Isgalamido by MOD_RAILGUN
+        0:08 Kill: 4 3 10: Zeh killed Mocinha by MOD_RAILGUN
+        0:09 Exit: Fraglimit hit.
+        0:10 ShutdownGame:
+        ";
 
-            let result = parse_kill(&mut parts, &mut total_kills, &mut kills_by_means_death, &mut players_data);
-            match result {
-                Err(ParsingError::UnexpectedError(_)) => {},
-                _ => prop_assert!(false),
-            }
+        let games = scan_file(log_content).unwrap();
+        let game = &games[0];
+        assert_eq!(game.game_type(), GameType::TeamDeathmatch);
+        assert_eq!(game.players_data.get(&2).unwrap().team, Team::Red);
+        assert_eq!(game.players_data.get(&5).unwrap().team, Team::Spectator);
+        // the team kill cancels the enemy kill
+        assert_eq!(game.players_data.get(&2).unwrap().score(&game.scoring), 0);
+        assert_eq!(game.players_data.get(&4).unwrap().score(&game.scoring), 2);
+        assert_eq!(
+            game.team_scores(),
+            BTreeMap::from([(Team::Red, 0), (Team::Blue, 2)])
+        );
+        assert_eq!(game.winner(), Some("Blue team".to_owned()));
+    }
+
+    #[test]
+    fn test_scan_file_team_scores() {
+        let log_content = r"
+        0:00 InitGame: \g_gametype\4\mapname\q3wcp16
+        0:01 ClientConnect: 2
+        0:01 ClientUserinfoChanged: 2 n\Isgalamido\t\1
+        0:02 ClientConnect: 3
+        0:02 ClientUserinfoChanged: 3 n\Zeh\t\2
+        0:03 Kill: 2 3 10: Isgalamido killed Zeh by MOD_RAILGUN
+        0:04 ClientUserinfoChanged: 2 n\Isgalamido\t\2
+        0:05 Kill: 2 3 10: Isgalamido killed Zeh by MOD_RAILGUN
+        0:06 Exit: Capturelimit hit.
+        0:06 red:3  blue:8
+        0:07 ShutdownGame:
+        ";
+
+        let games = scan_file(log_content).unwrap();
+        let game = &games[0];
+        assert_eq!(
+            game.players_data.get(&2).unwrap().team_history,
+            vec![
+                TeamChange {
+                    time: 1,
+                    team: Team::Red
+                },
+                TeamChange {
+                    time: 4,
+                    team: Team::Blue
+                },
+            ]
+        );
+        // the first kill is credited to red, the second one is a blue team kill
+        assert_eq!(
+            game.team_stats,
+            BTreeMap::from([
+                (
+                    Team::Red,
+                    TeamStats {
+                        kills: 1,
+                        team_kills: 0
+                    }
+                ),
+                (
+                    Team::Blue,
+                    TeamStats {
+                        kills: 0,
+                        team_kills: 1
+                    }
+                ),
+            ])
+        );
+        assert_eq!(
+            game.server_team_scores,
+            Some(BTreeMap::from([(Team::Red, 3), (Team::Blue, 8)]))
+        );
+        assert_eq!(
+            game.final_team_scores(),
+            game.server_team_scores.clone().unwrap()
+        );
+        assert_eq!(game.winner(), Some("Blue team".to_owned()));
+    }
+
+    #[test]
+    fn test_team_kill_outside_team_game_is_a_kill() {
+        let mut players_data: HashMap<u32, PlayerData> = HashMap::new();
+        for client_id in [2, 3] {
+            players_data.insert(
+                client_id,
+                PlayerData {
+                    team: Team::Red,
+                    ..Default::default()
+                },
+            );
         }
+        let mut total_kills = 0;
+        let mut kills_by_means_death: HashMap<MeanDeath, u32> = HashMap::new();
+
+        handle_kill(
+            2,
+            3,
+            MeanDeath::Railgun,
+            GameType::FreeForAll,
+            &mut total_kills,
+            &mut kills_by_means_death,
+            &mut players_data,
+        )
+        .unwrap();
+        assert_eq!(
+            players_data
+                .get(&2)
+                .unwrap()
+                .score(&ScoringRules::default()),
+            1
+        );
+
+        handle_kill(
+            2,
+            3,
+            MeanDeath::Railgun,
+            GameType::CaptureTheFlag,
+            &mut total_kills,
+            &mut kills_by_means_death,
+            &mut players_data,
+        )
+        .unwrap();
+        assert_eq!(
+            players_data
+                .get(&2)
+                .unwrap()
+                .score(&ScoringRules::default()),
+            0
+        );
+        assert_eq!(total_kills, 2);
     }
 
     #[test]
     fn test_scan_file() {
-        let log_content = r#"
+        let log_content = r"
         0:00 ------------------------------------------------------------
         0:00 InitGame: \sv_floodProtect\1\sv_maxPing\0\sv_minPing\0\sv_maxRate\10000\sv_minRate\0\sv_hostname\Code Miner Server\g_gametype\0\sv_privateClients\2\sv_maxclients\16\sv_allowDownload\0\bot_minplayers\0\dmflags\0\fraglimit\20\timelimit\15\g_maxGameClients\0\capturelimit\8\version\ioq3 1.36 linux-x86_64 Apr 12 2009\protocol\68\mapname\q3dm17\gamename\baseq3\g_needpass\0
         0:01 ClientConnect: 2
         0:02 ClientUserinfoChanged: 2 n\Isgalamido\t\0\model\uriel/zael\hmodel\uriel/zael\g_redteam\\g_blueteam\\c1\5\c2\5\hc\100\w\0\l\0\tt\0\tl\0
         0:03 ClientConnect: 3
         0:04 ClientUserinfoChanged: 3 n\Mocinha\t\0\model\sarge\hmodel\sarge\g_redteam\\g_blueteam\\c1\4\c2\5\hc\95\w\0\l\0\tt\0\tl\0
+        0:05 Item: 2 weapon_rocketlauncher
         0:05 Kill: 2 3 7: Isgalamido killed Mocinha by MOD_ROCKET_SPLASH
+        0:06 Item: 3 item_armor_body
+        0:06 Item: 3 item_armor_body
         0:06 Kill: 3 2 7: Mocinha killed Isgalamido by MOD_ROCKET_SPLASH
         0:07 ShutdownGame:
         0:07 ------------------------------------------------------------
@@ -624,7 +2352,7 @@ mod tests {
         0:12 Kill: 1022 2 22: <world> killed Isgalamido by MOD_TRIGGER_HURT
         0:13 ShutdownGame:
         0:14 ------------------------------------------------------------
-        "#;
+        ";
 
         let games = scan_file(log_content).unwrap();
         assert_eq!(games.len(), 2);
@@ -641,9 +2369,27 @@ mod tests {
         );
         assert_eq!(game0.players_data.len(), 2);
         assert_eq!(game0.players_data.get(&2).unwrap().name, "Isgalamido");
-        assert_eq!(game0.players_data.get(&2).unwrap().kills, 1);
+        assert_eq!(game0.players_data.get(&2).unwrap().score(&game0.scoring), 1);
         assert_eq!(game0.players_data.get(&3).unwrap().name, "Mocinha");
-        assert_eq!(game0.players_data.get(&3).unwrap().kills, 1);
+        assert_eq!(game0.players_data.get(&3).unwrap().score(&game0.scoring), 1);
+        assert_eq!(
+            game0
+                .players_data
+                .get(&2)
+                .unwrap()
+                .items
+                .get("weapon_rocketlauncher"),
+            Some(&1)
+        );
+        assert_eq!(
+            game0
+                .players_data
+                .get(&3)
+                .unwrap()
+                .items
+                .get("item_armor_body"),
+            Some(&2)
+        );
 
         let game1 = &games[1];
         assert_eq!(game1.total_kills, 2);
@@ -657,7 +2403,13 @@ mod tests {
         );
         assert_eq!(game1.players_data.len(), 1);
         assert_eq!(game1.players_data.get(&2).unwrap().name, "Isgalamido");
-        assert_eq!(game1.players_data.get(&2).unwrap().kills, 0);
+        // a suicide and a world death, each costs a point
+        let player = game1.players_data.get(&2).unwrap();
+        assert_eq!(player.frags, 0);
+        assert_eq!(player.deaths, 2);
+        assert_eq!(player.suicides, 1);
+        assert_eq!(player.world_deaths, 1);
+        assert_eq!(player.score(&ScoringRules::default()), -2);
     }
 
     proptest! {
@@ -669,7 +2421,7 @@ mod tests {
             mean_id in 0..28u32,
         ) {
             let log_content = format!(
-                r#"
+                r"
                 0:00 ------------------------------------------------------------
                 0:00 InitGame: {whatever}
                 0:01 ClientConnect: {player1_id}
@@ -680,7 +2432,7 @@ mod tests {
                 0:06 Kill: {player2_id} {player1_id} {mean_id}: {whatever}
                 0:07 ShutdownGame:
                 0:07 ------------------------------------------------------------
-                "#,
+                ",
             );
 
             let games = scan_file(&log_content).unwrap();
@@ -692,15 +2444,15 @@ mod tests {
             assert_eq!(game0.kills_by_means_death.get(&MeanDeath::from(mean_id)).unwrap(), &2);
             assert_eq!(game0.players_data.len(), 2);
             assert_eq!(game0.players_data.get(&player1_id).unwrap().name, "Isgalamido");
-            assert_eq!(game0.players_data.get(&player1_id).unwrap().kills, 1);
+            assert_eq!(game0.players_data.get(&player1_id).unwrap().score(&game0.scoring), 1);
             assert_eq!(game0.players_data.get(&player2_id).unwrap().name, "Mocinha");
-            assert_eq!(game0.players_data.get(&player2_id).unwrap().kills, 1);
+            assert_eq!(game0.players_data.get(&player2_id).unwrap().score(&game0.scoring), 1);
         }
     }
 
     #[test]
     fn test_buggy_scan_file() {
-        let log_content = r#"
+        let log_content = r"
         0:00 ------------------------------------------------------------
         0:00 InitGame: \sv_floodProtect\1\sv_maxPing\0\sv_minPing\0\sv_maxRate\10000\sv_minRate\0\sv_hostname\Code Miner Server\g_gametype\0\sv_privateClients\2\sv_maxclients\16\sv_allowDownload\0\bot_minplayers\0\dmflags\0\fraglimit\20\timelimit\15\g_maxGameClients\0\capturelimit\8\version\ioq3 1.36 linux-x86_64 Apr 12 2009\protocol\68\mapname\q3dm17\gamename\baseq3\g_needpass\0
         0:01 ClientConnect: 2
@@ -716,7 +2468,7 @@ mod tests {
         0:11 Kill: 2 2 22: Isgalamido killed Isgalamido by MOD_TRIGGER_HURT
         0:12 ShutdownGame:
         0:13 ------------------------------------------------------------
-        "#;
+        ";
 
         let games = scan_file(log_content).unwrap();
         assert_eq!(games.len(), 2);
@@ -733,9 +2485,9 @@ mod tests {
         );
         assert_eq!(game0.players_data.len(), 2);
         assert_eq!(game0.players_data.get(&2).unwrap().name, "Dono da bola");
-        assert_eq!(game0.players_data.get(&2).unwrap().kills, 1);
+        assert_eq!(game0.players_data.get(&2).unwrap().score(&game0.scoring), 1);
         assert_eq!(game0.players_data.get(&3).unwrap().name, "Mocinha");
-        assert_eq!(game0.players_data.get(&3).unwrap().kills, 1);
+        assert_eq!(game0.players_data.get(&3).unwrap().score(&game0.scoring), 1);
 
         let game1 = &games[1];
         assert_eq!(game1.total_kills, 1);
@@ -749,7 +2501,564 @@ mod tests {
         );
         assert_eq!(game1.players_data.len(), 1);
         assert_eq!(game1.players_data.get(&2).unwrap().name, "Isgalamido");
-        assert_eq!(game1.players_data.get(&2).unwrap().kills, 1);
+        assert_eq!(
+            game1.players_data.get(&2).unwrap().score(&game1.scoring),
+            -1
+        );
+    }
+
+    #[test]
+    fn test_finish_game_and_set_new_game() {
+        let mut state = GameState {
+            total_kills: 1,
+            ..Default::default()
+        };
+        state.kills_by_means_death.insert(MeanDeath::Rocket, 1);
+        state.players_data.insert(
+            2,
+            PlayerData {
+                name: "Isgalamido".to_owned(),
+                frags: 1,
+                ..Default::default()
+            },
+        );
+
+        let game = finish_game_and_set_new_game(&mut state, 0, None);
+        assert_eq!(game.incomplete, None);
+        assert_eq!(game.total_kills, 1);
+        assert_eq!(game.kills_by_means_death.get(&MeanDeath::Rocket), Some(&1));
+        assert_eq!(game.players_data.get(&2).unwrap().name, "Isgalamido");
+
+        assert_eq!(state.total_kills, 0);
+        assert!(state.kills_by_means_death.is_empty());
+        assert!(state.players_data.is_empty());
+    }
+
+    #[test]
+    fn test_scan_file_incomplete_games() {
+        let log_content = concat!(
+            "  0:00 InitGame: \\mapname\\q3dm17\n",
+            "  0:01 ClientConnect: 2\n",
+            "  0:02 ClientUserinfoChanged: 2 n\\Isgalamido\\t\\0\n",
+            "  0:00 InitGame: \\mapname\\q3dm6\n",
+            "  0:01 ClientConnect: 3\n",
+            "  0:02 ClientUserinfoChanged: 3 n\\Mocinha\\t\\0\n",
+            "  0:03 Kill: 1022 3 22: <world> killed Mocinha by MOD_TRIGGER_HURT\n",
+            "  0:04 ShutdownGame:\n",
+            "  0:00 InitGame: \\mapname\\q3dm17\n",
+            "  0:01 ClientConnect: 2\n",
+            "  0:05 ClientUserinfoChanged: 2 n\\Isgalamido\\t\\0\n",
+        );
+
+        let games = scan_file(log_content).unwrap();
+        assert_eq!(games.len(), 3);
+
+        // the kill-less crashed game is kept, and not glued to the next one
+        assert_eq!(games[0].incomplete, Some(IncompleteReason::NewGameStarted));
+        assert_eq!(games[0].settings.map_name.as_deref(), Some("q3dm17"));
+        assert_eq!(games[0].players_data.len(), 1);
+        assert_eq!(
+            games[0].players_data[&2].sessions[0].disconnected_at,
+            Some(2)
+        );
+        assert_eq!(games[0].ended_at, 2);
+
+        assert_eq!(games[1].incomplete, None);
+        assert_eq!(games[1].settings.map_name.as_deref(), Some("q3dm6"));
+        assert_eq!(games[1].players_data.len(), 1);
+        assert_eq!(games[1].total_kills, 1);
+
+        // the sessions of a cut log are closed at the last event
+        assert_eq!(games[2].incomplete, Some(IncompleteReason::LogEnded));
+        assert_eq!(
+            games[2].players_data[&2].sessions[0].disconnected_at,
+            Some(5)
+        );
+
+        assert!(scan_file("  0:00 ShutdownGame:\n  0:00 -----\n")
+            .unwrap()
+            .iter()
+            .all(|game| game.incomplete.is_none()));
+    }
+
+    #[test]
+    fn test_scan_file_game_times() {
+        let log_content = concat!(
+            " 98:00 InitGame: \\mapname\\q3dm17\n",
+            " 98:30 ClientConnect: 2\n",
+            " 99:15 Kill: 1022 2 22: <world> killed Isgalamido by MOD_TRIGGER_HURT\n",
+            "101:40 Kill: 1022 2 22: <world> killed Isgalamido by MOD_TRIGGER_HURT\n",
+            "102:00 ShutdownGame:\n",
+            "102:00 InitGame: \\mapname\\q3dm6\n",
+            "110:00 ShutdownGame:\n",
+            "  0:00 InitGame: \\mapname\\q3dm17\n",
+            "  0:45 ClientConnect: 2\n",
+        );
+
+        let games = scan_file(log_content).unwrap();
+        assert_eq!(games.len(), 3);
+
+        assert_eq!(games[0].started_at, 5880);
+        assert_eq!(games[0].ended_at, 6120);
+        assert_eq!(games[0].duration(), 240);
+        assert_eq!(games[0].first_kill_at, Some(5955));
+        assert_eq!(games[0].time_to_first_kill(), Some(75));
+        assert!(!games[0].clock_reset);
+
+        assert_eq!(games[1].duration(), 480);
+        assert_eq!(games[1].time_to_first_kill(), None);
+        assert!(!games[1].clock_reset);
+
+        // the server restarted, and the log was cut during the game
+        assert!(games[2].clock_reset);
+        assert_eq!(games[2].started_at, 0);
+        assert_eq!(games[2].ended_at, 45);
+        assert_eq!(games[2].duration(), 45);
+    }
+
+    #[test]
+    fn test_scan_file_dated_log() {
+        let log_content = concat!(
+            "[2024-05-01 20:30:00] InitGame: \\mapname\\q3dm17\n",
+            "[2024-05-01 20:30:30] ClientConnect: 2\n",
+            "[2024-05-01 20:31:15] Kill: 1022 2 22: <world> killed Isgalamido by MOD_TRIGGER_HURT\n",
+            "[2024-05-01 20:34:00] ShutdownGame:\n",
+            "[2024-05-01 20:35:00] InitGame: \\mapname\\q3dm6\n",
+            "[2024-05-01 20:37:12] ClientConnect: 2\n",
+        );
+
+        let games = scan_file(log_content).unwrap();
+        assert_eq!(games.len(), 2);
+
+        // the log clock starts at the first line
+        assert_eq!(games[0].started_at, 0);
+        assert_eq!(games[0].duration(), 240);
+        assert_eq!(games[0].time_to_first_kill(), Some(75));
+        assert_eq!(games[0].started_at_utc, Some(1_714_595_400));
+        assert_eq!(games[0].ended_at_utc, Some(1_714_595_640));
+
+        assert_eq!(games[1].started_at, 300);
+        assert_eq!(games[1].duration(), 132);
+        assert_eq!(games[1].ended_at_utc, Some(1_714_595_832));
+        assert_eq!(games[1].incomplete, Some(IncompleteReason::LogEnded));
+    }
+
+    #[test]
+    fn test_scan_file_dated_log_with_clock() {
+        let log_content = concat!(
+            "1714595400  98:00 InitGame: \\mapname\\q3dm17\n",
+            "1714595430  98:30 ClientConnect: 2\n",
+            "1714595475  99:15 Kill: 1022 2 22: <world> killed Isgalamido by MOD_TRIGGER_HURT\n",
+            "1714595640 102:00 ShutdownGame:\n",
+            "  0:00 InitGame: \\mapname\\q3dm17\n",
+        );
+
+        let games = scan_file(log_content).unwrap();
+        // the line without a date is skipped, the log has dates
+        assert_eq!(games.len(), 1);
+        // the `MM:SS` times stay the log clock, next to the dates
+        assert_eq!(games[0].started_at, 5880);
+        assert_eq!(games[0].ended_at, 6120);
+        assert_eq!(games[0].started_at_utc, Some(1_714_595_400));
+        assert_eq!(games[0].ended_at_utc, Some(1_714_595_640));
+    }
+
+    #[test]
+    fn test_scan_reader_yields_games_one_at_a_time() {
+        let first_game = concat!(
+            "  0:00 InitGame: \\sv_hostname\\Code Miner Server\n",
+            "  0:01 ClientConnect: 2\n",
+            "  0:02 ClientUserinfoChanged: 2 n\\Isgalamido\\t\\0\n",
+            "  0:03 Kill: 1022 2 22: <world> killed Isgalamido by MOD_TRIGGER_HURT\n",
+            "  0:04 ShutdownGame:\n",
+        );
+        // the second game is not valid utf-8, so reading it fails
+        let log_content = [first_game.as_bytes(), b"  0:05 InitGame: \xff\n"].concat();
+
+        let mut stream = scan_reader(
+            log_content.as_slice(),
+            ParseMode::Strict,
+            TimestampFormat::Auto,
+        );
+        let game = stream.next().unwrap().unwrap();
+        assert_eq!(game.total_kills, 1);
+        assert_eq!(game.players_data.get(&2).unwrap().name, "Isgalamido");
+        assert_eq!(game.players_data.get(&2).unwrap().score(&game.scoring), -1);
+
+        match stream.next() {
+            Some(Err(ParsingError::AtLine(line_error))) => {
+                assert_eq!(
+                    line_error.error,
+                    ParsingError::IoError(std::io::ErrorKind::InvalidData)
+                );
+                assert_eq!(line_error.location.line_number, 6);
+                assert_eq!(line_error.location.byte_span(), 229..230);
+                assert_eq!(line_error.location.line, "  0:05 InitGame: \u{fffd}");
+            }
+            other => panic!("Expected IoError, got {other:?}"),
+        }
+        assert!(stream.next().is_none());
+    }
+
+    #[test]
+    fn test_scan_file_lenient_skips_bad_lines() {
+        let log_content = concat!(
+            "  0:00 InitGame: \\sv_hostname\\Code Miner Server\n",
+            "  0:01 ClientConnect: 2\n",
+            "  0:02 ClientUserinfoChanged: 2 n\\Isgalamido\\t\\0\n",
+            "  0:03 Kill: 1022 2\n",
+            "  0:04 Kill: 1022 2 22: <world> killed Isgalamido by MOD_TRIGGER_HURT\n",
+            "  0:05 Item: 7 weapon_rocketlauncher\n",
+            "  0:06 ShutdownGame:\n",
+            "  0:07 InitGame: \\sv_hostname\\Code Miner Server\n",
+            "  0:08 ClientConnect: two\n",
+            "  0:09 ShutdownGame:\n",
+        );
+
+        let parsed = scan_file_lenient(log_content);
+        assert_eq!(parsed.games.len(), 2);
+        assert_eq!(parsed.games[0].total_kills, 1);
+        assert_eq!(
+            parsed.games[0]
+                .players_data
+                .get(&2)
+                .unwrap()
+                .score(&parsed.games[0].scoring),
+            -1
+        );
+        assert!(parsed.games[1].players_data.is_empty());
+        assert_eq!(
+            parsed
+                .diagnostics
+                .iter()
+                .map(ParsingError::inner)
+                .collect::<Vec<_>>(),
+            vec![
+                &ParsingError::LogPartNotFound("mean_id".to_owned()),
+                &ParsingError::UnknownClient(7),
+                &ParsingError::ParseIntError("two".parse::<u32>().unwrap_err()),
+            ]
+        );
+
+        let locations = parsed
+            .diagnostics
+            .iter()
+            .map(|diagnostic| match diagnostic {
+                ParsingError::AtLine(line_error) => (
+                    line_error.location.line_number,
+                    line_error.location.span.clone(),
+                ),
+                other => panic!("Expected a located error, got {other:?}"),
+            })
+            .collect::<Vec<_>>();
+        // the unknown client is pointed at, not the whole line
+        assert_eq!(locations, vec![(4, 19..19), (6, 13..14), (9, 22..25)]);
+
+        match scan_file(log_content) {
+            Err(err) => assert_eq!(
+                err.inner(),
+                &ParsingError::LogPartNotFound("mean_id".to_owned())
+            ),
+            other => panic!("Expected LogPartNotFound, got {other:?}"),
+        }
+    }
+
+    #[test]
+    fn test_scan_file_lenient_skipped_kill_leaves_game_unchanged() {
+        let log_content = concat!(
+            "  0:00 InitGame: \\g_gametype\\3\\mapname\\q3dm17\n",
+            "  0:01 ClientConnect: 2\n",
+            "  0:02 ClientUserinfoChanged: 2 n\\Isgalamido\\t\\1\n",
+            "  0:03 Kill: 7 2 10: Mocinha killed Isgalamido by MOD_RAILGUN\n",
+            "  0:04 Kill: 2 9 10: Isgalamido killed Zeh by MOD_RAILGUN\n",
+            "  0:05 ShutdownGame:\n",
+        );
+
+        let parsed = scan_file_lenient(log_content);
+        // the unknown killer, then the unknown victim
+        let spans = parsed
+            .diagnostics
+            .iter()
+            .map(|diagnostic| match diagnostic {
+                ParsingError::AtLine(line_error) => line_error.location.span.clone(),
+                other => panic!("Expected a located error, got {other:?}"),
+            })
+            .collect::<Vec<_>>();
+        assert_eq!(spans, vec![13..14, 15..16]);
+        let game = &parsed.games[0];
+        assert_eq!(game.total_kills, 0);
+        assert!(game.kills_by_means_death.is_empty());
+        assert!(game.team_stats.is_empty());
+        assert!(game.kill_matrix.is_empty());
+        assert_eq!(game.first_kill_at, None);
+        let player = &game.players_data[&2];
+        assert_eq!(
+            (
+                player.frags,
+                player.deaths,
+                player.team_kills,
+                player.kills_by_means.len()
+            ),
+            (0, 0, 0, 0)
+        );
+    }
+
+    #[test]
+    fn test_parse_log_follows_mode() {
+        // the user info line is not valid utf-8, so reading it fails
+        let log_content = [
+            b"  0:00 InitGame: \\sv_hostname\\Code Miner Server\n".as_slice(),
+            b"  0:01 ClientConnect: 2\n",
+            b"  0:02 ClientUserinfoChanged: 2 n\\\xff\n",
+            b"  0:03 Kill: 1022 2 22: <world> killed Isgalamido by MOD_TRIGGER_HURT\n",
+            b"  0:04 ShutdownGame:\n",
+        ]
+        .concat();
+
+        let parsed = scan_reader(
+            log_content.as_slice(),
+            ParseMode::Lenient,
+            TimestampFormat::Auto,
+        )
+        .parse_log()
+        .unwrap();
+        assert_eq!(parsed.games.len(), 1);
+        assert_eq!(parsed.games[0].total_kills, 1);
+        assert_eq!(parsed.diagnostics.len(), 1);
+        assert_eq!(
+            parsed.diagnostics[0].inner(),
+            &ParsingError::IoError(std::io::ErrorKind::InvalidData)
+        );
+
+        match scan_reader(
+            log_content.as_slice(),
+            ParseMode::Strict,
+            TimestampFormat::Auto,
+        )
+        .parse_log()
+        {
+            Err(err) => assert_eq!(
+                err.inner(),
+                &ParsingError::IoError(std::io::ErrorKind::InvalidData)
+            ),
+            other => panic!("Expected IoError, got {other:?}"),
+        }
+    }
+
+    /// The example log of a real server, to build truncated logs from
+    const REAL_LOG: &str = include_str!("../../examples/qgames.log");
+
+    /// Returns the lines `from..to` of the real log, a truncated copy of it
+    fn truncated_real_log(from: usize, to: usize) -> String {
+        REAL_LOG
+            .lines()
+            .skip(from)
+            .take(to.saturating_sub(from))
+            .flat_map(|line| [line, "\n"])
+            .collect()
+    }
+
+    #[test]
+    fn test_scan_truncated_log_starting_mid_game() {
+        // starts at the rename of client 2, whose `ClientConnect` was cut
+        // and ends at the `ShutdownGame` of the game
+        let log_content = truncated_real_log(100, 156);
+        assert!(log_content.starts_with("  0:27 ClientUserinfoChanged: 2 n\\Mocinha"));
+
+        let parsed = scan_file_lenient(&log_content);
+        assert_eq!(parsed.games.len(), 1);
+        assert!(parsed.diagnostics.is_empty());
+        let game = &parsed.games[0];
+        assert_eq!(game.total_kills, 4);
+        let player = &game.players_data[&2];
+        assert_eq!(player.name, "Dono da Bola");
+        assert_eq!(player.name_history, vec!["Mocinha", "Dono da Bola"]);
+        assert_eq!(player.sessions[0].connected_at, 27);
+
+        match scan_file(&log_content) {
+            Err(ParsingError::AtLine(line_error)) => {
+                assert_eq!(line_error.error, ParsingError::UnknownClient(2));
+                assert_eq!(line_error.location.line_number, 1);
+                assert_eq!(line_error.location.span, 30..31);
+            }
+            other => panic!("Expected UnknownClient, got {other:?}"),
+        }
+    }
+
+    #[test]
+    fn test_scan_truncated_log_cut_mid_line() {
+        // the first line lost its start, so it has no timestamp
+        let log_content = truncated_real_log(122, 156);
+        let log_content = log_content.get(13..).unwrap();
+        assert!(log_content.starts_with("UserinfoChanged: 4"));
+
+        let parsed = scan_file_lenient(log_content);
+        assert_eq!(parsed.games.len(), 1);
+        let game = &parsed.games[0];
+        assert_eq!(game.players_data[&4].name, "Zeh");
+        // the kills and items of the players seen before the cut are skipped
+        assert!(!parsed.diagnostics.is_empty());
+        assert!(parsed
+            .diagnostics
+            .iter()
+            .all(|diagnostic| matches!(diagnostic.inner(), ParsingError::UnknownClient(_))));
+    }
+
+    proptest! {
+        #![proptest_config(ProptestConfig::with_cases(32))]
+        #[test]
+        fn test_scan_truncated_real_log_never_panics(
+            from in 0..5306_usize,
+            length in 0..400_usize,
+        ) {
+            let log_content = truncated_real_log(from, from.saturating_add(length));
+            let parsed = scan_file_lenient(&log_content);
+            prop_assert!(parsed.games.len() <= 21);
+            if scan_file(&log_content).is_ok() {
+                prop_assert!(parsed.diagnostics.is_empty());
+            }
+        }
+    }
+
+    /// Parses the real log in two runs, the first one stopping at the byte `cut`
+    /// returns the games of both runs and the final checkpoint
+    fn resume_real_log_at(cut: usize) -> (Vec<Game>, ParseCheckpoint) {
+        let log = REAL_LOG.as_bytes();
+        let mut first_run = resume_reader(
+            log.get(..cut).unwrap(),
+            ParseMode::Strict,
+            ParseCheckpoint::new(TimestampFormat::Auto),
+        );
+        let mut games = first_run.parse_available().unwrap().games;
+        let checkpoint = first_run.checkpoint();
+        let rest = log.get(checkpoint.position.offset..).unwrap();
+        let mut second_run = resume_reader(rest, ParseMode::Strict, checkpoint);
+        games.extend(second_run.parse_available().unwrap().games);
+        (games, second_run.checkpoint())
+    }
+
+    proptest! {
+        #![proptest_config(ProptestConfig::with_cases(16))]
+        #[test]
+        fn test_resume_reader_matches_scan_file(cut in 0..REAL_LOG.len()) {
+            let (resumed, checkpoint) = resume_real_log_at(cut);
+            let scanned: Vec<Game> = scan_file(REAL_LOG)
+                .unwrap()
+                .into_iter()
+                .filter(|game| game.incomplete != Some(IncompleteReason::LogEnded))
+                .collect();
+            prop_assert_eq!(
+                serde_json::to_value(&resumed).unwrap(),
+                serde_json::to_value(&scanned).unwrap()
+            );
+            prop_assert_eq!(checkpoint.position.offset, REAL_LOG.len());
+            prop_assert_eq!(checkpoint.position.line_number, REAL_LOG.lines().count());
+        }
+    }
+
+    #[test]
+    fn test_checkpoint_keeps_running_game() {
+        let log_content = "  0:00 InitGame: \\mapname\\q3dm17\n\
+            0:01 ClientConnect: 2\n\
+            0:02 ClientUserinfoChanged: 2 n\\Isgalamido\\t\\0\n\
+            0:10 Kill: 1022 2 22: <world> killed Isgalamido by MOD_TRIGGER_HURT\n";
+
+        let mut stream = resume_reader(
+            log_content.as_bytes(),
+            ParseMode::Strict,
+            ParseCheckpoint::new(TimestampFormat::Auto),
+        );
+        assert!(stream.parse_available().unwrap().games.is_empty());
+        let checkpoint = stream.checkpoint();
+        assert!(checkpoint.has_running_game());
+        assert_eq!(checkpoint.position.line_number, 4);
+
+        // the checkpoint survives a round trip through its json
+        let saved = serde_json::to_string(&checkpoint).unwrap();
+        let checkpoint: ParseCheckpoint = serde_json::from_str(&saved).unwrap();
+        let appended = "  0:20 ShutdownGame:\n";
+        let games: Vec<Game> = resume_reader(appended.as_bytes(), ParseMode::Strict, checkpoint)
+            .collect::<Result<_, _>>()
+            .unwrap();
+        assert_eq!(games.len(), 1);
+        assert_eq!(games[0].incomplete, None);
+        assert_eq!(games[0].total_kills, 1);
+        assert_eq!(games[0].players_data[&2].world_deaths, 1);
+        assert_eq!(games[0].ended_at, 20);
+    }
+
+    #[test]
+    fn test_kill_feed() {
+        let log_content = r"
+        0:00 InitGame: \mapname\q3dm17
+        0:00 ClientConnect: 2
+        0:00 ClientUserinfoChanged: 2 n\Izzy\t\0
+        0:00 ClientConnect: 3
+        0:00 ClientUserinfoChanged: 3 n\Zeh\t\0
+        0:10 Kill: 2 3 10: Izzy killed Zeh by MOD_RAILGUN
+        0:20 ClientUserinfoChanged: 2 n\Isgalamido\t\0
+        1:05 Kill: 1022 2 22: <world> killed Isgalamido by MOD_TRIGGER_HURT
+        1:10 ShutdownGame:
+        ";
+
+        let mut stream = scan_reader(
+            log_content.as_bytes(),
+            ParseMode::Strict,
+            TimestampFormat::Auto,
+        );
+        assert!(stream.next().unwrap().is_ok());
+        assert!(stream.take_kill_feed().is_empty());
+
+        let mut stream = scan_reader(
+            log_content.as_bytes(),
+            ParseMode::Strict,
+            TimestampFormat::Auto,
+        )
+        .with_kill_feed();
+        assert!(stream.next().unwrap().is_ok());
+        let kill_feed: Vec<String> = stream
+            .take_kill_feed()
+            .iter()
+            .map(ToString::to_string)
+            .collect();
+        assert_eq!(
+            kill_feed,
+            vec![
+                "00:10 Izzy killed Zeh by Railgun",
+                "01:05 <world> killed Isgalamido by TriggerHurt",
+            ]
+        );
+        assert!(stream.take_kill_feed().is_empty());
+    }
+
+    proptest! {
+        #[test]
+        fn test_scan_reader_matches_scan_file(
+            player1_id in any::<u32>(),
+            player2_id in any::<u32>(),
+            mean_id in 0..28_u32,
+            games_count in 0..5_usize,
+        ) {
+            let game = format!(
+                "0:00 InitGame: \\sv_hostname\\Code Miner Server\n\
+                0:01 ClientConnect: {player1_id}\n\
+                0:02 ClientUserinfoChanged: {player1_id} n\\Isgalamido\\t\\0\n\
+                0:03 ClientConnect: {player2_id}\n\
+                0:04 ClientUserinfoChanged: {player2_id} n\\Mocinha\\t\\0\n\
+                0:05 Kill: {player1_id} {player2_id} {mean_id}: Isgalamido killed Mocinha\n\
+                0:06 ShutdownGame:\n"
+            );
+            let log_content = game.repeat(games_count);
+
+            let streamed: Vec<Game> = scan_reader(std::io::Cursor::new(&log_content), ParseMode::Strict, TimestampFormat::Auto)
+                .collect::<Result<_, _>>()
+                .unwrap();
+            prop_assert_eq!(streamed.len(), games_count);
+            for game in &streamed {
+                prop_assert_eq!(game.total_kills, 1);
+                prop_assert_eq!(game.players_data.get(&player1_id).unwrap().name.as_str(), "Isgalamido");
+            }
+        }
     }
 
     proptest! {
@@ -759,16 +3068,16 @@ mod tests {
             whatever in "\\PC*",
         ) {
             let log_content = format!(
-                r#"
+                "
                 0:00 ------------------------------------------------------------
                 0:00 InitGame: {whatever}
                 0:01 ClientConnect: 2
                 0:02 {event}
-                "#,
+                ",
             );
 
             let result = scan_file(&log_content);
-            match result {
+            match result.as_ref().map_err(ParsingError::inner) {
                 Err(ParsingError::LogPartNotFound(_)) => {},
                 _ => prop_assert!(false),
             }
@@ -0,0 +1,432 @@
+use super::errors::ParsingError;
+use clap::ValueEnum;
+use serde::{Deserialize, Serialize};
+
+/// The seconds in a day
+const SECONDS_PER_DAY: i64 = 86_400;
+
+#[derive(Clone, Copy, Debug, Default, ValueEnum, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+/// The timestamp prefixes a log line can start with:
+/// - The time since the server started (`20:37`)
+/// - An ISO-8601 date and time (`2024-05-01T20:37:12Z`, `[2024-05-01 20:37:12]`)
+/// - Unix epoch seconds (`1714595832`)
+///
+/// The date and epoch prefixes can be followed by the `MM:SS` time of the classic logs
+pub enum TimestampFormat {
+    #[default]
+    /// Detected from the first line with a timestamp, then kept for the whole log
+    Auto,
+    /// The classic `MM:SS` time since the server started
+    Relative,
+    /// An ISO-8601 date and time, in UTC unless it has an offset
+    Iso8601,
+    /// Unix epoch seconds, the fraction of a second is dropped
+    Epoch,
+}
+
+impl TimestampFormat {
+    /// Returns the format of a line starting with `token`
+    /// `None` if the token isn't a timestamp
+    pub fn detect(token: &str) -> Option<Self> {
+        [Self::Iso8601, Self::Epoch, Self::Relative]
+            .into_iter()
+            .find(|format| format.matches(token))
+    }
+
+    /// Returns whether a line starting with `token` has a timestamp of this format
+    /// only the shape of the token is checked, its values are checked when parsed
+    pub fn matches(self, token: &str) -> bool {
+        let token = token.trim_start_matches('[');
+        match self {
+            Self::Auto => Self::detect(token).is_some(),
+            Self::Relative => is_clock(token),
+            Self::Iso8601 => is_date(token),
+            Self::Epoch => is_epoch(token.trim_end_matches(']')),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+/// The time of a log line, at least one of the two is set
+pub struct Timestamp {
+    /// The seconds since the server started, from the `MM:SS` prefix
+    pub clock: Option<u32>,
+    /// The unix seconds, from the date or epoch prefix
+    pub utc: Option<i64>,
+}
+
+/// Returns whether the token looks like a `MM:SS` time
+/// the values are checked by `parse_clock`
+pub fn is_clock(token: &str) -> bool {
+    token.len() >= 4 && token.chars().all(|c| c.is_ascii_digit() || c == ':')
+}
+
+/// Returns whether the token starts with a `YYYY-MM-DD` date
+fn is_date(token: &str) -> bool {
+    token.len() >= 10
+        && token.bytes().take(10).enumerate().all(|(index, byte)| {
+            if index == 4 || index == 7 {
+                byte == b'-'
+            } else {
+                byte.is_ascii_digit()
+            }
+        })
+}
+
+/// Returns whether the token looks like epoch seconds,
+/// at least 9 digits to tell them apart from the other numbers of a log
+fn is_epoch(token: &str) -> bool {
+    let (seconds, fraction) = token.split_once('.').unwrap_or((token, "0"));
+    seconds.len() >= 9
+        && seconds.bytes().all(|byte| byte.is_ascii_digit())
+        && !fraction.is_empty()
+        && fraction.bytes().all(|byte| byte.is_ascii_digit())
+}
+
+/// Parses a `MM:SS` log timestamp into seconds since the server started
+/// the minutes go past 99 on servers that run for long (e.g. `123:45`)
+///
+/// can error if the timestamp has no `:` separator, if a part is not a number
+/// or if the seconds are past 59
+pub fn parse_clock(time: &str) -> Result<u32, ParsingError> {
+    let (minutes, seconds) = time
+        .split_once(':')
+        .ok_or_else(|| ParsingError::LogPartNotFound("timestamp".to_owned()))?;
+    let minutes = minutes.parse::<u32>()?;
+    let seconds = seconds.parse::<u32>()?;
+    if seconds >= 60 {
+        return Err(ParsingError::InvalidTimestamp(time.to_owned()));
+    }
+    minutes
+        .checked_mul(60)
+        .and_then(|minutes| minutes.checked_add(seconds))
+        .ok_or_else(|| ParsingError::UnexpectedError("Timestamp overflow".to_owned()))
+}
+
+/// Parses unix epoch seconds (e.g. `1714595832` or `1714595832.250`)
+/// the fraction of a second is dropped
+///
+/// can error if the seconds are not a number
+pub fn parse_epoch(time: &str) -> Result<i64, ParsingError> {
+    let seconds = time.split_once('.').map_or(time, |(seconds, _)| seconds);
+    Ok(seconds.parse::<i64>()?)
+}
+
+/// Parses an ISO-8601 date and time into unix seconds
+/// (e.g. `2024-05-01T20:37:12Z`, `2024-05-01 20:37:12.250` or `2024-05-01T22:37:12+02:00`)
+/// the time is in UTC unless it has an offset, the fraction of a second is dropped
+///
+/// can error if a part is missing or out of range (e.g. `2024-02-30`)
+pub fn parse_iso8601(time: &str) -> Result<i64, ParsingError> {
+    let invalid = || ParsingError::InvalidTimestamp(time.to_owned());
+    let (date, time_of_day) = time.split_once(['T', ' ']).ok_or_else(invalid)?;
+
+    let mut date_parts = date.split('-');
+    let year = number(date_parts.next(), 4).ok_or_else(invalid)?;
+    let month = number(date_parts.next(), 2).filter(|month| (1..=12).contains(month));
+    let month = month.ok_or_else(invalid)?;
+    let day = number(date_parts.next(), 2)
+        .filter(|&day| day >= 1 && day <= days_in_month(year, month))
+        .ok_or_else(invalid)?;
+    if date_parts.next().is_some() {
+        return Err(invalid());
+    }
+
+    let (clock, offset) = time_of_day
+        .find(['Z', 'z', '+', '-'])
+        .map_or((time_of_day, ""), |index| time_of_day.split_at(index));
+    let clock = clock.split_once('.').map_or(clock, |(clock, _)| clock);
+    let seconds_of_day = seconds_of_day(clock, 23).ok_or_else(invalid)?;
+    let offset = parse_offset(offset).ok_or_else(invalid)?;
+
+    Ok(days_from_civil(year, month, day)
+        .saturating_mul(SECONDS_PER_DAY)
+        .saturating_add(seconds_of_day)
+        .saturating_sub(offset))
+}
+
+/// Parses a number of exactly `digits` digits
+fn number(part: Option<&str>, digits: usize) -> Option<i64> {
+    part.filter(|part| part.len() == digits && part.bytes().all(|byte| byte.is_ascii_digit()))
+        .and_then(|part| part.parse::<i64>().ok())
+}
+
+/// Parses a `HH:MM:SS` time into seconds, `HH:MM` for offsets
+/// the hours can't be past `max_hour`
+fn seconds_of_day(clock: &str, max_hour: i64) -> Option<i64> {
+    let mut parts = clock.split(':');
+    let hours = number(parts.next(), 2).filter(|&hours| hours <= max_hour)?;
+    let minutes = number(parts.next(), 2).filter(|&minutes| minutes < 60)?;
+    // a leap second is read as the next second
+    let seconds = match parts.next() {
+        Some(seconds) => number(Some(seconds), 2).filter(|&seconds| seconds <= 60)?,
+        None if max_hour < 23 => 0,
+        None => return None,
+    };
+    if parts.next().is_some() {
+        return None;
+    }
+    Some(
+        hours
+            .saturating_mul(3600)
+            .saturating_add(minutes.saturating_mul(60))
+            .saturating_add(seconds),
+    )
+}
+
+/// Parses a UTC offset (`Z`, `+02:00`, `-0300` or `+02`) into seconds
+/// no offset is UTC
+fn parse_offset(offset: &str) -> Option<i64> {
+    let (sign, offset) = match offset.split_at(offset.len().min(1)) {
+        ("" | "Z" | "z", "") => return Some(0),
+        ("+", offset) => (1, offset),
+        ("-", offset) => (-1, offset),
+        _ => return None,
+    };
+    let offset = match offset.len() {
+        2 => format!("{offset}:00"),
+        4 => format!("{}:{}", offset.get(..2)?, offset.get(2..)?),
+        _ => offset.to_owned(),
+    };
+    seconds_of_day(&offset, 18).map(|seconds| seconds.saturating_mul(sign))
+}
+
+/// Returns whether the year has a February 29th
+const fn is_leap_year(year: i64) -> bool {
+    year % 4 == 0 && (year % 100 != 0 || year % 400 == 0)
+}
+
+/// Returns the number of days of the month, from 1 (January) to 12
+const fn days_in_month(year: i64, month: i64) -> i64 {
+    match month {
+        2 if is_leap_year(year) => 29,
+        2 => 28,
+        4 | 6 | 9 | 11 => 30,
+        _ => 31,
+    }
+}
+
+/// Returns the days since 1970-01-01 of a date of the proleptic Gregorian calendar
+/// from Howard Hinnant's `days_from_civil` algorithm
+const fn days_from_civil(year: i64, month: i64, day: i64) -> i64 {
+    // the years start in March, so the leap day is the last day of the year
+    let year = if month <= 2 {
+        year.saturating_sub(1)
+    } else {
+        year
+    };
+    let era = year.div_euclid(400);
+    let year_of_era = year.rem_euclid(400);
+    let month_from_march = month.saturating_add(9).rem_euclid(12);
+    let day_of_year = month_from_march
+        .saturating_mul(153)
+        .saturating_add(2)
+        .div_euclid(5)
+        .saturating_add(day)
+        .saturating_sub(1);
+    let day_of_era = year_of_era
+        .saturating_mul(365)
+        .saturating_add(year_of_era.div_euclid(4))
+        .saturating_sub(year_of_era.div_euclid(100))
+        .saturating_add(day_of_year);
+    era.saturating_mul(146_097)
+        .saturating_add(day_of_era)
+        .saturating_sub(719_468)
+}
+
+/// Returns the date, as (year, month, day), of the days since 1970-01-01
+/// the inverse of `days_from_civil`
+const fn civil_from_days(days: i64) -> (i64, i64, i64) {
+    let days = days.saturating_add(719_468);
+    let era = days.div_euclid(146_097);
+    let day_of_era = days.rem_euclid(146_097);
+    let year_of_era = day_of_era
+        .saturating_sub(day_of_era.div_euclid(1460))
+        .saturating_add(day_of_era.div_euclid(36_524))
+        .saturating_sub(day_of_era.div_euclid(146_096))
+        .div_euclid(365);
+    let day_of_year = day_of_era.saturating_sub(
+        year_of_era
+            .saturating_mul(365)
+            .saturating_add(year_of_era.div_euclid(4))
+            .saturating_sub(year_of_era.div_euclid(100)),
+    );
+    let month_from_march = day_of_year
+        .saturating_mul(5)
+        .saturating_add(2)
+        .div_euclid(153);
+    let day = day_of_year
+        .saturating_sub(
+            month_from_march
+                .saturating_mul(153)
+                .saturating_add(2)
+                .div_euclid(5),
+        )
+        .saturating_add(1);
+    let month = month_from_march
+        .saturating_add(2)
+        .rem_euclid(12)
+        .saturating_add(1);
+    let year = year_of_era.saturating_add(era.saturating_mul(400));
+    let year = if month <= 2 {
+        year.saturating_add(1)
+    } else {
+        year
+    };
+    (year, month, day)
+}
+
+/// Formats unix seconds as an ISO-8601 date and time in UTC (e.g. `2024-05-01T20:37:12Z`)
+pub fn format_utc(seconds: i64) -> String {
+    let (year, month, day) = civil_from_days(seconds.div_euclid(SECONDS_PER_DAY));
+    let seconds_of_day = seconds.rem_euclid(SECONDS_PER_DAY);
+    format!(
+        "{year:04}-{month:02}-{day:02}T{:02}:{:02}:{:02}Z",
+        seconds_of_day.div_euclid(3600),
+        seconds_of_day.rem_euclid(3600).div_euclid(60),
+        seconds_of_day.rem_euclid(60)
+    )
+}
+
+#[cfg(test)]
+#[allow(clippy::panic, clippy::unwrap_used)]
+// the tests panic on the unexpected timestamps
+mod tests {
+    use super::*;
+    use proptest::prelude::*;
+
+    #[test]
+    fn test_detect() {
+        assert_eq!(
+            TimestampFormat::detect("20:37"),
+            Some(TimestampFormat::Relative)
+        );
+        assert_eq!(
+            TimestampFormat::detect("981:13"),
+            Some(TimestampFormat::Relative)
+        );
+        assert_eq!(
+            TimestampFormat::detect("2024-05-01T20:37:12Z"),
+            Some(TimestampFormat::Iso8601)
+        );
+        assert_eq!(
+            TimestampFormat::detect("[2024-05-01"),
+            Some(TimestampFormat::Iso8601)
+        );
+        assert_eq!(
+            TimestampFormat::detect("1714595832"),
+            Some(TimestampFormat::Epoch)
+        );
+        assert_eq!(
+            TimestampFormat::detect("[1714595832.250]"),
+            Some(TimestampFormat::Epoch)
+        );
+        assert_eq!(TimestampFormat::detect("26"), None);
+        assert_eq!(TimestampFormat::detect("Kill:"), None);
+        assert_eq!(TimestampFormat::detect("red:8"), None);
+        assert_eq!(TimestampFormat::detect("2024-05"), None);
+    }
+
+    #[test]
+    fn test_matches() {
+        assert!(TimestampFormat::Epoch.matches("1714595832"));
+        assert!(!TimestampFormat::Epoch.matches("20:37"));
+        assert!(!TimestampFormat::Epoch.matches("1714595832."));
+        assert!(!TimestampFormat::Relative.matches("2024-05-01T20:37:12Z"));
+        assert!(!TimestampFormat::Iso8601.matches("1714595832"));
+        assert!(TimestampFormat::Auto.matches("20:37"));
+        assert!(!TimestampFormat::Auto.matches("------"));
+    }
+
+    #[test]
+    fn test_parse_clock() {
+        assert_eq!(parse_clock("0:00").unwrap(), 0);
+        assert_eq!(parse_clock("20:37").unwrap(), 1237);
+        assert_eq!(parse_clock("981:13").unwrap(), 58873);
+        assert_eq!(parse_clock("123:45").unwrap(), 7425);
+        assert_eq!(
+            parse_clock("20:60"),
+            Err(ParsingError::InvalidTimestamp("20:60".to_owned()))
+        );
+        match parse_clock("2037") {
+            Err(ParsingError::LogPartNotFound(part)) => assert_eq!(part, "timestamp"),
+            other => panic!("Expected LogPartNotFound, got {other:?}"),
+        }
+        match parse_clock("20:") {
+            Err(ParsingError::ParseIntError(_)) => {}
+            other => panic!("Expected ParseIntError, got {other:?}"),
+        }
+    }
+
+    #[test]
+    fn test_parse_epoch() {
+        assert_eq!(parse_epoch("1714595832").unwrap(), 1_714_595_832);
+        assert_eq!(parse_epoch("1714595832.999").unwrap(), 1_714_595_832);
+        assert!(matches!(
+            parse_epoch("17145x5832"),
+            Err(ParsingError::ParseIntError(_))
+        ));
+    }
+
+    #[test]
+    fn test_parse_iso8601() {
+        for time in [
+            "2024-05-01T20:37:12Z",
+            "2024-05-01T20:37:12",
+            "2024-05-01 20:37:12",
+            "2024-05-01T20:37:12.250z",
+            "2024-05-01T22:37:12+02:00",
+            "2024-05-01T22:37:12+0200",
+            "2024-05-01T22:37:12+02",
+            "2024-05-01T17:37:12-03:00",
+        ] {
+            assert_eq!(parse_iso8601(time).unwrap(), 1_714_595_832, "{time}");
+        }
+        assert_eq!(parse_iso8601("1970-01-01T00:00:00Z").unwrap(), 0);
+        assert_eq!(parse_iso8601("1969-12-31T23:59:59Z").unwrap(), -1);
+        assert_eq!(
+            parse_iso8601("2024-02-29T00:00:00Z").unwrap(),
+            1_709_164_800
+        );
+    }
+
+    #[test]
+    fn test_parse_invalid_iso8601() {
+        for time in [
+            "2024-05-01",
+            "2024-05-01T20:37",
+            "2024-13-01T20:37:12",
+            "2024-00-01T20:37:12",
+            "2023-02-29T20:37:12",
+            "2024-04-31T20:37:12",
+            "2024-05-01T24:00:00",
+            "2024-05-01T20:60:00",
+            "2024-05-01T20:37:12:00",
+            "2024-05-01T20:37:12+25:00",
+            "2024-05-01T20:37:12UTC",
+            "2024-5-01T20:37:12",
+        ] {
+            assert_eq!(
+                parse_iso8601(time),
+                Err(ParsingError::InvalidTimestamp(time.to_owned())),
+                "{time}"
+            );
+        }
+    }
+
+    #[test]
+    fn test_format_utc() {
+        assert_eq!(format_utc(0), "1970-01-01T00:00:00Z");
+        assert_eq!(format_utc(1_714_595_832), "2024-05-01T20:37:12Z");
+        assert_eq!(format_utc(1_709_164_800), "2024-02-29T00:00:00Z");
+        assert_eq!(format_utc(-1), "1969-12-31T23:59:59Z");
+    }
+
+    proptest! {
+        #[test]
+        fn test_format_utc_round_trip(seconds in -62_135_596_800_i64..253_402_300_799_i64) {
+            prop_assert_eq!(parse_iso8601(&format_utc(seconds)).unwrap(), seconds);
+        }
+    }
+}
@@ -1,4 +1,7 @@
-use table_to_html::HtmlTable;
+use table_to_html::{
+    html::{Attribute, HtmlElement, HtmlValue, HtmlVisitorMut},
+    HtmlTable,
+};
 use tabled::{
     builder::Builder,
     settings::{object::Segment, Alignment, Settings, Style},
@@ -6,11 +9,33 @@ use tabled::{
 };
 
 use crate::{
-    cli::{ReportFormat, ReportType},
-    quake3_data::{MeanDeath, PlayerData},
-    quake3_parser::parser::Game,
+    cli::{RankBy, ReportFormat, ReportType},
+    leaderboard::{standings, Standing},
+    quake3_data::{
+        ExitReason, GameSettings, GameType, IncompleteReason, ItemClass, MeanDeath, PlayerData,
+        Team, TeamStats,
+    },
+    quake3_parser::{parser::Game, timestamps::format_utc},
+    scoring::ScoringRules,
 };
-use std::fmt::Display;
+use serde::{Deserialize, Serialize};
+use std::{cmp::Ordering, collections::BTreeMap, fmt::Display};
+
+/// The items worth highlighting in the item pickups report
+/// with the name players know them by
+const KEY_ITEMS: [(&str, &str); 3] = [
+    ("item_armor_body", "Red Armor"),
+    ("item_health_mega", "Mega Health"),
+    ("item_quad", "Quad Damage"),
+];
+
+/// Version of the json report schema
+/// Bumped whenever a field is renamed, removed or changes meaning,
+/// adding new fields does not require a bump
+///
+/// - 2: `players` holds the net score of each player instead of its kills,
+///   see `PlayerData::score`
+pub const JSON_SCHEMA_VERSION: u32 = 2;
 
 #[allow(clippy::large_enum_variant)]
 // I think size difference isn't actually that big
@@ -19,12 +44,14 @@ use std::fmt::Display;
 // let's keep it for now
 #[derive(Debug, Clone)]
 /// The report type
-/// Can be a text table or an html table
+/// Can be a text table, an html table or a json document
 pub enum Report {
     /// Text table report, via the `tabled` crate
     Text(Table),
     /// Html table report, via the `table_to_html` crate
     Html(HtmlTable),
+    /// Json report, already serialized via the `serde_json` crate
+    Json(String),
 }
 
 impl Display for Report {
@@ -32,8 +59,870 @@ impl Display for Report {
         match self {
             Self::Text(table) => write!(f, "{table}"),
             Self::Html(html_table) => write!(f, "{html_table}"),
+            Self::Json(json) => write!(f, "{json}"),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+/// The root of the json report
+///
+/// Schema (version 2):
+/// ```json
+/// {
+///   "version": 2,
+///   "games": [
+///     {
+///       "game": 1,
+///       "total_kills": 3,
+///       "settings": { "map_name": "q3dm17", "game_type": 0, "frag_limit": 20, ... },
+///       "game_type": "FreeForAll",
+///       "exit_reason": "Fraglimit",
+///       "started_at": 0,
+///       "ended_at": 154,
+///       "duration": 154,
+///       "winner": "Player2",
+///       "players": { "Player1": -1, "Player2": 2 },
+///       "player_stats": { "Player1": { "frags": 0, ..., "score": -1 }, ... },
+///       "ranking": ["Player2", "Player1"],
+///       "kills_by_means": { "RocketSplash": 2, "TriggerHurt": 1 }
+///     }
+///   ]
+/// }
+/// ```
+/// Every report type has the `version` and the `games`, and every game has
+/// `game`, `total_kills`, `settings`, `game_type`, `exit_reason`, `started_at`, `ended_at`
+/// and `duration`, along with the optional fields below when they apply.
+/// The other fields of a game depend on the report type, as shown above for the `all` report type
+///
+/// `settings` holds the typed server settings (`map_name`, `game_type`, `frag_limit`,
+/// `time_limit`, `capture_limit`, `hostname` and `version`), omitted when absent from the log,
+/// and every other cvar by name in `other`
+///
+/// `aliases` maps the players that used other names in the game to those names,
+/// e.g. `"aliases": { "Isgalamido": ["Izzy"] }`, and is omitted when nobody did
+///
+/// `exit_reason` is `Fraglimit`, `Timelimit`, `Capturelimit`, `Aborted`
+/// or `{ "Other": "reason" }`, and `winner` is omitted when nobody won
+///
+/// `incomplete` is `NewGameStarted` or `LogEnded` for the games that never reached
+/// their `ShutdownGame:` line, and is omitted for the others
+///
+/// `started_at` and `ended_at` are the seconds on the log clock, `duration`
+/// and `time_to_first_kill` are in seconds, the latter omitted when nobody was killed,
+/// and `clock_reset` is only present, as `true`, when the server clock was reset
+/// since the previous game
+///
+/// `started_at_utc` and `ended_at_utc` are the ISO-8601 dates in UTC the game
+/// started and ended at (e.g. `2024-05-01T20:37:12Z`), only for the logs with dates
+///
+/// `source` is the log the game was read from (e.g. `games.log.1`, or `<stdin>`),
+/// the games of several logs are numbered one after the other
+///
+/// `player_stats` holds the kill counters of each player next to `players`,
+/// and `ranking` the player names ordered by the `--rank-by` metric
+///
+/// `players`, `player_stats` and `ranking` are only present for the `all` and `player-rank`
+/// report types, as is `teams` with the final score of each team in team games,
+/// and `kills_by_means` is only present for the `all` and `mean-death` report types
+///
+/// The `item-pickups` report type adds the `items` field to each game
+/// with the pickups by item name for each player:
+/// `"items": { "Player1": { "item_armor_body": 2, "weapon_railgun": 1 } }`
+///
+/// The `time-played` report type adds the `time_played` field to each game
+/// with the seconds each player spent in the game:
+/// `"time_played": { "Player1": 312, "Player2": 95 }`
+///
+/// The `teams` report type adds the `team_stats` field to each team game
+/// with the kills, team kills and final score of each team:
+/// `"team_stats": { "Red": { "kills": 12, "team_kills": 1, "score": 8 } }`
+///
+/// The `kill-matrix` report type adds the `kill_matrix` field to each game
+/// with the kills of each player on each other player, by killer then victim,
+/// the suicides under the player itself and without the pairs that never happened:
+/// `"kill_matrix": { "Player1": { "Player2": 3, "Player1": 1 }, "Player2": { "Player1": 5 } }`
+///
+/// The `leaderboard` report type has, next to the games, the `leaderboard` field
+/// with the standings of the players across the games, best first:
+/// `"leaderboard": [ { "name": "Player2", "games": 2, "frags": 5, ..., "score": 4,
+/// "kills_by_means": { "Railgun": 5 } } ]`
+pub struct JsonReport {
+    /// The schema version, see `JSON_SCHEMA_VERSION`
+    pub version: u32,
+    /// The games in the same order as they appear in the log
+    pub games: Vec<JsonGame>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    /// The standings of the players across the games, ranked by the `--rank-by` metric
+    /// only for the `leaderboard` report type
+    pub leaderboard: Option<Vec<JsonStanding>>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+/// A single game in the json report
+pub struct JsonGame {
+    /// The game number, starting from 1
+    pub game: usize,
+    /// The total kills in the game, world kills included
+    pub total_kills: u32,
+    #[serde(default)]
+    /// The server settings of the game, see `GameSettings`
+    pub settings: GameSettings,
+    #[serde(default)]
+    /// The game type, from the `game_type` setting
+    pub game_type: GameType,
+    #[serde(default)]
+    /// Why the game ended
+    pub exit_reason: ExitReason,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    /// Why the game never reached its shutdown, omitted for complete games
+    pub incomplete: Option<IncompleteReason>,
+    #[serde(default)]
+    /// When the game started, in seconds on the log clock
+    pub started_at: u32,
+    #[serde(default)]
+    /// When the game ended, in seconds on the log clock
+    pub ended_at: u32,
+    #[serde(default)]
+    /// The seconds from the start to the end of the game
+    pub duration: u32,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    /// The seconds from the start of the game to its first kill
+    pub time_to_first_kill: Option<u32>,
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    /// Whether the server clock was reset since the previous game
+    pub clock_reset: bool,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    /// The date the game started at, only for the logs with dates
+    pub started_at_utc: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    /// The date the game ended at, only for the logs with dates
+    pub ended_at_utc: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    /// The log the game was read from, see `Game::source`
+    pub source: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    /// The name of the player that won the game, see `Game::winner`
+    pub winner: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    /// The player score by player name
+    pub players: Option<BTreeMap<String, i32>>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    /// The kill counters by player name
+    pub player_stats: Option<BTreeMap<String, JsonPlayerStats>>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    /// The player names, ordered by the metric the report ranks by
+    pub ranking: Option<Vec<String>>,
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    /// The other names each player used in the game, by player name
+    /// only the players with at least one alias are present
+    pub aliases: BTreeMap<String, Vec<String>>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    /// The final team score by team, only for team games, see `Game::final_team_scores`
+    pub teams: Option<BTreeMap<Team, i32>>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    /// The kill count by mean of death
+    pub kills_by_means: Option<BTreeMap<MeanDeath, u32>>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    /// The item pickups by item name, by player name
+    pub items: Option<BTreeMap<String, BTreeMap<String, u32>>>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    /// The seconds spent in the game, by player name
+    pub time_played: Option<BTreeMap<String, u32>>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    /// The kill statistics and final score by team, only for team games
+    pub team_stats: Option<BTreeMap<Team, JsonTeamStats>>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    /// The kills by victim name, by killer name
+    pub kill_matrix: Option<BTreeMap<String, BTreeMap<String, u32>>>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+/// The kill counters of a player in the json report, see `PlayerData`
+pub struct JsonPlayerStats {
+    /// The enemies killed
+    pub frags: u32,
+    /// The deaths, whatever the cause
+    pub deaths: u32,
+    /// The times the player killed itself
+    pub suicides: u32,
+    /// The times the world killed the player
+    pub world_deaths: u32,
+    /// The teammates killed
+    pub team_kills: u32,
+    /// The net score, see `PlayerData::score`
+    pub score: i32,
+}
+
+impl JsonPlayerStats {
+    /// Builds the kill counters of a player, with its score
+    /// see `PlayerData::score` and `Standing::score`
+    const fn new(player: &PlayerData, score: i32) -> Self {
+        Self {
+            frags: player.frags,
+            deaths: player.deaths,
+            suicides: player.suicides,
+            world_deaths: player.world_deaths,
+            team_kills: player.team_kills,
+            score,
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+/// The standing of a player across the games in the json report, see `Standing`
+pub struct JsonStanding {
+    /// The last name the player used
+    pub name: String,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    /// The other names the player used
+    pub aliases: Vec<String>,
+    /// The number of games the player was part of
+    pub games: u32,
+    #[serde(flatten)]
+    /// The kill counters summed over the games
+    pub stats: JsonPlayerStats,
+    #[serde(default)]
+    /// The enemies and teammates killed, by mean of death
+    pub kills_by_means: BTreeMap<MeanDeath, u32>,
+}
+
+impl From<&Standing> for JsonStanding {
+    fn from(standing: &Standing) -> Self {
+        let player = &standing.player;
+        Self {
+            name: player.name.clone(),
+            aliases: player.aliases().into_iter().map(str::to_owned).collect(),
+            games: standing.games(),
+            stats: JsonPlayerStats::new(player, standing.score),
+            kills_by_means: player
+                .kills_by_means
+                .iter()
+                .map(|(mean, count)| (mean.clone(), *count))
+                .collect(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+/// The statistics of a team in the json report
+pub struct JsonTeamStats {
+    /// The enemies killed by the team players
+    pub kills: u32,
+    /// The teammates killed by the team players
+    pub team_kills: u32,
+    /// The final team score, see `Game::final_team_scores`
+    pub score: i32,
+}
+
+/// Builds the json kill matrix of a game, keyed by killer then victim
+/// leaving out the victims never killed and the players that killed no one
+fn json_kill_matrix(game: &Game, people: &[PlayerData]) -> BTreeMap<String, BTreeMap<String, u32>> {
+    let matrix = game.kill_matrix_by_identity(people);
+    people
+        .iter()
+        .zip(&matrix)
+        .map(|(killer, kills)| {
+            let victims = people
+                .iter()
+                .zip(kills)
+                .filter(|(_, kills)| **kills > 0)
+                .map(|(victim, kills)| (victim.name.clone(), *kills))
+                .collect::<BTreeMap<String, u32>>();
+            (killer.name.clone(), victims)
+        })
+        .filter(|(_, victims)| !victims.is_empty())
+        .collect()
+}
+
+/// Builds the json statistics of the red and blue teams of a game, see `team_rows`
+fn json_team_stats(game: &Game) -> BTreeMap<Team, JsonTeamStats> {
+    team_rows(game)
+        .into_iter()
+        .map(|(team, stats, score)| {
+            let stats = JsonTeamStats {
+                kills: stats.kills,
+                team_kills: stats.team_kills,
+                score,
+            };
+            (team, stats)
+        })
+        .collect()
+}
+
+impl JsonGame {
+    /// Builds the json representation of a game
+    /// the players and means of death are only kept if the report type asks for them
+    ///
+    /// players that are the same person are merged, see `Game::players_by_identity`
+    fn from_game(
+        game: &Game,
+        game_number: usize,
+        report_type: &ReportType,
+        rank_by: &RankBy,
+    ) -> Self {
+        let mut people = game.players_by_identity();
+        sort_players(&mut people, rank_by, &game.scoring);
+        let with_players = matches!(report_type, ReportType::All | ReportType::PlayerRank);
+        let players = with_players.then(|| {
+            people
+                .iter()
+                .map(|player| (player.name.clone(), player.score(&game.scoring)))
+                .collect()
+        });
+        let player_stats = with_players.then(|| {
+            people
+                .iter()
+                .map(|player| {
+                    let stats = JsonPlayerStats::new(player, player.score(&game.scoring));
+                    (player.name.clone(), stats)
+                })
+                .collect()
+        });
+        let ranking =
+            with_players.then(|| people.iter().map(|player| player.name.clone()).collect());
+        let aliases = people
+            .iter()
+            .filter(|player| !player.aliases().is_empty())
+            .map(|player| {
+                (
+                    player.name.clone(),
+                    player.aliases().into_iter().map(str::to_owned).collect(),
+                )
+            })
+            .collect();
+        let teams = (players.is_some() && game.game_type().is_team_game())
+            .then(|| game.final_team_scores());
+        let kills_by_means =
+            matches!(report_type, ReportType::All | ReportType::MeanDeath).then(|| {
+                game.kills_by_means_death
+                    .iter()
+                    .map(|(mean, count)| (mean.clone(), *count))
+                    .collect()
+            });
+        let items = (*report_type == ReportType::ItemPickups).then(|| {
+            people
+                .iter()
+                .map(|player| {
+                    (
+                        player.name.clone(),
+                        player.items.clone().into_iter().collect(),
+                    )
+                })
+                .collect()
+        });
+        let time_played = (*report_type == ReportType::TimePlayed).then(|| {
+            people
+                .iter()
+                .map(|player| (player.name.clone(), player.time_played()))
+                .collect()
+        });
+        let team_stats = (*report_type == ReportType::Teams && game.game_type().is_team_game())
+            .then(|| json_team_stats(game));
+        let kill_matrix =
+            (*report_type == ReportType::KillMatrix).then(|| json_kill_matrix(game, &people));
+
+        Self {
+            game: game_number,
+            total_kills: game.total_kills,
+            settings: game.settings.clone(),
+            game_type: game.game_type(),
+            exit_reason: game.exit_reason.clone(),
+            incomplete: game.incomplete,
+            started_at: game.started_at,
+            ended_at: game.ended_at,
+            duration: game.duration(),
+            time_to_first_kill: game.time_to_first_kill(),
+            clock_reset: game.clock_reset,
+            started_at_utc: game.started_at_utc.map(format_utc),
+            ended_at_utc: game.ended_at_utc.map(format_utc),
+            source: game.source.clone(),
+            winner: game.winner(),
+            players,
+            player_stats,
+            ranking,
+            aliases,
+            teams,
+            kills_by_means,
+            items,
+            time_played,
+            team_stats,
+            kill_matrix,
+        }
+    }
+}
+
+/// Returns the json report for the games, following the `JsonReport` schema
+/// the incomplete games are left out unless `include_incomplete` is set,
+/// the games are numbered from `first_game_number`
+fn get_json_report(
+    games: &[Game],
+    first_game_number: usize,
+    report_type: &ReportType,
+    rank_by: &RankBy,
+    include_incomplete: bool,
+) -> Result<Report, &'static str> {
+    let json_report = JsonReport {
+        version: JSON_SCHEMA_VERSION,
+        games: games
+            .iter()
+            .zip(first_game_number..)
+            .filter(|(game, _)| include_incomplete || game.incomplete.is_none())
+            .map(|(game, game_number)| JsonGame::from_game(game, game_number, report_type, rank_by))
+            .collect(),
+        leaderboard: (*report_type == ReportType::Leaderboard).then(|| {
+            ranked_standings(&shown_games(games, include_incomplete), rank_by)
+                .iter()
+                .map(JsonStanding::from)
+                .collect()
+        }),
+    };
+
+    serde_json::to_string_pretty(&json_report)
+        .map(Report::Json)
+        .map_err(|_| "Could not serialize report to json")
+}
+
+/// Formats the name of a player
+/// followed by the other names it used in the game, if any
+fn format_player_name(player: &PlayerData) -> String {
+    let aliases = player.aliases();
+    if aliases.is_empty() {
+        player.name.clone()
+    } else {
+        format!("{} (aka {})", player.name, aliases.join(", "))
+    }
+}
+
+/// Formats the item pickups of a player
+/// one line with the pickups by item class
+/// and one line with the key items, if the player picked any
+fn format_player_items(player: &PlayerData) -> String {
+    let items_by_class = player.items_by_class();
+    let classes = if items_by_class.is_empty() {
+        "No pickups".to_owned()
+    } else {
+        items_by_class
+            .iter()
+            .map(|(class, count): (&ItemClass, &u32)| format!("{class}: {count}"))
+            .collect::<Vec<String>>()
+            .join(" | ")
+    };
+    let key_items = KEY_ITEMS
+        .iter()
+        .filter_map(|(item, item_name)| {
+            player
+                .items
+                .get(*item)
+                .map(|count| format!("{item_name}: {count}"))
+        })
+        .collect::<Vec<String>>()
+        .join(" | ");
+
+    if key_items.is_empty() {
+        format!("\n{}\n{classes}\n", format_player_name(player))
+    } else {
+        format!("\n{}\n{classes}\n{key_items}\n", format_player_name(player))
+    }
+}
+
+/// Compares two players, each given with its score, by the metric
+/// the player with the most of the metric comes first, whatever the metric
+fn compare_players(a: (&PlayerData, i32), b: (&PlayerData, i32), rank_by: &RankBy) -> Ordering {
+    let ((a, a_score), (b, b_score)) = (a, b);
+    match rank_by {
+        RankBy::Score => b_score.cmp(&a_score),
+        RankBy::Frags => b.frags.cmp(&a.frags),
+        RankBy::Deaths => b.deaths.cmp(&a.deaths),
+        RankBy::Suicides => b.suicides.cmp(&a.suicides),
+        RankBy::WorldDeaths => b.world_deaths.cmp(&a.world_deaths),
+        RankBy::TeamKills => b.team_kills.cmp(&a.team_kills),
+        RankBy::Kd => b.kd_ratio().total_cmp(&a.kd_ratio()),
+    }
+}
+
+/// Sorts the players by the metric, best first
+/// their scores following the `scoring` rules, see `compare_players`, ties keep their order
+fn sort_players(players_data: &mut [PlayerData], rank_by: &RankBy, scoring: &ScoringRules) {
+    players_data
+        .sort_by(|a, b| compare_players((a, a.score(scoring)), (b, b.score(scoring)), rank_by));
+}
+
+/// Returns the name of the metric, for the report headers
+const fn rank_by_label(rank_by: &RankBy) -> &'static str {
+    match rank_by {
+        RankBy::Score => "Score",
+        RankBy::Frags => "Frags",
+        RankBy::Deaths => "Deaths",
+        RankBy::Suicides => "Suicides",
+        RankBy::WorldDeaths => "World deaths",
+        RankBy::TeamKills => "Team kills",
+        RankBy::Kd => "K/D",
+    }
+}
+
+/// Formats the value of the metric for a player
+/// the kill/death ratio with two decimals, the counters as is
+/// and the score following the `scoring` rules
+fn format_rank_value(player: &PlayerData, rank_by: &RankBy, scoring: &ScoringRules) -> String {
+    match rank_by {
+        RankBy::Score => player.score(scoring).to_string(),
+        RankBy::Frags => player.frags.to_string(),
+        RankBy::Deaths => player.deaths.to_string(),
+        RankBy::Suicides => player.suicides.to_string(),
+        RankBy::WorldDeaths => player.world_deaths.to_string(),
+        RankBy::TeamKills => player.team_kills.to_string(),
+        RankBy::Kd => format!("{:.2}", player.kd_ratio()),
+    }
+}
+
+/// Formats the player ranking, following the game type
+/// - free for all: every player by the metric
+/// - tournament: the duel of the two best players, then the other players
+/// - team games: each team final score followed by its players, then the players without a team
+///
+/// the team scores are the final ones, see `Game::final_team_scores`,
+/// the players are expected to be already sorted by the metric, see `sort_players`
+fn format_player_rank(game: &Game, players_data: &[PlayerData], rank_by: &RankBy) -> String {
+    let format_players = |players: &[&PlayerData]| {
+        players
+            .iter()
+            .map(|player| {
+                format!(
+                    "\n{}: {}\n",
+                    format_player_name(player),
+                    format_rank_value(player, rank_by, &game.scoring)
+                )
+            })
+            .collect::<Vec<String>>()
+            .concat()
+    };
+    let players: Vec<&PlayerData> = players_data.iter().collect();
+
+    match game.game_type() {
+        GameType::FreeForAll => format_players(&players),
+        GameType::Tournament => match players.as_slice() {
+            [first, second, others @ ..] => format!(
+                "\n{} {} x {} {}\n{}",
+                format_player_name(first),
+                format_rank_value(first, rank_by, &game.scoring),
+                format_rank_value(second, rank_by, &game.scoring),
+                format_player_name(second),
+                format_players(others)
+            ),
+            _ => format_players(&players),
+        },
+        GameType::TeamDeathmatch | GameType::CaptureTheFlag => {
+            let team_scores = game.final_team_scores();
+            let teams = team_scores
+                .iter()
+                .map(|(team, score)| {
+                    let members: Vec<&PlayerData> = players
+                        .iter()
+                        .copied()
+                        .filter(|player| player.team == *team)
+                        .collect();
+                    format!("\n{team} team: {score}\n{}", format_players(&members))
+                })
+                .collect::<Vec<String>>()
+                .concat();
+            let without_team: Vec<&PlayerData> = players
+                .iter()
+                .copied()
+                .filter(|player| !team_scores.contains_key(&player.team))
+                .collect();
+            format!("{teams}{}", format_players(&without_team))
+        }
+    }
+}
+
+/// Formats the time played of a player as `MM:SS`
+/// followed by the kills per minute, if the player spent any time in the game
+fn format_player_time_played(player: &PlayerData) -> String {
+    let time_played = player.time_played();
+    let kills_per_minute = player.kills_per_minute().map_or_else(
+        || "-".to_owned(),
+        |kills_per_minute| format!("{kills_per_minute:.2}"),
+    );
+
+    format!(
+        "\n{}: {} | {kills_per_minute} kills/min\n",
+        format_player_name(player),
+        format_clock(time_played),
+    )
+}
+
+/// Formats seconds as `MM:SS`
+/// the minutes keep growing past 99 (e.g. `123:45`)
+fn format_clock(seconds: u32) -> String {
+    format!(
+        "{:02}:{:02}",
+        seconds.div_euclid(60),
+        seconds.rem_euclid(60)
+    )
+}
+
+/// Returns the red and blue teams of a game
+/// with their kill statistics and final score
+fn team_rows(game: &Game) -> Vec<(Team, TeamStats, i32)> {
+    let final_scores = game.final_team_scores();
+    [Team::Red, Team::Blue]
+        .into_iter()
+        .map(|team| {
+            (
+                team,
+                game.team_stats.get(&team).copied().unwrap_or_default(),
+                final_scores.get(&team).copied().unwrap_or_default(),
+            )
+        })
+        .collect()
+}
+
+/// Formats the kills, team kills and final score of each team
+/// or `No teams` when the game isn't a team game
+fn format_team_stats(game: &Game) -> String {
+    if !game.game_type().is_team_game() {
+        return "\nNo teams\n".to_owned();
+    }
+
+    team_rows(game)
+        .iter()
+        .map(|(team, stats, score)| {
+            format!(
+                "\n{team}: {} kills | {} team kills | {score}\n",
+                stats.kills, stats.team_kills
+            )
+        })
+        .collect::<Vec<String>>()
+        .concat()
+}
+
+/// Formats the kill matrix of a game as a grid
+/// the killers are the rows and the victims the columns, both in ranking order
+fn format_kill_matrix(game: &Game, players_data: &[PlayerData]) -> String {
+    if players_data.is_empty() {
+        return String::new();
+    }
+
+    let matrix = game.kill_matrix_by_identity(players_data);
+    let mut builder = Builder::default();
+    builder.push_record(
+        std::iter::once("Killer \\ Victim".to_owned())
+            .chain(players_data.iter().map(|player| player.name.clone())),
+    );
+    for (killer, kills) in players_data.iter().zip(&matrix) {
+        builder.push_record(
+            std::iter::once(killer.name.clone()).chain(kills.iter().map(u32::to_string)),
+        );
+    }
+    let mut grid = builder.build();
+    grid.with(Style::modern());
+    format!("\n{grid}\n")
+}
+
+/// Builds the kill matrix of a game as an html heat table
+/// each cell is shaded by its share of the most kills of a player on another
+fn kill_matrix_heat_table(game: &Game, players_data: &[PlayerData]) -> HtmlElement {
+    let matrix = game.kill_matrix_by_identity(players_data);
+    let most_kills = matrix.iter().flatten().copied().max().unwrap_or_default();
+    let cell = |tag: &str, attrs: Vec<Attribute>, text: String| {
+        HtmlElement::new(tag, attrs, Some(HtmlValue::Content(text)))
+    };
+    let row =
+        |cells: Vec<HtmlElement>| HtmlElement::new("tr", vec![], Some(HtmlValue::Elements(cells)));
+
+    let header = std::iter::once(cell("th", vec![], "Killer \\ Victim".to_owned()))
+        .chain(
+            players_data
+                .iter()
+                .map(|player| cell("th", vec![], player.name.clone())),
+        )
+        .collect();
+    let mut rows = vec![row(header)];
+    for (killer, kills) in players_data.iter().zip(&matrix) {
+        let cells = std::iter::once(cell("th", vec![], killer.name.clone()))
+            .chain(kills.iter().map(|&kills| {
+                let attrs = if kills > 0 {
+                    let heat = f64::from(kills) / f64::from(most_kills);
+                    vec![Attribute::new(
+                        "style",
+                        format!("background-color: rgba(220, 20, 60, {heat:.2})"),
+                    )]
+                } else {
+                    vec![]
+                };
+                cell("td", attrs, kills.to_string())
+            }))
+            .collect();
+        rows.push(row(cells));
+    }
+
+    HtmlElement::new(
+        "table",
+        vec![Attribute::new("class", "kill-matrix")],
+        Some(HtmlValue::Elements(rows)),
+    )
+}
+
+/// Replaces the content of a cell of an html table with an element
+/// the cell is found by its (row, column) position, the header being the row 0
+///
+/// the rows after the cell are not visited,
+/// so the cells have to be replaced from the last one up
+#[derive(Debug)]
+struct CellReplacer {
+    /// The position of the cell to replace
+    position: (usize, usize),
+    /// The element to put in the cell, taken once the cell is found
+    element: Option<HtmlElement>,
+    /// The row being visited, `None` before the first row
+    row: Option<usize>,
+    /// The column being visited in the row
+    column: usize,
+}
+
+impl HtmlVisitorMut for CellReplacer {
+    fn visit_element_mut(&mut self, element: &mut HtmlElement) -> bool {
+        match element.tag() {
+            "tr" => {
+                self.row = Some(self.row.map_or(0, |row| row.saturating_add(1)));
+                self.column = 0;
+            }
+            "td" | "th" if self.row == Some(self.position.0) => {
+                if self.column == self.position.1 {
+                    let tag = element.tag().to_owned();
+                    let attrs = element.attrs().to_vec();
+                    let content = self
+                        .element
+                        .take()
+                        .map(|content| HtmlValue::Elements(vec![content]));
+                    *element = HtmlElement::new(tag, attrs, content);
+                    return false;
+                }
+                self.column = self.column.saturating_add(1);
+            }
+            _ => {}
         }
+        true
+    }
+}
+
+/// Replaces the text kill matrices of the html report with heat tables
+/// the games are the ones shown in the report, in their order
+fn set_kill_matrix_heat_tables(html_table: &mut HtmlTable, games: &[&Game], rank_by: &RankBy) {
+    // the kill matrix is the column after the title, total kills and outcome
+    for (index, game) in games.iter().enumerate().rev() {
+        let mut players_data = game.players_by_identity();
+        sort_players(&mut players_data, rank_by, &game.scoring);
+        html_table.visit_mut(CellReplacer {
+            position: (index.saturating_add(1), 3),
+            element: Some(kill_matrix_heat_table(game, &players_data)),
+            row: None,
+            column: 0,
+        });
+    }
+}
+
+/// Returns the games shown in the report, in their order
+/// the incomplete games are left out unless `include_incomplete` is set
+fn shown_games(games: &[Game], include_incomplete: bool) -> Vec<&Game> {
+    games
+        .iter()
+        .filter(|game| include_incomplete || game.incomplete.is_none())
+        .collect()
+}
+
+/// Returns the standings of the players across the games, best first
+/// ranked by the metric, and by name for the ties
+fn ranked_standings(games: &[&Game], rank_by: &RankBy) -> Vec<Standing> {
+    let mut standings = standings(games);
+    standings.sort_by(|a, b| a.player.name.cmp(&b.player.name));
+    standings.sort_by(|a, b| compare_players((&a.player, a.score), (&b.player, b.score), rank_by));
+    standings
+}
+
+/// Formats the kills of a player by mean of death, the most used first
+/// or `No kills` when the player killed no one
+fn format_kills_by_means(player: &PlayerData) -> String {
+    if player.kills_by_means.is_empty() {
+        return "No kills".to_owned();
+    }
+
+    let mut kills_by_means: Vec<(&MeanDeath, &u32)> = player.kills_by_means.iter().collect();
+    kills_by_means.sort_unstable_by(|a, b| b.1.cmp(a.1).then_with(|| a.0.cmp(b.0)));
+    kills_by_means
+        .iter()
+        .map(|(mean, count)| format!("{mean}: {count}"))
+        .collect::<Vec<String>>()
+        .join("\n")
+}
+
+/// Builds the leaderboard table
+/// a row per player with its kill counters summed over the games, ranked by the metric
+fn leaderboard_builder(games: &[&Game], rank_by: &RankBy) -> Builder {
+    let mut builder = Builder::default();
+    builder.push_record([
+        format!("Rank\n(by {})", rank_by_label(rank_by)),
+        "Player".to_owned(),
+        "Games".to_owned(),
+        "Frags".to_owned(),
+        "Deaths".to_owned(),
+        "Suicides".to_owned(),
+        "World deaths".to_owned(),
+        "Team kills".to_owned(),
+        "K/D".to_owned(),
+        "Score".to_owned(),
+        "Kills by mean\n(Mean: Kills)".to_owned(),
+    ]);
+    for (rank, standing) in (1_usize..).zip(ranked_standings(games, rank_by)) {
+        let player = &standing.player;
+        builder.push_record([
+            rank.to_string(),
+            format_player_name(player),
+            standing.games().to_string(),
+            player.frags.to_string(),
+            player.deaths.to_string(),
+            player.suicides.to_string(),
+            player.world_deaths.to_string(),
+            player.team_kills.to_string(),
+            format!("{:.2}", player.kd_ratio()),
+            standing.score.to_string(),
+            format_kills_by_means(player),
+        ]);
     }
+    builder
+}
+
+/// Formats the game title
+/// the game number followed by the map name, game type, duration and start date, when known
+/// and by the log the game was read from when `with_source` is set
+fn format_game_title(game: &Game, game_number: usize, with_source: bool) -> String {
+    let mut title = vec![format!("Game {game_number}")];
+    if let Some(map_name) = &game.settings.map_name {
+        title.push(map_name.clone());
+    }
+    if game.settings.game_type.is_some() {
+        title.push(game.game_type().to_string());
+    }
+    if game.duration() > 0 {
+        title.push(format_clock(game.duration()));
+    }
+    if let Some(started_at_utc) = game.started_at_utc {
+        title.push(format_utc(started_at_utc));
+    }
+    if let Some(source) = game.source.as_ref().filter(|_| with_source) {
+        title.push(source.clone());
+    }
+    title.join("\n")
+}
+
+/// Formats the outcome of a game
+/// the winner, if any, and why the game ended
+fn format_game_outcome(game: &Game) -> String {
+    let incomplete = game
+        .incomplete
+        .map_or_else(String::new, |reason| format!("(incomplete: {reason})\n"));
+    format!("\n{}\n{}\n{incomplete}", game.outcome(), game.exit_reason)
 }
 
 /// Populates the table content rows for the terminal report
@@ -41,26 +930,39 @@ impl Display for Report {
 fn populate_table_content(
     builder: &mut Builder,
     game: &Game,
-    players_data: &[&PlayerData],
+    players_data: &[PlayerData],
     report_type: &ReportType,
+    rank_by: &RankBy,
     game_number: usize,
+    with_source: bool,
 ) {
-    let mut m_data = String::new();
     let mut kills_by_means_death: Vec<(&MeanDeath, &u32)> =
         game.kills_by_means_death.iter().collect();
     kills_by_means_death.sort_unstable_by(|a, b| b.1.cmp(a.1));
-    for (mean, count) in &kills_by_means_death {
-        m_data.push_str(&format!("\n{mean}: {count}\n"));
-    }
+    let m_data = kills_by_means_death
+        .iter()
+        .map(|(mean, count)| format!("\n{mean}: {count}\n"))
+        .collect::<Vec<String>>()
+        .concat();
 
-    let mut p_data = String::new();
-    for player in players_data {
-        p_data.push_str(&format!("\n{}: {}\n", player.name, player.kills));
-    }
+    let p_data = format_player_rank(game, players_data, rank_by);
+
+    let i_data = players_data
+        .iter()
+        .map(format_player_items)
+        .collect::<Vec<String>>()
+        .concat();
+
+    let t_data = players_data
+        .iter()
+        .map(format_player_time_played)
+        .collect::<Vec<String>>()
+        .concat();
 
     let mut game_data = vec![
-        format!("Game {}", game_number),
+        format_game_title(game, game_number, with_source),
         format!("{}", game.total_kills),
+        format_game_outcome(game),
     ];
     match report_type {
         ReportType::All => {
@@ -73,36 +975,117 @@ fn populate_table_content(
         ReportType::MeanDeath => {
             game_data.push(m_data);
         }
+        ReportType::ItemPickups => {
+            game_data.push(i_data);
+        }
+        ReportType::TimePlayed => {
+            game_data.push(t_data);
+        }
+        ReportType::Teams => {
+            game_data.push(format_team_stats(game));
+        }
+        ReportType::KillMatrix => {
+            game_data.push(format_kill_matrix(game, players_data));
+        }
+        // the leaderboard has its own table, see `leaderboard_builder`
+        ReportType::Leaderboard => {}
     }
     builder.insert_record(0, game_data);
 }
 
 /// Populates the table headers for the terminal report
 /// with the columns for the report type
-fn populate_table_headers(builder: &mut Builder, report_type: &ReportType) {
-    let mut columns = vec!["\n\n", "\nTotal game kills\n"];
+/// the kill rank column names the metric the players are ranked by
+fn populate_table_headers(builder: &mut Builder, report_type: &ReportType, rank_by: &RankBy) {
+    let kill_rank = format!("\nKill Rank\n(Player: {})\n", rank_by_label(rank_by));
+    let mut columns = vec!["\n\n", "\nTotal game kills\n", "\nOutcome\n(Winner, End)\n"];
     match report_type {
         ReportType::All => {
-            columns.push("\nKill Rank\n(Player: Score)\n");
+            columns.push(&kill_rank);
             columns.push("\nDeath Causes\n(Cause: Count)\n");
         }
         ReportType::PlayerRank => {
-            columns.push("\nKill Rank\n(Player: Score)\n");
+            columns.push(&kill_rank);
         }
         ReportType::MeanDeath => {
             columns.push("\nDeath Causes\n(Cause: Count)\n");
         }
+        ReportType::ItemPickups => {
+            columns.push("\nItem Pickups\n(Class: Count)\n");
+        }
+        ReportType::TimePlayed => {
+            columns.push("\nTime Played\n(Player: Time | Kills/min)\n");
+        }
+        ReportType::Teams => {
+            columns.push("\nTeams\n(Team: Kills | Team kills | Score)\n");
+        }
+        ReportType::KillMatrix => {
+            columns.push("\nKill Matrix\n(Killer \\ Victim: Kills)\n");
+        }
+        ReportType::Leaderboard => {}
     }
 
     builder.insert_record(0, columns);
 }
 
+/// Builds the table with a row per game, for every report type but the leaderboard
+/// the incomplete games are left out unless `include_incomplete` is set,
+/// the other games keep their number in the log, starting at `first_game_number`,
+/// and their titles name their log when the games were read from several logs
+fn games_builder(
+    games: &[Game],
+    first_game_number: usize,
+    report_type: &ReportType,
+    rank_by: &RankBy,
+    include_incomplete: bool,
+) -> Result<Builder, &'static str> {
+    let mut builder = Builder::default();
+    let mut game_number = games
+        .len()
+        .checked_add(first_game_number)
+        .and_then(|end| end.checked_sub(1))
+        .ok_or("Game number is out of range")?;
+    let with_source = games
+        .split_first()
+        .is_some_and(|(first, rest)| rest.iter().any(|game| game.source != first.source));
+
+    for game in games.iter().rev() {
+        if include_incomplete || game.incomplete.is_none() {
+            let mut players_data = game.players_by_identity();
+            sort_players(&mut players_data, rank_by, &game.scoring);
+
+            populate_table_content(
+                &mut builder,
+                game,
+                &players_data,
+                report_type,
+                rank_by,
+                game_number,
+                with_source,
+            );
+        }
+
+        game_number = game_number.checked_sub(1).ok_or("Game number is zero")?;
+    }
+    populate_table_headers(&mut builder, report_type, rank_by);
+
+    Ok(builder)
+}
+
 /// Returns report with the game data, player data and means of death data
 /// in a table format
 ///
-/// The report can be either text or html
+/// The report can be either text, html or json
+/// (the json report follows the `JsonReport` schema instead of the table below)
 ///
 /// And can include or exclude the player ranking and the mean of death ranking
+/// the players of every report are ranked by the `rank_by` metric
+///
+/// The incomplete games are left out unless `include_incomplete` is set,
+/// the other games keep their number in the log
+///
+/// The leaderboard report type has a single table instead,
+/// with the standings of the players across the games, see `leaderboard_builder`
 ///
 /// The report format is as follows:
 /// Game N | Total kills in game: X | Player with most kills: Y
@@ -115,19 +1098,59 @@ pub fn get_report(
     games: &[Game],
     report_type: &ReportType,
     report_format: &ReportFormat,
+    rank_by: &RankBy,
+    include_incomplete: bool,
 ) -> Result<Report, &'static str> {
-    let mut builder = Builder::default();
-    let mut game_number = games.len();
-
-    for game in games.iter().rev() {
-        let players_data: &mut Vec<&PlayerData> = &mut game.players_data.values().collect();
-        players_data.sort_unstable();
+    build_report(
+        games,
+        1,
+        report_type,
+        report_format,
+        rank_by,
+        include_incomplete,
+    )
+}
 
-        populate_table_content(&mut builder, game, players_data, report_type, game_number);
+/// Returns the report of a single game, numbered `game_number` as in its log
+/// e.g. for the games reported as soon as they end, see `get_report`
+pub fn get_game_report(
+    game: &Game,
+    game_number: usize,
+    report_type: &ReportType,
+    report_format: &ReportFormat,
+    rank_by: &RankBy,
+) -> Result<Report, &'static str> {
+    build_report(
+        std::slice::from_ref(game),
+        game_number,
+        report_type,
+        report_format,
+        rank_by,
+        true,
+    )
+}
 
-        game_number = game_number.checked_sub(1).ok_or("Game number is zero")?;
-    }
-    populate_table_headers(&mut builder, report_type);
+/// Returns the report of the games, numbered from `first_game_number`, see `get_report`
+fn build_report(
+    games: &[Game],
+    first_game_number: usize,
+    report_type: &ReportType,
+    report_format: &ReportFormat,
+    rank_by: &RankBy,
+    include_incomplete: bool,
+) -> Result<Report, &'static str> {
+    let shown = shown_games(games, include_incomplete);
+    let builder = if *report_type == ReportType::Leaderboard {
+        leaderboard_builder(&shown, rank_by)
+    } else {
+        games_builder(
+            games,
+            first_game_number,
+            report_type,
+            rank_by,
+            include_incomplete,
+        )?
+    };
 
     match report_format {
         ReportFormat::Text => {
@@ -146,13 +1169,31 @@ pub fn get_report(
                 table_to_html::Alignment::center(),
             );
             html_table.set_border(1);
+            if *report_type == ReportType::KillMatrix {
+                set_kill_matrix_heat_tables(&mut html_table, &shown, rank_by);
+            }
             Ok(Report::Html(html_table))
         }
+        ReportFormat::Json => get_json_report(
+            games,
+            first_game_number,
+            report_type,
+            rank_by,
+            include_incomplete,
+        ),
     }
 }
 
 #[cfg(test)]
+#[allow(
+    clippy::default_numeric_fallback,
+    clippy::panic,
+    clippy::shadow_unrelated,
+    clippy::unwrap_used
+)]
+// the tests unwrap the reports and compare them with plain literals
 mod tests {
+    use crate::quake3_data::Session;
     use std::collections::HashMap;
 
     use super::*;
@@ -169,7 +1210,7 @@ mod tests {
     fn test_display_empty_html_report() {
         let report = Report::Html(HtmlTable::new(vec![vec![""]]));
         let report_str = report.to_string();
-        let expected = r#"<table>
+        let expected = "<table>
     <tbody>
         <tr>
             <td>
@@ -181,12 +1222,12 @@ mod tests {
             </td>
         </tr>
     </tbody>
-</table>"#;
+</table>";
         assert_eq!(report_str, expected);
     }
 
     /// Since the display of Report only passed to the formatter of each variant
-    /// let's test the `tabled` directly with: https://github.com/zhiburt/tabled/tree/master?tab=readme-ov-file#usage
+    /// let's test the `tabled` directly with: <https://github.com/zhiburt/tabled/tree/master?tab=readme-ov-file#usage>
     #[test]
     fn test_display_report_text_table() {
         struct Language {
@@ -195,7 +1236,7 @@ mod tests {
             invented_year: usize,
         }
 
-        let languages = vec![
+        let languages = [
             Language {
                 name: "C",
                 designed_by: "Dennis Ritchie",
@@ -216,8 +1257,8 @@ mod tests {
         let mut builder = Builder::new();
         for language in languages.iter().rev() {
             let record = vec![
-                language.name.to_string(),
-                language.designed_by.to_string(),
+                language.name.to_owned(),
+                language.designed_by.to_owned(),
                 language.invented_year.to_string(),
             ];
             builder.insert_record(0, record);
@@ -241,7 +1282,7 @@ mod tests {
     }
 
     /// Since the display of Report only passed to the formatter of each variant
-    /// let's test the `table_to_html` directly with: https://docs.rs/table_to_html/latest/table_to_html/#example-building-a-table-from-iterator
+    /// let's test the `table_to_html` directly with: <https://docs.rs/table_to_html/latest/table_to_html/#example-building-a-table-from-iterator>
     #[test]
     fn test_display_report_html_table() {
         let data = vec![
@@ -330,7 +1371,7 @@ mod tests {
                 "    </tbody>\n",
                 "</table>"
             ),
-        )
+        );
     }
 
     fn a_random_mean_death() -> impl Strategy<Value = MeanDeath> {
@@ -368,8 +1409,8 @@ mod tests {
     }
 
     prop_compose! {
-        fn arb_player_data()(name in "[a-z]*", kills in any::<i32>()) -> PlayerData {
-            PlayerData { name, kills }
+        fn arb_player_data()(name in "[a-z]*", frags in any::<u16>(), world_deaths in any::<u16>()) -> PlayerData {
+            PlayerData { name, frags: frags.into(), deaths: world_deaths.into(), world_deaths: world_deaths.into(), ..Default::default() }
         }
     }
 
@@ -382,7 +1423,8 @@ mod tests {
             Game {
                 total_kills,
                 kills_by_means_death,
-                players_data
+                players_data,
+                ..Default::default()
             }
         }
     }
@@ -392,11 +1434,19 @@ mod tests {
             Just(ReportType::All),
             Just(ReportType::PlayerRank),
             Just(ReportType::MeanDeath),
+            Just(ReportType::ItemPickups),
+            Just(ReportType::TimePlayed),
+            Just(ReportType::Teams),
+            Just(ReportType::KillMatrix),
         ]
     }
 
     fn report_format() -> impl Strategy<Value = ReportFormat> {
-        prop_oneof![Just(ReportFormat::Html), Just(ReportFormat::Text)]
+        prop_oneof![
+            Just(ReportFormat::Html),
+            Just(ReportFormat::Text),
+            Just(ReportFormat::Json)
+        ]
     }
 
     proptest! {
@@ -406,7 +1456,7 @@ mod tests {
             report_type in report_type(),
             report_format in report_format(),
         ) {
-            let result = get_report(&games, &report_type, &report_format);
+            let result = get_report(&games, &report_type, &report_format, &RankBy::Score, true);
             assert!(result.is_ok());
 
             match result {
@@ -418,11 +1468,371 @@ mod tests {
                     let html_table_str = html_table.to_string();
                     assert!(!html_table_str.is_empty());
                 }
+                Ok(Report::Json(json)) => {
+                    let json_report: JsonReport = serde_json::from_str(&json).unwrap();
+                    assert_eq!(json_report.version, JSON_SCHEMA_VERSION);
+                    assert_eq!(json_report.games.len(), games.len());
+                }
                 _ => panic!("Unexpected result"),
             }
         }
     }
 
+    /// A single free for all game, won by Player2 with 2 frags
+    fn simple_games() -> Vec<Game> {
+        let kills_by_means_death =
+            HashMap::from([(MeanDeath::TriggerHurt, 1), (MeanDeath::RocketSplash, 2)]);
+        let players_data = HashMap::from([
+            (
+                2,
+                PlayerData {
+                    name: "Player1".to_owned(),
+                    deaths: 1,
+                    world_deaths: 1,
+                    ..Default::default()
+                },
+            ),
+            (
+                3,
+                PlayerData {
+                    name: "Player2".to_owned(),
+                    frags: 2,
+                    ..Default::default()
+                },
+            ),
+        ]);
+
+        vec![Game {
+            total_kills: 3,
+            kills_by_means_death,
+            players_data,
+            exit_reason: ExitReason::Fraglimit,
+            ..Default::default()
+        }]
+    }
+
+    #[test]
+    fn test_get_simple_json_report() {
+        let games = simple_games();
+        let result = get_report(
+            &games,
+            &ReportType::All,
+            &ReportFormat::Json,
+            &RankBy::Score,
+            true,
+        )
+        .unwrap();
+        let expected = concat!(
+            "{\n",
+            "  \"version\": 2,\n",
+            "  \"games\": [\n",
+            "    {\n",
+            "      \"game\": 1,\n",
+            "      \"total_kills\": 3,\n",
+            "      \"settings\": {},\n",
+            "      \"game_type\": \"FreeForAll\",\n",
+            "      \"exit_reason\": \"Fraglimit\",\n",
+            "      \"started_at\": 0,\n",
+            "      \"ended_at\": 0,\n",
+            "      \"duration\": 0,\n",
+            "      \"winner\": \"Player2\",\n",
+            "      \"players\": {\n",
+            "        \"Player1\": -1,\n",
+            "        \"Player2\": 2\n",
+            "      },\n",
+            "      \"player_stats\": {\n",
+            "        \"Player1\": {\n",
+            "          \"frags\": 0,\n",
+            "          \"deaths\": 1,\n",
+            "          \"suicides\": 0,\n",
+            "          \"world_deaths\": 1,\n",
+            "          \"team_kills\": 0,\n",
+            "          \"score\": -1\n",
+            "        },\n",
+            "        \"Player2\": {\n",
+            "          \"frags\": 2,\n",
+            "          \"deaths\": 0,\n",
+            "          \"suicides\": 0,\n",
+            "          \"world_deaths\": 0,\n",
+            "          \"team_kills\": 0,\n",
+            "          \"score\": 2\n",
+            "        }\n",
+            "      },\n",
+            "      \"ranking\": [\n",
+            "        \"Player2\",\n",
+            "        \"Player1\"\n",
+            "      ],\n",
+            "      \"kills_by_means\": {\n",
+            "        \"RocketSplash\": 2,\n",
+            "        \"TriggerHurt\": 1\n",
+            "      }\n",
+            "    }\n",
+            "  ]\n",
+            "}",
+        );
+        assert_eq!(result.to_string(), expected);
+    }
+
+    #[test]
+    fn test_format_game_title_duration() {
+        let mut game = Game {
+            started_at: 1200,
+            ended_at: 1200,
+            ..Default::default()
+        };
+        game.settings.map_name = Some("q3dm17".to_owned());
+        assert_eq!(format_game_title(&game, 3, false), "Game 3\nq3dm17");
+
+        game.ended_at = 8625;
+        assert_eq!(format_game_title(&game, 3, false), "Game 3\nq3dm17\n123:45");
+        assert_eq!(format_clock(65), "01:05");
+
+        game.started_at_utc = Some(1_714_595_832);
+        assert_eq!(
+            format_game_title(&game, 3, false),
+            "Game 3\nq3dm17\n123:45\n2024-05-01T20:37:12Z"
+        );
+
+        game.source = Some("games.log.1".to_owned());
+        assert_eq!(
+            format_game_title(&game, 3, false),
+            "Game 3\nq3dm17\n123:45\n2024-05-01T20:37:12Z"
+        );
+        assert_eq!(
+            format_game_title(&game, 3, true),
+            "Game 3\nq3dm17\n123:45\n2024-05-01T20:37:12Z\ngames.log.1"
+        );
+    }
+
+    #[test]
+    fn test_get_report_names_sources() {
+        let mut games = simple_games();
+        games.extend(simple_games());
+        for game in &mut games {
+            game.source = Some("games.log".to_owned());
+        }
+        let report = |games: &[Game], report_format| {
+            get_report(
+                games,
+                &ReportType::MeanDeath,
+                report_format,
+                &RankBy::Score,
+                true,
+            )
+            .unwrap()
+            .to_string()
+        };
+        // a single log isn't named in the titles
+        assert!(!report(&games, &ReportFormat::Text).contains("games.log"));
+
+        games[0].source = Some("games.log.1".to_owned());
+        let result = report(&games, &ReportFormat::Text);
+        assert!(result.contains("games.log.1"));
+        assert!(result.contains("Game 2"));
+
+        let result = report(&games, &ReportFormat::Json);
+        let json_report: JsonReport = serde_json::from_str(&result).unwrap();
+        let sources: Vec<Option<&str>> = json_report
+            .games
+            .iter()
+            .map(|game| game.source.as_deref())
+            .collect();
+        assert_eq!(sources, vec![Some("games.log.1"), Some("games.log")]);
+    }
+
+    #[test]
+    fn test_get_game_report_keeps_game_number() {
+        let games = simple_games();
+        let result = get_game_report(
+            &games[0],
+            7,
+            &ReportType::All,
+            &ReportFormat::Text,
+            &RankBy::Score,
+        )
+        .unwrap()
+        .to_string();
+        assert!(result.contains("Game 7"));
+
+        let result = get_game_report(
+            &games[0],
+            7,
+            &ReportType::All,
+            &ReportFormat::Json,
+            &RankBy::Score,
+        )
+        .unwrap()
+        .to_string();
+        let json_report: JsonReport = serde_json::from_str(&result).unwrap();
+        assert_eq!(json_report.games[0].game, 7);
+    }
+
+    #[test]
+    fn test_get_report_incomplete_games() {
+        let mut games = simple_games();
+        games.insert(
+            0,
+            Game {
+                incomplete: Some(IncompleteReason::NewGameStarted),
+                ..Default::default()
+            },
+        );
+
+        let result = get_report(
+            &games,
+            &ReportType::MeanDeath,
+            &ReportFormat::Text,
+            &RankBy::Score,
+            true,
+        )
+        .unwrap()
+        .to_string();
+        assert!(result.contains("Game 1"));
+        assert!(result.contains("(incomplete: new game started before shutdown)"));
+        assert!(result.contains("Game 2"));
+
+        let result = get_report(
+            &games,
+            &ReportType::MeanDeath,
+            &ReportFormat::Text,
+            &RankBy::Score,
+            false,
+        )
+        .unwrap()
+        .to_string();
+        assert!(!result.contains("Game 1"));
+        assert!(!result.contains("incomplete"));
+        // the complete games keep their number in the log
+        assert!(result.contains("Game 2"));
+
+        for include_incomplete in [true, false] {
+            let result = get_report(
+                &games,
+                &ReportType::All,
+                &ReportFormat::Json,
+                &RankBy::Score,
+                include_incomplete,
+            )
+            .unwrap();
+            let json_report: JsonReport = serde_json::from_str(&result.to_string()).unwrap();
+            let last = json_report.games.last().unwrap();
+            assert_eq!(last.game, 2);
+            assert_eq!(last.incomplete, None);
+            assert_eq!(
+                json_report.games.len(),
+                if include_incomplete { 2 } else { 1 }
+            );
+            if include_incomplete {
+                assert_eq!(
+                    json_report.games[0].incomplete,
+                    Some(IncompleteReason::NewGameStarted)
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn test_get_json_report_by_report_type() {
+        let games = simple_games();
+        let result = get_report(
+            &games,
+            &ReportType::PlayerRank,
+            &ReportFormat::Json,
+            &RankBy::Score,
+            true,
+        )
+        .unwrap();
+        let json_report: JsonReport = serde_json::from_str(&result.to_string()).unwrap();
+        assert!(json_report.games[0].players.is_some());
+        assert_eq!(json_report.games[0].kills_by_means, None);
+
+        let result = get_report(
+            &games,
+            &ReportType::MeanDeath,
+            &ReportFormat::Json,
+            &RankBy::Score,
+            true,
+        )
+        .unwrap();
+        let json_report: JsonReport = serde_json::from_str(&result.to_string()).unwrap();
+        assert_eq!(json_report.games[0].players, None);
+        assert!(json_report.games[0].kills_by_means.is_some());
+    }
+
+    #[test]
+    fn test_json_report_merges_players_with_same_name() {
+        let mut players_data: HashMap<u32, PlayerData> = HashMap::new();
+        players_data.insert(
+            2,
+            PlayerData {
+                name: "Zeh".to_owned(),
+                frags: 1,
+                ..Default::default()
+            },
+        );
+        players_data.insert(
+            5,
+            PlayerData {
+                name: "Zeh".to_owned(),
+                frags: 3,
+                ..Default::default()
+            },
+        );
+        let game = Game {
+            total_kills: 4,
+            kills_by_means_death: HashMap::new(),
+            players_data,
+            ..Default::default()
+        };
+
+        let json_game = JsonGame::from_game(&game, 1, &ReportType::All, &RankBy::Score);
+        let players = json_game.players.unwrap();
+        assert_eq!(players.len(), 1);
+        assert_eq!(players.get("Zeh"), Some(&4));
+    }
+
+    #[test]
+    fn test_report_shows_player_aliases() {
+        let mut game =
+            game_with_players(0, &[("Isgalamido", 3, Team::Free), ("Zeh", 1, Team::Free)]);
+        game.departed_players.push(PlayerData {
+            name: "Izzy".to_owned(),
+            frags: 2,
+            name_history: vec!["Izzy".to_owned()],
+            sessions: vec![Session {
+                disconnected_at: Some(60),
+                ..Default::default()
+            }],
+            ..Default::default()
+        });
+        for player in game.players_data.values_mut() {
+            player.name_history = vec![player.name.clone()];
+            player.sessions = vec![Session {
+                connected_at: 60,
+                ..Default::default()
+            }];
+        }
+        game.players_data
+            .get_mut(&0)
+            .unwrap()
+            .name_history
+            .insert(0, "Izzy".to_owned());
+
+        let mut players_data = game.players_by_identity();
+        sort_players(&mut players_data, &RankBy::Score, &game.scoring);
+        assert_eq!(
+            format_player_rank(&game, &players_data, &RankBy::Score),
+            "\nIsgalamido (aka Izzy): 5\n\nZeh: 1\n"
+        );
+
+        let json_game = JsonGame::from_game(&game, 1, &ReportType::All, &RankBy::Score);
+        assert_eq!(
+            json_game.aliases,
+            BTreeMap::from([("Isgalamido".to_owned(), vec!["Izzy".to_owned()])])
+        );
+        assert_eq!(json_game.players.unwrap().len(), 2);
+    }
+
     #[test]
     fn test_get_simple_report() {
         let mut kills_by_means_death: HashMap<MeanDeath, u32> = HashMap::new();
@@ -431,8 +1841,9 @@ mod tests {
         players_data.insert(
             2,
             PlayerData {
-                name: "Player1".to_string(),
-                kills: -1,
+                name: "Player1".to_owned(),
+                world_deaths: 1,
+                ..Default::default()
             },
         );
 
@@ -441,34 +1852,39 @@ mod tests {
                 total_kills: 1,
                 kills_by_means_death: kills_by_means_death.clone(),
                 players_data: players_data.clone(),
+                exit_reason: ExitReason::Fraglimit,
+                ..Default::default()
             },
             Game {
                 total_kills: 1,
                 kills_by_means_death,
                 players_data,
+                ..Default::default()
             },
         ];
 
         let report_type = ReportType::All;
         let report_format = ReportFormat::Text;
-        let result = get_report(&games, &report_type, &report_format);
+        let result = get_report(&games, &report_type, &report_format, &RankBy::Score, true);
         assert!(result.is_ok());
 
         let expected = concat!(
-            "╭────────┬──────────────────┬─────────────────┬────────────────╮\n",
-            "│        │                  │                 │                │\n",
-            "│        │ Total game kills │ Kill Rank       │ Death Causes   │\n",
-            "│        │                  │ (Player: Score) │ (Cause: Count) │\n",
-            "│        │                  │                 │                │\n",
-            "├────────┼──────────────────┼─────────────────┼────────────────┤\n",
-            "│        │                  │                 │                │\n",
-            "│ Game 1 │        1         │   Player1: -1   │ TriggerHurt: 1 │\n",
-            "│        │                  │                 │                │\n",
-            "├────────┼──────────────────┼─────────────────┼────────────────┤\n",
-            "│        │                  │                 │                │\n",
-            "│ Game 2 │        1         │   Player1: -1   │ TriggerHurt: 1 │\n",
-            "│        │                  │                 │                │\n",
-            "╰────────┴──────────────────┴─────────────────┴────────────────╯",
+            "╭────────┬──────────────────┬───────────────┬─────────────────┬────────────────╮\n",
+            "│        │                  │               │                 │                │\n",
+            "│        │ Total game kills │ Outcome       │ Kill Rank       │ Death Causes   │\n",
+            "│        │                  │ (Winner, End) │ (Player: Score) │ (Cause: Count) │\n",
+            "│        │                  │               │                 │                │\n",
+            "├────────┼──────────────────┼───────────────┼─────────────────┼────────────────┤\n",
+            "│        │                  │               │                 │                │\n",
+            "│ Game 1 │        1         │ Player1       │   Player1: -1   │ TriggerHurt: 1 │\n",
+            "│        │                  │ Fraglimit hit │                 │                │\n",
+            "│        │                  │               │                 │                │\n",
+            "├────────┼──────────────────┼───────────────┼─────────────────┼────────────────┤\n",
+            "│        │                  │               │                 │                │\n",
+            "│ Game 2 │        1         │   No winner   │   Player1: -1   │ TriggerHurt: 1 │\n",
+            "│        │                  │   Aborted     │                 │                │\n",
+            "│        │                  │               │                 │                │\n",
+            "╰────────┴──────────────────┴───────────────┴─────────────────┴────────────────╯",
         );
 
         let table_str = result.unwrap().to_string();
@@ -484,9 +1900,9 @@ mod tests {
             game_number in any::<usize>(),
         ) {
             let mut builder = Builder::default();
-            let players_data: &mut Vec<&PlayerData> = &mut game.players_data.values().collect();
-            players_data.sort_unstable();
-            populate_table_content(&mut builder, &game, players_data, &report_type, game_number);
+            let mut players_data = game.players_by_identity();
+            sort_players(&mut players_data, &RankBy::Score, &game.scoring);
+            populate_table_content(&mut builder, &game, &players_data, &report_type, &RankBy::Score, game_number, false);
             let table = builder.build();
             let table_str = table.to_string();
             assert!(!table_str.is_empty());
@@ -501,8 +1917,9 @@ mod tests {
         players_data.insert(
             2,
             PlayerData {
-                name: "Player1".to_string(),
-                kills: -1,
+                name: "Player1".to_owned(),
+                world_deaths: 1,
+                ..Default::default()
             },
         );
 
@@ -510,14 +1927,23 @@ mod tests {
             total_kills: 1,
             kills_by_means_death,
             players_data,
+            ..Default::default()
         };
 
         let report_type = ReportType::All;
         let game_number = 1;
         let mut builder = Builder::default();
-        let players_data: &mut Vec<&PlayerData> = &mut game.players_data.values().collect();
-        players_data.sort_unstable();
-        populate_table_content(&mut builder, &game, players_data, &report_type, game_number);
+        let mut players_data = game.players_by_identity();
+        sort_players(&mut players_data, &RankBy::Score, &game.scoring);
+        populate_table_content(
+            &mut builder,
+            &game,
+            &players_data,
+            &report_type,
+            &RankBy::Score,
+            game_number,
+            false,
+        );
         let mut table = builder.build();
         table.with(Style::modern_rounded());
         let table_str = table.to_string();
@@ -525,23 +1951,495 @@ mod tests {
 
         // formatting seems weird because we didn't set the alignment
         let expected = concat!(
-            "╭────────┬───┬─────────────┬────────────────╮\n",
-            "│ Game 1 │ 1 │             │                │\n",
-            "│        │   │ Player1: -1 │ TriggerHurt: 1 │\n",
-            "│        │   │             │                │\n",
-            "╰────────┴───┴─────────────┴────────────────╯",
+            "╭────────┬───┬───────────┬─────────────┬────────────────╮\n",
+            "│ Game 1 │ 1 │           │             │                │\n",
+            "│        │   │ No winner │ Player1: -1 │ TriggerHurt: 1 │\n",
+            "│        │   │ Aborted   │             │                │\n",
+            "│        │   │           │             │                │\n",
+            "╰────────┴───┴───────────┴─────────────┴────────────────╯",
         );
 
         assert_eq!(table_str, expected);
     }
 
+    #[test]
+    fn test_get_item_pickups_report() {
+        let mut players_data: HashMap<u32, PlayerData> = HashMap::new();
+        players_data.insert(
+            2,
+            PlayerData {
+                name: "Player1".to_owned(),
+                frags: 1,
+                items: HashMap::from([
+                    ("weapon_railgun".to_owned(), 2),
+                    ("item_armor_body".to_owned(), 1),
+                    ("item_armor_shard".to_owned(), 3),
+                ]),
+                ..Default::default()
+            },
+        );
+        players_data.insert(
+            3,
+            PlayerData {
+                name: "Player2".to_owned(),
+                ..Default::default()
+            },
+        );
+        let games = vec![Game {
+            total_kills: 1,
+            kills_by_means_death: HashMap::new(),
+            players_data,
+            ..Default::default()
+        }];
+
+        let result = get_report(
+            &games,
+            &ReportType::ItemPickups,
+            &ReportFormat::Text,
+            &RankBy::Score,
+            true,
+        )
+        .unwrap();
+        let expected = concat!(
+            "╭────────┬──────────────────┬───────────────┬──────────────────────╮\n",
+            "│        │                  │               │                      │\n",
+            "│        │ Total game kills │ Outcome       │    Item Pickups      │\n",
+            "│        │                  │ (Winner, End) │    (Class: Count)    │\n",
+            "│        │                  │               │                      │\n",
+            "├────────┼──────────────────┼───────────────┼──────────────────────┤\n",
+            "│        │                  │               │                      │\n",
+            "│        │                  │               │ Player1              │\n",
+            "│        │                  │               │ Weapon: 2 | Armor: 4 │\n",
+            "│ Game 1 │        1         │   No winner   │ Red Armor: 1         │\n",
+            "│        │                  │   Aborted     │                      │\n",
+            "│        │                  │               │ Player2              │\n",
+            "│        │                  │               │ No pickups           │\n",
+            "│        │                  │               │                      │\n",
+            "╰────────┴──────────────────┴───────────────┴──────────────────────╯",
+        );
+        assert_eq!(result.to_string(), expected);
+
+        let result = get_report(
+            &games,
+            &ReportType::ItemPickups,
+            &ReportFormat::Json,
+            &RankBy::Score,
+            true,
+        )
+        .unwrap();
+        let json_report: JsonReport = serde_json::from_str(&result.to_string()).unwrap();
+        let items = json_report.games[0].items.as_ref().unwrap();
+        assert_eq!(
+            items.get("Player1").unwrap().get("item_armor_shard"),
+            Some(&3)
+        );
+        assert!(items.get("Player2").unwrap().is_empty());
+        assert_eq!(json_report.games[0].players, None);
+        assert_eq!(json_report.games[0].kills_by_means, None);
+    }
+
+    /// builds a game of the given type with the given players, by client id
+    fn game_with_players(game_type: u32, players: &[(&str, i32, Team)]) -> Game {
+        let mut game = Game::default();
+        game.settings.game_type = Some(game_type);
+        for (client_id, (name, score, team)) in (0_u32..).zip(players) {
+            game.players_data.insert(
+                client_id,
+                PlayerData {
+                    name: (*name).to_owned(),
+                    frags: (*score).max(0).unsigned_abs(),
+                    world_deaths: (*score).min(0).unsigned_abs(),
+                    team: *team,
+                    ..Default::default()
+                },
+            );
+        }
+        game
+    }
+
+    #[test]
+    fn test_format_player_rank_by_game_type() {
+        let players = [
+            ("Isgalamido", 20, Team::Red),
+            ("Mocinha", 15, Team::Blue),
+            ("Zeh", 3, Team::Red),
+            ("Mal", 0, Team::Spectator),
+        ];
+
+        let game = game_with_players(0, &players);
+        let mut players_data = game.players_by_identity();
+        sort_players(&mut players_data, &RankBy::Score, &game.scoring);
+        assert_eq!(
+            format_player_rank(&game, &players_data, &RankBy::Score),
+            "\nIsgalamido: 20\n\nMocinha: 15\n\nZeh: 3\n\nMal: 0\n"
+        );
+
+        let game = game_with_players(1, &players);
+        assert_eq!(
+            format_player_rank(&game, &players_data, &RankBy::Score),
+            "\nIsgalamido 20 x 15 Mocinha\n\nZeh: 3\n\nMal: 0\n"
+        );
+
+        let game = game_with_players(3, &players);
+        assert_eq!(
+            format_player_rank(&game, &players_data, &RankBy::Score),
+            concat!(
+                "\nRed team: 23\n\nIsgalamido: 20\n\nZeh: 3\n",
+                "\nBlue team: 15\n\nMocinha: 15\n",
+                "\nMal: 0\n",
+            )
+        );
+
+        let json_game = JsonGame::from_game(&game, 1, &ReportType::All, &RankBy::Score);
+        assert_eq!(json_game.game_type, GameType::TeamDeathmatch);
+        assert_eq!(
+            json_game.teams,
+            Some(BTreeMap::from([(Team::Red, 23), (Team::Blue, 15)]))
+        );
+        let json_game = JsonGame::from_game(&game, 1, &ReportType::MeanDeath, &RankBy::Score);
+        assert_eq!(json_game.teams, None);
+    }
+
+    #[test]
+    fn test_team_scores_prefer_the_server_ones() {
+        let mut game = game_with_players(
+            4,
+            &[("Isgalamido", 5, Team::Red), ("Mocinha", 3, Team::Blue)],
+        );
+        game.server_team_scores = Some(BTreeMap::from([(Team::Red, 2), (Team::Blue, 8)]));
+        let mut players_data = game.players_by_identity();
+        sort_players(&mut players_data, &RankBy::Score, &game.scoring);
+        assert_eq!(
+            format_player_rank(&game, &players_data, &RankBy::Score),
+            "\nRed team: 2\n\nIsgalamido: 5\n\nBlue team: 8\n\nMocinha: 3\n"
+        );
+
+        let server_scores = Some(BTreeMap::from([(Team::Red, 2), (Team::Blue, 8)]));
+        let json_game = JsonGame::from_game(&game, 1, &ReportType::All, &RankBy::Score);
+        assert_eq!(json_game.teams, server_scores);
+        let json_game = JsonGame::from_game(&game, 1, &ReportType::Teams, &RankBy::Score);
+        let team_stats = json_game.team_stats.unwrap();
+        assert_eq!(
+            Some(
+                team_stats
+                    .iter()
+                    .map(|(team, stats)| (*team, stats.score))
+                    .collect()
+            ),
+            server_scores
+        );
+    }
+
+    #[test]
+    fn test_get_teams_report() {
+        let mut game = game_with_players(
+            4,
+            &[("Isgalamido", 5, Team::Red), ("Mocinha", 3, Team::Blue)],
+        );
+        game.exit_reason = ExitReason::Capturelimit;
+        game.team_stats = BTreeMap::from([
+            (
+                Team::Red,
+                TeamStats {
+                    kills: 6,
+                    team_kills: 1,
+                },
+            ),
+            (
+                Team::Blue,
+                TeamStats {
+                    kills: 3,
+                    team_kills: 0,
+                },
+            ),
+        ]);
+        game.server_team_scores = Some(BTreeMap::from([(Team::Red, 2), (Team::Blue, 8)]));
+        let games = vec![game, game_with_players(0, &[("Zeh", 1, Team::Free)])];
+
+        let result = get_report(
+            &games,
+            &ReportType::Teams,
+            &ReportFormat::Text,
+            &RankBy::Score,
+            true,
+        )
+        .unwrap();
+        let expected = concat!(
+            "╭──────────────────┬──────────────────┬──────────────────┬────────────────────────────────────╮\n",
+            "│                  │                  │                  │                                    │\n",
+            "│                  │ Total game kills │  Outcome         │ Teams                              │\n",
+            "│                  │                  │  (Winner, End)   │ (Team: Kills | Team kills | Score) │\n",
+            "│                  │                  │                  │                                    │\n",
+            "├──────────────────┼──────────────────┼──────────────────┼────────────────────────────────────┤\n",
+            "│                  │                  │                  │                                    │\n",
+            "│ Game 1           │                  │ Blue team        │  Red: 6 kills | 1 team kills | 2   │\n",
+            "│ Capture The Flag │        0         │ Capturelimit hit │                                    │\n",
+            "│                  │                  │                  │  Blue: 3 kills | 0 team kills | 8  │\n",
+            "│                  │                  │                  │                                    │\n",
+            "├──────────────────┼──────────────────┼──────────────────┼────────────────────────────────────┤\n",
+            "│                  │                  │                  │                                    │\n",
+            "│   Game 2         │        0         │    No winner     │              No teams              │\n",
+            "│   Free For All   │                  │    Aborted       │                                    │\n",
+            "│                  │                  │                  │                                    │\n",
+            "╰──────────────────┴──────────────────┴──────────────────┴────────────────────────────────────╯",
+        );
+        assert_eq!(result.to_string(), expected);
+
+        let result = get_report(
+            &games,
+            &ReportType::Teams,
+            &ReportFormat::Json,
+            &RankBy::Score,
+            true,
+        )
+        .unwrap();
+        let json_report: JsonReport = serde_json::from_str(&result.to_string()).unwrap();
+        assert_eq!(
+            json_report.games[0].team_stats,
+            Some(BTreeMap::from([
+                (
+                    Team::Red,
+                    JsonTeamStats {
+                        kills: 6,
+                        team_kills: 1,
+                        score: 2
+                    }
+                ),
+                (
+                    Team::Blue,
+                    JsonTeamStats {
+                        kills: 3,
+                        team_kills: 0,
+                        score: 8
+                    }
+                ),
+            ]))
+        );
+        assert_eq!(json_report.games[0].winner, Some("Blue team".to_owned()));
+        assert_eq!(json_report.games[0].players, None);
+        assert_eq!(json_report.games[1].team_stats, None);
+    }
+
+    #[test]
+    fn test_get_kill_matrix_report() {
+        let mut game =
+            game_with_players(0, &[("Isgalamido", 3, Team::Free), ("Zeh", 1, Team::Free)]);
+        game.kill_matrix = BTreeMap::from([
+            (
+                "Isgalamido".to_owned(),
+                BTreeMap::from([("Zeh".to_owned(), 3), ("Isgalamido".to_owned(), 1)]),
+            ),
+            (
+                "Zeh".to_owned(),
+                BTreeMap::from([("Isgalamido".to_owned(), 1)]),
+            ),
+        ]);
+        let people = game.players_by_identity();
+
+        let expected = concat!(
+            "\n",
+            "┌─────────────────┬────────────┬─────┐\n",
+            "│ Killer \\ Victim │ Isgalamido │ Zeh │\n",
+            "├─────────────────┼────────────┼─────┤\n",
+            "│ Isgalamido      │ 1          │ 3   │\n",
+            "├─────────────────┼────────────┼─────┤\n",
+            "│ Zeh             │ 1          │ 0   │\n",
+            "└─────────────────┴────────────┴─────┘\n",
+        );
+        assert_eq!(format_kill_matrix(&game, &people), expected);
+
+        let games = vec![game];
+        let result = get_report(
+            &games,
+            &ReportType::KillMatrix,
+            &ReportFormat::Json,
+            &RankBy::Score,
+            true,
+        )
+        .unwrap();
+        let json_report: JsonReport = serde_json::from_str(&result.to_string()).unwrap();
+        assert_eq!(
+            json_report.games[0].kill_matrix,
+            Some(BTreeMap::from([
+                (
+                    "Isgalamido".to_owned(),
+                    BTreeMap::from([("Isgalamido".to_owned(), 1), ("Zeh".to_owned(), 3)])
+                ),
+                (
+                    "Zeh".to_owned(),
+                    BTreeMap::from([("Isgalamido".to_owned(), 1)])
+                ),
+            ]))
+        );
+        assert_eq!(json_report.games[0].players, None);
+
+        let result = get_report(
+            &games,
+            &ReportType::KillMatrix,
+            &ReportFormat::Html,
+            &RankBy::Score,
+            true,
+        )
+        .unwrap()
+        .to_string();
+        assert!(result.contains(r#"<table class="kill-matrix">"#));
+        // the cells are shaded by their share of the most kills
+        assert!(result.contains("background-color: rgba(220, 20, 60, 1.00)"));
+        assert!(result.contains("background-color: rgba(220, 20, 60, 0.33)"));
+    }
+
+    #[test]
+    fn test_get_leaderboard_report() {
+        let mut first =
+            game_with_players(0, &[("Isgalamido", 3, Team::Free), ("Zeh", -1, Team::Free)]);
+        first.players_data.get_mut(&0).unwrap().kills_by_means =
+            HashMap::from([(MeanDeath::Railgun, 2), (MeanDeath::Rocket, 1)]);
+        let second = game_with_players(0, &[("Zeh", 4, Team::Free), ("Mal", 0, Team::Free)]);
+        let mut incomplete = game_with_players(0, &[("Mal", 9, Team::Free)]);
+        incomplete.incomplete = Some(IncompleteReason::LogEnded);
+        let games = vec![first, second, incomplete];
+
+        let result = get_report(
+            &games,
+            &ReportType::Leaderboard,
+            &ReportFormat::Text,
+            &RankBy::Score,
+            false,
+        )
+        .unwrap();
+        let expected = concat!(
+            "╭────────────┬────────────┬───────┬───────┬────────┬──────────┬──────────────┬────────────┬──────┬───────┬───────────────╮\n",
+            "│ Rank       │   Player   │ Games │ Frags │ Deaths │ Suicides │ World deaths │ Team kills │ K/D  │ Score │ Kills by mean │\n",
+            "│ (by Score) │            │       │       │        │          │              │            │      │       │ (Mean: Kills) │\n",
+            "├────────────┼────────────┼───────┼───────┼────────┼──────────┼──────────────┼────────────┼──────┼───────┼───────────────┤\n",
+            "│     1      │ Isgalamido │   1   │   3   │   0    │    0     │      0       │     0      │ 3.00 │   3   │  Railgun: 2   │\n",
+            "│            │            │       │       │        │          │              │            │      │       │  Rocket: 1    │\n",
+            "├────────────┼────────────┼───────┼───────┼────────┼──────────┼──────────────┼────────────┼──────┼───────┼───────────────┤\n",
+            "│     2      │    Zeh     │   2   │   4   │   0    │    0     │      1       │     0      │ 4.00 │   3   │   No kills    │\n",
+            "├────────────┼────────────┼───────┼───────┼────────┼──────────┼──────────────┼────────────┼──────┼───────┼───────────────┤\n",
+            "│     3      │    Mal     │   1   │   0   │   0    │    0     │      0       │     0      │ 0.00 │   0   │   No kills    │\n",
+            "╰────────────┴────────────┴───────┴───────┴────────┴──────────┴──────────────┴────────────┴──────┴───────┴───────────────╯",
+        );
+        assert_eq!(result.to_string(), expected);
+
+        let result = get_report(
+            &games,
+            &ReportType::Leaderboard,
+            &ReportFormat::Json,
+            &RankBy::Frags,
+            true,
+        )
+        .unwrap();
+        let json_report: JsonReport = serde_json::from_str(&result.to_string()).unwrap();
+        assert_eq!(json_report.games.len(), 3);
+        assert_eq!(json_report.games[0].players, None);
+        let leaderboard = json_report.leaderboard.unwrap();
+        let summary: Vec<(&str, u32, u32, i32)> = leaderboard
+            .iter()
+            .map(|standing| {
+                (
+                    standing.name.as_str(),
+                    standing.games,
+                    standing.stats.frags,
+                    standing.stats.score,
+                )
+            })
+            .collect();
+        assert_eq!(
+            summary,
+            vec![("Mal", 2, 9, 9), ("Zeh", 2, 4, 3), ("Isgalamido", 1, 3, 3)]
+        );
+        assert_eq!(
+            leaderboard[2].kills_by_means,
+            BTreeMap::from([(MeanDeath::Railgun, 2), (MeanDeath::Rocket, 1)])
+        );
+
+        let result = get_report(
+            &games,
+            &ReportType::Leaderboard,
+            &ReportFormat::Html,
+            &RankBy::Score,
+            true,
+        )
+        .unwrap()
+        .to_string();
+        assert!(result.contains("Kills by mean"));
+        assert!(result.contains("Isgalamido"));
+    }
+
+    #[test]
+    fn test_rank_by_metric() {
+        let mut game = Game::default();
+        for (client_id, (name, frags, deaths, suicides)) in
+            (0_u32..).zip([("Isgalamido", 6, 4, 3), ("Zeh", 4, 1, 0), ("Mal", 1, 4, 0)])
+        {
+            game.players_data.insert(
+                client_id,
+                PlayerData {
+                    name: name.to_owned(),
+                    frags,
+                    deaths,
+                    suicides,
+                    ..Default::default()
+                },
+            );
+        }
+
+        let ranked = |rank_by: &RankBy| {
+            let mut players_data = game.players_by_identity();
+            sort_players(&mut players_data, rank_by, &game.scoring);
+            format_player_rank(&game, &players_data, rank_by)
+        };
+        assert_eq!(
+            ranked(&RankBy::Score),
+            "\nZeh: 4\n\nIsgalamido: 3\n\nMal: 1\n"
+        );
+        assert_eq!(
+            ranked(&RankBy::Frags),
+            "\nIsgalamido: 6\n\nZeh: 4\n\nMal: 1\n"
+        );
+        assert_eq!(
+            ranked(&RankBy::Suicides),
+            "\nIsgalamido: 3\n\nMal: 0\n\nZeh: 0\n"
+        );
+        assert_eq!(
+            ranked(&RankBy::Kd),
+            "\nZeh: 4.00\n\nIsgalamido: 1.50\n\nMal: 0.25\n"
+        );
+
+        let mut builder = Builder::default();
+        populate_table_headers(&mut builder, &ReportType::PlayerRank, &RankBy::Kd);
+        assert!(builder.build().to_string().contains("(Player: K/D)"));
+
+        let json_game = JsonGame::from_game(&game, 1, &ReportType::PlayerRank, &RankBy::Frags);
+        assert_eq!(
+            json_game.ranking,
+            Some(vec![
+                "Isgalamido".to_owned(),
+                "Zeh".to_owned(),
+                "Mal".to_owned()
+            ])
+        );
+        assert_eq!(json_game.player_stats.unwrap()["Isgalamido"].suicides, 3);
+    }
+
+    #[test]
+    fn test_format_player_rank_tournament_without_opponent() {
+        let game = game_with_players(1, &[("Isgalamido", 2, Team::Free)]);
+        let players_data = game.players_by_identity();
+        assert_eq!(
+            format_player_rank(&game, &players_data, &RankBy::Score),
+            "\nIsgalamido: 2\n"
+        );
+    }
+
     proptest! {
         #[test]
         fn test_populate_table_headers(
             report_type in report_type(),
         ) {
             let mut builder = Builder::default();
-            populate_table_headers(&mut builder, &report_type);
+            populate_table_headers(&mut builder, &report_type, &RankBy::Score);
             let table = builder.build();
             let table_str = table.to_string();
             assert!(!table_str.is_empty());
@@ -552,19 +2450,19 @@ mod tests {
     fn test_simple_populate_table_headers() {
         let report_type = ReportType::All;
         let mut builder = Builder::default();
-        populate_table_headers(&mut builder, &report_type);
+        populate_table_headers(&mut builder, &report_type, &RankBy::Score);
         let mut table = builder.build();
         table.with(Style::modern_rounded());
         let table_str = table.to_string();
         assert!(!table_str.is_empty());
 
         let expected = concat!(
-            "╭──┬──────────────────┬─────────────────┬────────────────╮\n",
-            "│  │                  │                 │                │\n",
-            "│  │ Total game kills │ Kill Rank       │ Death Causes   │\n",
-            "│  │                  │ (Player: Score) │ (Cause: Count) │\n",
-            "│  │                  │                 │                │\n",
-            "╰──┴──────────────────┴─────────────────┴────────────────╯",
+            "╭──┬──────────────────┬───────────────┬─────────────────┬────────────────╮\n",
+            "│  │                  │               │                 │                │\n",
+            "│  │ Total game kills │ Outcome       │ Kill Rank       │ Death Causes   │\n",
+            "│  │                  │ (Winner, End) │ (Player: Score) │ (Cause: Count) │\n",
+            "│  │                  │               │                 │                │\n",
+            "╰──┴──────────────────┴───────────────┴─────────────────┴────────────────╯",
         );
 
         assert_eq!(table_str, expected);
@@ -0,0 +1,259 @@
+use crate::quake3_data::PlayerData;
+use clap::ValueEnum;
+use serde::{Deserialize, Serialize};
+use std::{cmp::Ordering, fmt::Display, path::Path};
+
+#[derive(Clone, Copy, Debug, Default, ValueEnum, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+/// The scoring presets, one per server mod
+/// only Quake 3 (baseq3): the OSP and CPMA presets are out of scope,
+/// their rules are set with a scoring config, see `ScoringRules::from_config`
+pub enum ScoringPreset {
+    #[default]
+    /// The Quake 3 (baseq3) scoring
+    Quake3,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+/// The points a player gets for each kind of kill it is part of
+///
+/// The score of a player is the sum of the points of all its kills and deaths,
+/// see `ScoringRules::score`
+pub struct ScoringRules {
+    /// Points for killing an enemy
+    pub frag: i32,
+    /// Points for killing itself
+    pub suicide: i32,
+    /// Points for being killed by the world (e.g. falling)
+    pub world_death: i32,
+    /// Points for killing a teammate, in team games
+    pub team_kill: i32,
+    /// Points for every death, on top of the suicide and world death points
+    pub death: i32,
+}
+
+impl Default for ScoringRules {
+    fn default() -> Self {
+        Self::from(ScoringPreset::default())
+    }
+}
+
+impl From<ScoringPreset> for ScoringRules {
+    /// Returns the rules of a preset
+    fn from(preset: ScoringPreset) -> Self {
+        match preset {
+            ScoringPreset::Quake3 => Self {
+                frag: 1,
+                suicide: -1,
+                world_death: -1,
+                team_kill: -1,
+                death: 0,
+            },
+        }
+    }
+}
+
+impl ScoringRules {
+    /// Returns the score of a player following these rules
+    /// saturating at the `i32` bounds
+    pub fn score(&self, player: &PlayerData) -> i32 {
+        let points = |count: u32, points: i32| i64::from(count).saturating_mul(i64::from(points));
+        let score = points(player.frags, self.frag)
+            .saturating_add(points(player.suicides, self.suicide))
+            .saturating_add(points(player.world_deaths, self.world_death))
+            .saturating_add(points(player.team_kills, self.team_kill))
+            .saturating_add(points(player.deaths, self.death));
+        i32::try_from(score).unwrap_or(if score < 0 { i32::MIN } else { i32::MAX })
+    }
+
+    /// Orders two players by score in descending order
+    /// The player with the best score is first
+    pub fn compare(&self, player: &PlayerData, other: &PlayerData) -> Ordering {
+        other.score(self).cmp(&player.score(self))
+    }
+
+    /// Loads the rules from a json config file
+    /// see `ScoringRules::from_config`
+    ///
+    /// can error if the file can't be read or isn't a valid config
+    pub fn from_config_file(path: &Path, preset: ScoringPreset) -> Result<Self, ScoringError> {
+        let config = std::fs::read_to_string(path).map_err(|err| ScoringError::Io(err.kind()))?;
+        Self::from_config(&config, preset)
+    }
+
+    /// Builds the rules from a json config
+    /// starting from the `preset`, or the one named in the config,
+    /// and replacing the points the config sets:
+    /// ```json
+    /// { "preset": "quake3", "suicide": 0, "team_kill": -3 }
+    /// ```
+    ///
+    /// can error if the config isn't valid json or has unknown fields
+    pub fn from_config(config: &str, preset: ScoringPreset) -> Result<Self, ScoringError> {
+        let config: ScoringConfig = serde_json::from_str(config)
+            .map_err(|err| ScoringError::InvalidConfig(err.to_string()))?;
+        let rules = Self::from(config.preset.unwrap_or(preset));
+
+        Ok(Self {
+            frag: config.frag.unwrap_or(rules.frag),
+            suicide: config.suicide.unwrap_or(rules.suicide),
+            world_death: config.world_death.unwrap_or(rules.world_death),
+            team_kill: config.team_kill.unwrap_or(rules.team_kill),
+            death: config.death.unwrap_or(rules.death),
+        })
+    }
+}
+
+#[derive(Debug, Default, Deserialize)]
+#[serde(deny_unknown_fields)]
+/// The scoring config file, every field is optional
+struct ScoringConfig {
+    /// The preset to start from
+    preset: Option<ScoringPreset>,
+    /// Points for killing an enemy
+    frag: Option<i32>,
+    /// Points for killing itself
+    suicide: Option<i32>,
+    /// Points for being killed by the world
+    world_death: Option<i32>,
+    /// Points for killing a teammate
+    team_kill: Option<i32>,
+    /// Points for every death
+    death: Option<i32>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+/// The errors that can occur while loading the scoring rules
+pub enum ScoringError {
+    /// When the config file can't be read
+    Io(std::io::ErrorKind),
+    /// When the config isn't valid json or has unknown fields
+    InvalidConfig(String),
+}
+
+impl Display for ScoringError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Io(err) => write!(f, "IoError: {err}"),
+            Self::InvalidConfig(err) => write!(f, "Invalid scoring config: {err}"),
+        }
+    }
+}
+
+#[cfg(test)]
+#[allow(
+    clippy::default_numeric_fallback,
+    clippy::panic,
+    clippy::shadow_unrelated,
+    clippy::unwrap_used
+)]
+// the tests unwrap the configs and compare the rules with plain literals
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_score_follows_rules() {
+        let player = PlayerData {
+            frags: 10,
+            deaths: 6,
+            suicides: 2,
+            world_deaths: 1,
+            team_kills: 1,
+            ..Default::default()
+        };
+        assert_eq!(ScoringRules::default().score(&player), 6);
+
+        let rules = ScoringRules {
+            frag: 2,
+            suicide: 0,
+            world_death: 0,
+            team_kill: -5,
+            death: -1,
+        };
+        assert_eq!(rules.score(&player), 9);
+    }
+
+    #[test]
+    fn test_score_saturates() {
+        let player = PlayerData {
+            frags: u32::MAX,
+            ..Default::default()
+        };
+        let rules = ScoringRules {
+            frag: 3,
+            ..Default::default()
+        };
+        assert_eq!(rules.score(&player), i32::MAX);
+    }
+
+    #[test]
+    fn test_presets() {
+        let rules = ScoringRules::from(ScoringPreset::Quake3);
+        assert_eq!(rules.frag, 1);
+        assert_eq!(rules.suicide, -1);
+        assert_eq!(rules.world_death, -1);
+        assert_eq!(rules.team_kill, -1);
+        assert_eq!(rules.death, 0);
+        assert_eq!(
+            ScoringRules::default(),
+            ScoringRules::from(ScoringPreset::Quake3)
+        );
+    }
+
+    #[test]
+    fn test_from_config() {
+        let rules =
+            ScoringRules::from_config(r#"{ "suicide": 0, "death": -1 }"#, ScoringPreset::Quake3)
+                .unwrap();
+        assert_eq!(
+            rules,
+            ScoringRules {
+                suicide: 0,
+                death: -1,
+                ..ScoringRules::default()
+            }
+        );
+
+        let rules = ScoringRules::from_config(
+            r#"{ "preset": "quake3", "team_kill": -3 }"#,
+            ScoringPreset::Quake3,
+        )
+        .unwrap();
+        assert_eq!(
+            rules,
+            ScoringRules {
+                team_kill: -3,
+                ..ScoringRules::default()
+            }
+        );
+
+        assert_eq!(
+            ScoringRules::from_config("{}", ScoringPreset::Quake3).unwrap(),
+            ScoringRules::default()
+        );
+    }
+
+    #[test]
+    fn test_from_invalid_config() {
+        for config in [
+            "",
+            "[]",
+            r#"{ "frags": 2 }"#,
+            r#"{ "preset": "cpma" }"#,
+            r#"{ "frag": "2" }"#,
+        ] {
+            match ScoringRules::from_config(config, ScoringPreset::Quake3) {
+                Err(ScoringError::InvalidConfig(_)) => {}
+                other => panic!("{config} should be invalid, got {other:?}"),
+            }
+        }
+    }
+
+    #[test]
+    fn test_from_missing_config_file() {
+        assert_eq!(
+            ScoringRules::from_config_file(Path::new("does/not/exist.json"), ScoringPreset::Quake3),
+            Err(ScoringError::Io(std::io::ErrorKind::NotFound))
+        );
+    }
+}
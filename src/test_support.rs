@@ -0,0 +1,47 @@
+use std::{
+    fs::OpenOptions,
+    io::Write,
+    path::{Path, PathBuf},
+};
+
+/// The start of a game still running on `q3dm6`, its player connected
+pub const RUNNING_GAME: &str = "  0:00 InitGame: \\mapname\\q3dm6\n\
+    0:01 ClientConnect: 3\n\
+    0:02 ClientUserinfoChanged: 3 n\\Zeh\\t\\0\n";
+
+/// Returns a finished game on the map, with a single kill
+pub fn game_on(map_name: &str) -> String {
+    format!(
+        "  0:00 InitGame: \\mapname\\{map_name}\n\
+        0:01 ClientConnect: 2\n\
+        0:02 ClientUserinfoChanged: 2 n\\Isgalamido\\t\\0\n\
+        0:10 Kill: 1022 2 22: <world> killed Isgalamido by MOD_TRIGGER_HURT\n\
+        0:20 ShutdownGame:\n"
+    )
+}
+
+/// Returns a log with a finished game on `q3dm17` followed by the `RUNNING_GAME`
+pub fn started_log() -> String {
+    format!("{}{RUNNING_GAME}", game_on("q3dm17"))
+}
+
+/// Returns a path for a log file of a test, removing any previous file
+/// the module of the test keeps the paths of the modules apart
+pub fn log_path(module: &str, name: &str) -> PathBuf {
+    let path = std::env::temp_dir().join(format!(
+        "wolf_quake_{module}_{}_{name}.log",
+        std::process::id()
+    ));
+    std::fs::remove_file(&path).ok();
+    path
+}
+
+/// Appends the content to the log file, creating it if needed
+pub fn append(path: &Path, content: &str) {
+    let mut file = OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(path)
+        .unwrap();
+    file.write_all(content.as_bytes()).unwrap();
+}